@@ -0,0 +1,186 @@
+//! Benchmarks for the core matching and rewriting operations
+//!
+//! Run with `cargo bench`. The no-match benchmarks cover the common case of
+//! a rule which does not apply, which should cost no more than the regex
+//! search and never allocate.
+
+use std::hint::black_box;
+
+use bytes::Bytes;
+use criterion::{Criterion, criterion_group, criterion_main};
+use http::Request;
+use http_rewriter::config::{
+    ConditionConfig, ConditionType, ConditionalRewriterConfig, Rewriter as ConfigRewriter,
+    RewriterConfig, RewriterType,
+};
+use http_rewriter::{
+    BloomPathCondition, Condition, HeaderCondition, PathCondition, PathRewriter, Rewriter,
+    RouteTable,
+};
+
+fn request(uri: &str) -> Request<Bytes> {
+    Request::builder()
+        .uri(uri)
+        .header("Accept", "application/json")
+        .header("User-Agent", "bench/1.0")
+        .body(Bytes::new())
+        .unwrap()
+}
+
+fn path_rewriter(c: &mut Criterion) {
+    let rewriter = PathRewriter::new(r"^/api/v1/(.*)$", "/v2/$1").unwrap();
+    let mut group = c.benchmark_group("path_rewriter");
+
+    group.bench_function("match", |b| {
+        b.iter_batched(
+            || request("/api/v1/users/42?fields=name"),
+            |request| rewriter.rewrite(black_box(request)).unwrap(),
+            criterion::BatchSize::SmallInput,
+        )
+    });
+
+    // Requests the pattern does not match should pass through without
+    // allocating a new path or URI
+    group.bench_function("no_match", |b| {
+        b.iter_batched(
+            || request("/static/app.js"),
+            |request| rewriter.rewrite(black_box(request)).unwrap(),
+            criterion::BatchSize::SmallInput,
+        )
+    });
+
+    group.bench_function("no_match_in_place", |b| {
+        let mut request = request("/static/app.js");
+        b.iter(|| rewriter.rewrite_in_place(black_box(&mut request)).unwrap())
+    });
+
+    group.finish();
+}
+
+fn header_condition(c: &mut Criterion) {
+    let condition = HeaderCondition::new("Accept", "json").unwrap();
+    let matching = request("/api/users");
+    let missing = Request::builder()
+        .uri("/api/users")
+        .body(Bytes::new())
+        .unwrap();
+    let mut group = c.benchmark_group("header_condition");
+
+    group.bench_function("match", |b| {
+        b.iter(|| condition.matches(black_box(&matching)))
+    });
+    group.bench_function("missing_header", |b| {
+        b.iter(|| condition.matches(black_box(&missing)))
+    });
+
+    group.finish();
+}
+
+fn pipeline(c: &mut Criterion) {
+    let rules: Vec<ConditionalRewriterConfig> = (0..100)
+        .map(|i| {
+            let prefix = format!("^/service-{i}/");
+            let target = format!("/backends/{i}/");
+            ConditionalRewriterConfig::default()
+                .with_condition(ConditionConfig::new(ConditionType::Path, &[&prefix]))
+                .with_rewriter(RewriterConfig::new(RewriterType::Path, &[&prefix, &target]))
+        })
+        .collect();
+    let pipeline = ConfigRewriter::try_from(rules).unwrap();
+    let mut group = c.benchmark_group("pipeline_100_rules");
+
+    group.bench_function("last_rule_matches", |b| {
+        b.iter_batched(
+            || request("/service-99/users"),
+            |request| pipeline.rewrite(black_box(request)).unwrap(),
+            criterion::BatchSize::SmallInput,
+        )
+    });
+    group.bench_function("no_match", |b| {
+        b.iter_batched(
+            || request("/unrouted/users"),
+            |request| pipeline.rewrite(black_box(request)).unwrap(),
+            criterion::BatchSize::SmallInput,
+        )
+    });
+
+    group.finish();
+}
+
+fn route_table(c: &mut Criterion) {
+    let prefixes: Vec<String> = (0..10_000).map(|i| format!("/service-{i}/")).collect();
+    let conditions: Vec<PathCondition> = prefixes
+        .iter()
+        .map(|prefix| PathCondition::new(format!("^{prefix}")).unwrap())
+        .collect();
+    let table: RouteTable = prefixes.iter().collect();
+    let last = request("/service-9999/users");
+    let unrouted = request("/unrouted/users");
+    let mut group = c.benchmark_group("prefixes_10k");
+
+    // Checking each prefix pattern in turn, as a list of rules would
+    group.bench_function("regex_last_matches", |b| {
+        b.iter(|| {
+            conditions
+                .iter()
+                .position(|condition| condition.matches(black_box(&last)))
+        })
+    });
+    group.bench_function("regex_no_match", |b| {
+        b.iter(|| {
+            conditions
+                .iter()
+                .position(|condition| condition.matches(black_box(&unrouted)))
+        })
+    });
+    group.bench_function("route_table_last_matches", |b| {
+        b.iter(|| table.longest_match(black_box(last.uri().path())))
+    });
+    group.bench_function("route_table_no_match", |b| {
+        b.iter(|| table.longest_match(black_box(unrouted.uri().path())))
+    });
+
+    group.finish();
+}
+
+fn bloom_paths(c: &mut Criterion) {
+    let paths: Vec<String> = (0..1_000_000).map(|i| format!("/blocked/{i}")).collect();
+    let condition = BloomPathCondition::new(&paths);
+    let listed = request("/blocked/999999");
+    let unlisted = request("/api/v1/users/42");
+    let mut group = c.benchmark_group("bloom_paths_1m");
+
+    group.bench_function("listed", |b| {
+        b.iter(|| condition.matches(black_box(&listed)))
+    });
+    group.bench_function("unlisted", |b| {
+        b.iter(|| condition.matches(black_box(&unlisted)))
+    });
+
+    group.finish();
+}
+
+fn parts_round_trip(c: &mut Criterion) {
+    // The baseline every rewriter pays when it takes a request apart
+    c.bench_function("into_parts_from_parts", |b| {
+        b.iter_batched(
+            || request("/api/v1/users/42?fields=name"),
+            |request| {
+                let (parts, body) = black_box(request).into_parts();
+                Request::from_parts(black_box(parts), body)
+            },
+            criterion::BatchSize::SmallInput,
+        )
+    });
+}
+
+criterion_group!(
+    benches,
+    path_rewriter,
+    header_condition,
+    pipeline,
+    route_table,
+    bloom_paths,
+    parts_round_trip
+);
+criterion_main!(benches);
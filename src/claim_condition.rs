@@ -0,0 +1,137 @@
+//! Matching on structured claims stored in the request extensions
+//!
+//! This module provides the [`ClaimCondition`] type, which looks up a value
+//! in a [`serde_json::Value`] stored in the request extensions, such as the
+//! decoded claims of a JWT, and matches it against a regular expression. This
+//! allows gating rewrites on a claim without writing a condition type for
+//! every claim.
+//!
+//! This module is only available with the `serde_json` feature enabled.
+//!
+//! # Examples
+//!
+//! ```
+//! use http_rewriter::{ClaimCondition, PathRewriter, Rewriter, RewriterExt};
+//! use http::Request;
+//! use serde_json::json;
+//!
+//! // Send admins to the admin backend
+//! let rewriter = PathRewriter::new("^/dashboard", "/admin/dashboard")
+//!     .unwrap()
+//!     .when(ClaimCondition::new("/role", "^admin$").unwrap());
+//!
+//! let mut request = Request::builder().uri("/dashboard").body(()).unwrap();
+//! request.extensions_mut().insert(json!({ "sub": "42", "role": "admin" }));
+//!
+//! let result = rewriter.rewrite(request).unwrap();
+//! assert_eq!(result.uri().path(), "/admin/dashboard");
+//! ```
+
+use http::Request;
+use regex::Regex;
+use serde_json::Value;
+
+use super::Condition;
+
+/// Condition that matches a value in a JSON document from the request extensions
+///
+/// The document is the [`serde_json::Value`] in the request extensions, which
+/// the server or an earlier middleware inserts, for example after verifying
+/// a token. The value is found with a JSON Pointer as defined by RFC 6901,
+/// such as `/role` or `/org/permissions/0`, where the empty pointer refers to
+/// the whole document.
+///
+/// Strings are matched without their quotes. Numbers, booleans and `null` are
+/// matched as written in JSON, and arrays and objects as their compact JSON
+/// text. Requests without a document, or whose document has nothing at the
+/// pointer, never match.
+///
+/// # Examples
+///
+/// ```
+/// use http_rewriter::{ClaimCondition, Condition};
+/// use http::Request;
+/// use serde_json::json;
+///
+/// let verified = ClaimCondition::new("/email_verified", "^true$").unwrap();
+///
+/// let mut request = Request::builder().uri("/").body(()).unwrap();
+/// request.extensions_mut().insert(json!({ "email_verified": true }));
+/// assert!(verified.matches(&request));
+///
+/// // No claims, no match
+/// let request = Request::builder().uri("/").body(()).unwrap();
+/// assert!(!verified.matches(&request));
+/// ```
+#[derive(Debug, Clone)]
+pub struct ClaimCondition {
+    pointer: String,
+    pattern: Regex,
+}
+
+impl ClaimCondition {
+    /// Create a new claim condition
+    ///
+    /// # Arguments
+    ///
+    /// * `pointer` - JSON Pointer to the value to match, such as `/role`
+    /// * `pattern` - A regular expression pattern to match against the value
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the pattern is not a valid regular expression
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use http_rewriter::ClaimCondition;
+    ///
+    /// // Match tokens issued to any tenant of example.com
+    /// let condition = ClaimCondition::new("/iss", r"\.example\.com$").unwrap();
+    /// ```
+    pub fn new(pointer: impl Into<String>, pattern: impl AsRef<str>) -> Result<Self, regex::Error> {
+        Ok(Self::from_regex(pointer, Regex::new(pattern.as_ref())?))
+    }
+
+    /// Create a new claim condition from an already compiled regular expression
+    ///
+    /// # Arguments
+    ///
+    /// * `pointer` - JSON Pointer to the value to match, such as `/role`
+    /// * `pattern` - Compiled regular expression to match against the value
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use http_rewriter::ClaimCondition;
+    /// use regex::Regex;
+    ///
+    /// let condition = ClaimCondition::from_regex("/scope", Regex::new(r"\bwrite\b").unwrap());
+    /// ```
+    pub fn from_regex(pointer: impl Into<String>, pattern: Regex) -> Self {
+        Self {
+            pointer: pointer.into(),
+            pattern,
+        }
+    }
+}
+
+impl Condition for ClaimCondition {
+    fn matches<B>(&self, request: &Request<B>) -> bool {
+        let Some(value) = request
+            .extensions()
+            .get::<Value>()
+            .and_then(|claims| claims.pointer(&self.pointer))
+        else {
+            return false;
+        };
+        match value {
+            Value::String(value) => self.pattern.is_match(value),
+            value => self.pattern.is_match(&value.to_string()),
+        }
+    }
+
+    fn patterns(&self) -> Vec<&str> {
+        vec![self.pattern.as_str()]
+    }
+}
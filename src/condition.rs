@@ -33,10 +33,17 @@
 //! assert!(combined.matches(&request));
 //! ```
 
+use std::borrow::Cow;
+use std::collections::HashSet;
 use std::fmt::{Debug, Formatter, Result as FmtResult};
+use std::hash::{BuildHasher, RandomState};
+use std::marker::PhantomData;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
 
-use http::Request;
-use http_handler::RequestExt;
+use http::{Extensions, HeaderMap, Method, Request, Uri, Version};
+use http_handler::{DocumentRoot, RequestExt};
 use regex::Regex;
 
 /// Trait for types that can match against HTTP requests
@@ -74,6 +81,15 @@ pub trait Condition: Send + Sync {
     /// Returns `true` if the request matches this condition's criteria,
     /// `false` otherwise.
     fn matches<B>(&self, request: &Request<B>) -> bool;
+
+    /// Collect the regular expression patterns used by the condition
+    ///
+    /// Conditions built from other conditions, such as [`GroupCondition`],
+    /// return the patterns of all of them, in order. Conditions which do not
+    /// use a regular expression return no patterns, which is the default.
+    fn patterns(&self) -> Vec<&str> {
+        Vec::new()
+    }
 }
 
 /// Condition that matches request paths against a regular expression pattern
@@ -120,6 +136,8 @@ pub trait Condition: Send + Sync {
 #[derive(Debug, Clone)]
 pub struct PathCondition {
     pattern: Regex,
+    decoded: bool,
+    ignore_matrix_params: bool,
 }
 
 impl PathCondition {
@@ -148,16 +166,479 @@ impl PathCondition {
     /// assert!(PathCondition::new("[unclosed").is_err());
     /// ```
     pub fn new(pattern: impl AsRef<str>) -> Result<Self, regex::Error> {
+        Ok(Self::from_regex(Regex::new(pattern.as_ref())?))
+    }
+
+    /// Create a new path condition from an already compiled regular expression
+    ///
+    /// This allows sharing a compiled pattern, or tuning it with
+    /// [`RegexBuilder`](regex::RegexBuilder), for example to set size limits.
+    ///
+    /// # Arguments
+    ///
+    /// * `pattern` - Compiled regular expression to match against request paths
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use http_rewriter::PathCondition;
+    /// use regex::RegexBuilder;
+    ///
+    /// let pattern = RegexBuilder::new("^/admin/")
+    ///     .size_limit(1 << 16)
+    ///     .build()
+    ///     .unwrap();
+    /// let condition = PathCondition::from_regex(pattern);
+    /// ```
+    pub fn from_regex(pattern: Regex) -> Self {
+        Self {
+            pattern,
+            decoded: false,
+            ignore_matrix_params: false,
+        }
+    }
+
+    /// Create a new path condition which matches against the percent-decoded path
+    ///
+    /// By default paths are matched as they appear in the URI, so `%20` must be
+    /// written as `%20` in the pattern. A decoded path condition decodes the
+    /// path first, so patterns can be written against the literal characters.
+    ///
+    /// Decoding is lossy: distinct encodings such as `/a%2Fb` and `/a/b` decode
+    /// to the same path. Malformed escapes are kept as-is. If the decoded bytes
+    /// are not valid UTF-8 the condition does not match.
+    ///
+    /// # Arguments
+    ///
+    /// * `pattern` - A regular expression pattern to match against decoded request paths
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the pattern is not a valid regular expression
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use http_rewriter::{Condition, PathCondition};
+    /// use http::Request;
+    ///
+    /// let condition = PathCondition::new_decoded("^/files/a b$").unwrap();
+    ///
+    /// let request = Request::builder()
+    ///     .uri("/files/a%20b")
+    ///     .body(())
+    ///     .unwrap();
+    /// assert!(condition.matches(&request));
+    ///
+    /// // Invalid UTF-8 after decoding never matches
+    /// let condition = PathCondition::new_decoded(".*").unwrap();
+    /// let request = Request::builder()
+    ///     .uri("/files/%FF")
+    ///     .body(())
+    ///     .unwrap();
+    /// assert!(!condition.matches(&request));
+    /// ```
+    pub fn new_decoded(pattern: impl AsRef<str>) -> Result<Self, regex::Error> {
         Ok(Self {
-            pattern: Regex::new(pattern.as_ref())?,
+            decoded: true,
+            ..Self::new(pattern)?
         })
     }
+
+    /// Set whether matrix parameters are removed from the path before matching
+    ///
+    /// Paths may carry parameters on each segment, as in `/books;v=2/42`.
+    /// RFC 3986 leaves segments opaque, but notes that `;` is commonly used to
+    /// delimit parameters applying to a segment, which is the convention
+    /// followed here: everything from the first `;` of a segment to the end of
+    /// it is removed, so patterns are matched against `/books/42`. Percent
+    /// encoded semicolons (`%3B`) are not delimiters. This is applied before
+    /// percent-decoding for [`new_decoded`](PathCondition::new_decoded)
+    /// conditions.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use http_rewriter::{Condition, PathCondition};
+    /// use http::Request;
+    ///
+    /// let request = Request::builder()
+    ///     .uri("/a;v=1/b")
+    ///     .body(())
+    ///     .unwrap();
+    ///
+    /// let condition = PathCondition::new("^/a/b$").unwrap();
+    /// assert!(!condition.matches(&request));
+    ///
+    /// let condition = condition.with_ignore_matrix_params(true);
+    /// assert!(condition.matches(&request));
+    /// ```
+    pub fn with_ignore_matrix_params(mut self, ignore_matrix_params: bool) -> Self {
+        self.ignore_matrix_params = ignore_matrix_params;
+        self
+    }
 }
 
 impl Condition for PathCondition {
+    fn matches<B>(&self, request: &Request<B>) -> bool {
+        let path = request.uri().path();
+        let path = if self.ignore_matrix_params {
+            strip_matrix_params(path)
+        } else {
+            Cow::Borrowed(path)
+        };
+        if !self.decoded {
+            return self.pattern.is_match(&path);
+        }
+        percent_decode(&path).is_some_and(|path| self.pattern.is_match(&path))
+    }
+
+    fn patterns(&self) -> Vec<&str> {
+        vec![self.pattern.as_str()]
+    }
+}
+
+/// Remove the matrix parameters of each segment of a path, as in `/a;v=1/b`
+///
+/// Everything from the first `;` of a segment to the end of the segment is
+/// removed. Paths without a `;` are returned as they are.
+pub(crate) fn strip_matrix_params(path: &str) -> Cow<'_, str> {
+    if !path.contains(';') {
+        return Cow::Borrowed(path);
+    }
+    let segments: Vec<&str> = path
+        .split('/')
+        .map(|segment| segment.split_once(';').map_or(segment, |(name, _)| name))
+        .collect();
+    Cow::Owned(segments.join("/"))
+}
+
+/// Percent-decode a string, returning `None` if the result is not valid UTF-8
+///
+/// Malformed escapes, such as a `%` not followed by two hex digits, are kept
+/// as-is.
+pub(crate) fn percent_decode(input: &str) -> Option<String> {
+    let bytes = input.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%'
+            && let Some(hex) = input.get(i + 1..i + 3)
+            // `from_str_radix` alone would also take a sign, as in `%+1`
+            && hex.bytes().all(|b| b.is_ascii_hexdigit())
+            && let Ok(byte) = u8::from_str_radix(hex, 16)
+        {
+            decoded.push(byte);
+            i += 3;
+        } else {
+            decoded.push(bytes[i]);
+            i += 1;
+        }
+    }
+    String::from_utf8(decoded).ok()
+}
+
+/// Decode a query parameter name or value, treating `+` as a space
+pub(crate) fn decode_query_component(input: &str) -> Option<String> {
+    percent_decode(&input.replace('+', " "))
+}
+
+/// Condition that matches request paths against a shell-style glob
+///
+/// The glob must match the whole path. It supports these wildcards:
+///
+/// - `*` matches any run of characters within a single path segment, so it
+///   never crosses a `/`
+/// - `?` matches exactly one character other than `/`
+/// - `**` matches any run of characters, including `/`. When followed by a
+///   `/` it matches zero or more whole segments, so `/assets/**/*.css` matches
+///   both `/assets/site.css` and `/assets/css/site.css`
+///
+/// Every other character, including `[`, `]`, `{` and `}`, matches itself.
+/// The path is matched as it appears in the URI, without percent-decoding.
+///
+/// # Examples
+///
+/// ```
+/// use http_rewriter::{Condition, PathGlobCondition};
+/// use http::Request;
+///
+/// let styles = PathGlobCondition::new("/assets/**/*.css").unwrap();
+/// let top_level = PathGlobCondition::new("/assets/*.css").unwrap();
+///
+/// let request = Request::builder()
+///     .uri("/assets/themes/dark/site.css")
+///     .body(())
+///     .unwrap();
+/// assert!(styles.matches(&request));
+/// assert!(!top_level.matches(&request));
+/// ```
+#[derive(Debug, Clone)]
+pub struct PathGlobCondition {
+    glob: String,
+    pattern: Regex,
+}
+
+impl PathGlobCondition {
+    /// Create a new path glob condition
+    ///
+    /// # Arguments
+    ///
+    /// * `glob` - A glob pattern to match against request paths
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the regular expression compiled from the glob
+    /// exceeds the default size limits
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use http_rewriter::PathGlobCondition;
+    ///
+    /// // Any JavaScript file directly inside /static/
+    /// let condition = PathGlobCondition::new("/static/*.js").unwrap();
+    ///
+    /// // Any path below /docs/
+    /// let condition = PathGlobCondition::new("/docs/**").unwrap();
+    /// ```
+    pub fn new(glob: impl Into<String>) -> Result<Self, regex::Error> {
+        let glob = glob.into();
+        let pattern = Regex::new(&glob_to_regex(&glob))?;
+        Ok(Self::from_parts(glob, pattern))
+    }
+
+    /// Create a path glob condition from a glob and its compiled regex
+    pub(crate) fn from_parts(glob: String, pattern: Regex) -> Self {
+        Self { glob, pattern }
+    }
+
+    /// Get the glob this condition was created from
+    pub fn glob(&self) -> &str {
+        &self.glob
+    }
+}
+
+impl Condition for PathGlobCondition {
     fn matches<B>(&self, request: &Request<B>) -> bool {
         self.pattern.is_match(request.uri().path())
     }
+
+    fn patterns(&self) -> Vec<&str> {
+        vec![self.pattern.as_str()]
+    }
+}
+
+/// Condition that matches request paths in a large set of exact paths
+///
+/// This is meant for deny lists with up to millions of entries, such as
+/// blocked paths, where one pattern per entry would be far too slow and
+/// large. The paths are kept in a hash set, in front of which sits a bloom
+/// filter: a bit array about ten bits per path in size which answers "surely
+/// not in the set" for most paths which are not, by reading a handful of
+/// bits. Only paths the filter lets through are looked up in the set.
+///
+/// The filter only ever saves work on paths which are not listed. A path is
+/// reported as matching only once it is found in the exact set, so the
+/// condition never matches a path which is not listed, unlike a bare bloom
+/// filter, which has false positives.
+///
+/// Paths are compared byte for byte against the raw request path, without
+/// percent-decoding or normalization, and the query is not part of the path.
+///
+/// # Examples
+///
+/// ```
+/// use http_rewriter::{BloomPathCondition, Condition};
+/// use http::Request;
+///
+/// let blocked = BloomPathCondition::new(["/wp-login.php", "/xmlrpc.php", "/.env"]);
+///
+/// let request = Request::builder().uri("/.env").body(()).unwrap();
+/// assert!(blocked.matches(&request));
+///
+/// let request = Request::builder().uri("/index.html").body(()).unwrap();
+/// assert!(!blocked.matches(&request));
+/// ```
+#[derive(Clone)]
+pub struct BloomPathCondition {
+    bits: Vec<u64>,
+    state: RandomState,
+    paths: HashSet<Box<str>>,
+}
+
+// Bits per path and hashes per lookup, for a false positive rate around 1%
+const BLOOM_BITS_PER_PATH: usize = 10;
+const BLOOM_HASHES: u64 = 7;
+
+impl BloomPathCondition {
+    /// Create a new condition matching any of the given paths
+    ///
+    /// # Arguments
+    ///
+    /// * `paths` - The exact request paths to match
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use http_rewriter::BloomPathCondition;
+    ///
+    /// let blocked = BloomPathCondition::new((0..1000).map(|i| format!("/spam/{i}")));
+    /// assert_eq!(blocked.len(), 1000);
+    /// ```
+    pub fn new<P: AsRef<str>>(paths: impl IntoIterator<Item = P>) -> Self {
+        let paths: HashSet<Box<str>> = paths.into_iter().map(|path| path.as_ref().into()).collect();
+        let words = (paths.len() * BLOOM_BITS_PER_PATH).div_ceil(64).max(1);
+        let mut condition = Self {
+            bits: vec![0; words],
+            state: RandomState::new(),
+            paths,
+        };
+        let positions: Vec<usize> = condition
+            .paths
+            .iter()
+            .flat_map(|path| condition.bit_positions(path))
+            .collect();
+        for position in positions {
+            condition.bits[position / 64] |= 1 << (position % 64);
+        }
+        condition
+    }
+
+    /// Check whether the path is in the set
+    pub fn contains(&self, path: &str) -> bool {
+        self.bit_positions(path)
+            .all(|position| self.bits[position / 64] & (1 << (position % 64)) != 0)
+            && self.paths.contains(path)
+    }
+
+    /// Get the number of distinct paths in the set
+    pub fn len(&self) -> usize {
+        self.paths.len()
+    }
+
+    /// Check whether the set holds no paths
+    pub fn is_empty(&self) -> bool {
+        self.paths.is_empty()
+    }
+
+    // Derive the filter bits of a path from one 64-bit hash, using its two
+    // halves as a base and a step
+    fn bit_positions(&self, path: &str) -> impl Iterator<Item = usize> + use<> {
+        let hash = self.state.hash_one(path);
+        let (base, step) = (hash as u32 as u64, (hash >> 32) | 1);
+        let size = (self.bits.len() * 64) as u64;
+        (0..BLOOM_HASHES).map(move |i| (base.wrapping_add(i.wrapping_mul(step)) % size) as usize)
+    }
+}
+
+impl Debug for BloomPathCondition {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        f.debug_struct("BloomPathCondition")
+            .field("len", &self.paths.len())
+            .field("filter_bits", &(self.bits.len() * 64))
+            .finish_non_exhaustive()
+    }
+}
+
+impl Condition for BloomPathCondition {
+    fn matches<B>(&self, request: &Request<B>) -> bool {
+        self.contains(request.uri().path())
+    }
+}
+
+/// Condition that matches on whether the request path ends with a slash
+///
+/// This is useful for canonicalization rules, which add or remove a trailing
+/// slash depending on which form a site uses. The root path `/` counts as
+/// having a trailing slash, so it matches `new(true)` and never `new(false)`.
+/// A rule adding a slash therefore leaves the root alone, and a
+/// [`PathRewriter`](crate::PathRewriter) removing one turns the root back into
+/// `/`.
+///
+/// # Examples
+///
+/// ```
+/// use http_rewriter::{Condition, TrailingSlashCondition};
+/// use http::Request;
+///
+/// let with_slash = TrailingSlashCondition::new(true);
+///
+/// let request = Request::builder().uri("/docs/").body(()).unwrap();
+/// assert!(with_slash.matches(&request));
+///
+/// let request = Request::builder().uri("/docs?page=2").body(()).unwrap();
+/// assert!(!with_slash.matches(&request));
+/// ```
+///
+/// ```
+/// use http_rewriter::{PathRewriter, Rewriter, RewriterExt, TrailingSlashCondition};
+/// use http::Request;
+///
+/// // Add a trailing slash to paths without one
+/// let rewriter = PathRewriter::new("$", "/")
+///     .unwrap()
+///     .when(TrailingSlashCondition::new(false));
+///
+/// let request = Request::builder().uri("/docs").body(()).unwrap();
+/// assert_eq!(rewriter.rewrite(request).unwrap().uri().path(), "/docs/");
+///
+/// let request = Request::builder().uri("/").body(()).unwrap();
+/// assert_eq!(rewriter.rewrite(request).unwrap().uri().path(), "/");
+/// ```
+#[derive(Debug, Clone)]
+pub struct TrailingSlashCondition {
+    present: bool,
+}
+
+impl TrailingSlashCondition {
+    /// Create a new trailing slash condition
+    ///
+    /// # Arguments
+    ///
+    /// * `present` - Whether to match paths with a trailing slash, or paths without one
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use http_rewriter::TrailingSlashCondition;
+    ///
+    /// // Match paths which do not end with a slash
+    /// let condition = TrailingSlashCondition::new(false);
+    /// ```
+    pub fn new(present: bool) -> Self {
+        Self { present }
+    }
+}
+
+impl Condition for TrailingSlashCondition {
+    fn matches<B>(&self, request: &Request<B>) -> bool {
+        request.uri().path().ends_with('/') == self.present
+    }
+}
+
+/// Translate a glob into an anchored regular expression
+pub(crate) fn glob_to_regex(glob: &str) -> String {
+    let mut regex = String::from("^");
+    let mut rest = glob;
+    while let Some(c) = rest.chars().next() {
+        if let Some(after) = rest.strip_prefix("**/") {
+            regex.push_str("(?:.*/)?");
+            rest = after;
+        } else if let Some(after) = rest.strip_prefix("**") {
+            regex.push_str(".*");
+            rest = after;
+        } else {
+            match c {
+                '*' => regex.push_str("[^/]*"),
+                '?' => regex.push_str("[^/]"),
+                _ => regex.push_str(&regex::escape(c.encode_utf8(&mut [0; 4]))),
+            }
+            rest = &rest[c.len_utf8()..];
+        }
+    }
+    regex.push('$');
+    regex
 }
 
 /// Condition that matches requests based on their HTTP method
@@ -257,9 +738,28 @@ impl MethodCondition {
     ///     .expect("CUSTOM is a valid method");
     /// ```
     pub fn new(method: impl AsRef<str>) -> Result<Self, regex::Error> {
-        Ok(Self {
-            method: Regex::new(method.as_ref())?,
-        })
+        Ok(Self::from_regex(Regex::new(method.as_ref())?))
+    }
+
+    /// Create a new method condition from an already compiled regular expression
+    ///
+    /// This allows sharing a compiled pattern, or tuning it with
+    /// [`RegexBuilder`](regex::RegexBuilder), for example to set size limits.
+    ///
+    /// # Arguments
+    ///
+    /// * `method` - Compiled regular expression to match against the request method
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use http_rewriter::MethodCondition;
+    /// use regex::Regex;
+    ///
+    /// let condition = MethodCondition::from_regex(Regex::new("^(GET|HEAD)$").unwrap());
+    /// ```
+    pub fn from_regex(method: Regex) -> Self {
+        Self { method }
     }
 }
 
@@ -267,6 +767,10 @@ impl Condition for MethodCondition {
     fn matches<B>(&self, request: &Request<B>) -> bool {
         self.method.is_match(request.method().as_str())
     }
+
+    fn patterns(&self) -> Vec<&str> {
+        vec![self.method.as_str()]
+    }
 }
 
 /// Condition that matches request headers against a regular expression pattern
@@ -274,6 +778,11 @@ impl Condition for MethodCondition {
 /// This condition checks if a specific header exists and its value matches
 /// a regular expression pattern. Header names are case-insensitive.
 ///
+/// When the header is repeated, a condition created with [`HeaderCondition::new`]
+/// matches if any of its values matches, while one created with
+/// [`HeaderCondition::new_all`] only matches if every value matches. Neither
+/// matches when the header is absent.
+///
 /// # Examples
 ///
 /// ```
@@ -308,7 +817,15 @@ impl Condition for MethodCondition {
 #[derive(Debug, Clone)]
 pub struct HeaderCondition {
     name: String,
-    pattern: Regex,
+    matcher: HeaderMatcher,
+    all: bool,
+}
+
+/// How a [`HeaderCondition`] compares header values
+#[derive(Debug, Clone)]
+enum HeaderMatcher {
+    Pattern(Regex),
+    Exact(String),
 }
 
 impl HeaderCondition {
@@ -338,25 +855,2421 @@ impl HeaderCondition {
     /// let encoding = HeaderCondition::new("Accept-Encoding", ".*gzip.*").unwrap();
     /// ```
     pub fn new(name: impl Into<String>, pattern: impl AsRef<str>) -> Result<Self, regex::Error> {
-        Ok(Self {
+        Ok(Self::from_regex(name, Regex::new(pattern.as_ref())?))
+    }
+
+    /// Create a new header condition from an already compiled regular expression
+    ///
+    /// This allows sharing a compiled pattern, or tuning it with
+    /// [`RegexBuilder`](regex::RegexBuilder), for example to set size limits.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The header name to check (case-insensitive)
+    /// * `pattern` - Compiled regular expression to match against the header value
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use http_rewriter::HeaderCondition;
+    /// use regex::RegexBuilder;
+    ///
+    /// let pattern = RegexBuilder::new("^application/json")
+    ///     .case_insensitive(true)
+    ///     .size_limit(1 << 16)
+    ///     .build()
+    ///     .unwrap();
+    /// let condition = HeaderCondition::from_regex("Content-Type", pattern);
+    /// ```
+    pub fn from_regex(name: impl Into<String>, pattern: Regex) -> Self {
+        Self {
             name: name.into(),
-            pattern: Regex::new(pattern.as_ref())?,
-        })
+            matcher: HeaderMatcher::Pattern(pattern),
+            all: false,
+        }
     }
-}
 
-impl Condition for HeaderCondition {
-    fn matches<B>(&self, request: &Request<B>) -> bool {
-        request
-            .headers()
-            .get(&self.name)
-            .and_then(|value| value.to_str().ok())
-            .map(|value| self.pattern.is_match(value))
-            .unwrap_or(false)
+    /// Create a new header condition comparing the header value literally
+    ///
+    /// A value of the header must equal `value` exactly, byte for byte, so no
+    /// regular expression is compiled and characters such as `.` or `+` need
+    /// no escaping. Like [`HeaderCondition::new`], a repeated header matches
+    /// if any of its values is equal. Parameters are part of the value, so
+    /// `application/json; charset=utf-8` does not equal `application/json`.
+    /// If the header is absent the condition does not match.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The header name to check (case-insensitive)
+    /// * `value` - The value the header must equal
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use http_rewriter::{Condition, HeaderCondition};
+    /// use http::Request;
+    ///
+    /// let json = HeaderCondition::exact("Accept", "application/json");
+    ///
+    /// let request = Request::builder()
+    ///     .uri("/")
+    ///     .header("Accept", "application/json")
+    ///     .body(())
+    ///     .unwrap();
+    /// assert!(json.matches(&request));
+    ///
+    /// let request = Request::builder()
+    ///     .uri("/")
+    ///     .header("Accept", "application/json+x")
+    ///     .body(())
+    ///     .unwrap();
+    /// assert!(!json.matches(&request));
+    /// ```
+    pub fn exact(name: impl Into<String>, value: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            matcher: HeaderMatcher::Exact(value.into()),
+            all: false,
+        }
     }
-}
 
-/// Condition that matches if a file exists on the filesystem
+    /// Create a new header condition which requires every header value to match
+    ///
+    /// Values which are not valid text never match. If the header is absent the
+    /// condition does not match.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The header name to check (case-insensitive)
+    /// * `pattern` - A regular expression pattern every header value must match
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the pattern is not a valid regular expression
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use http_rewriter::{Condition, HeaderCondition};
+    /// use http::Request;
+    ///
+    /// let cacheable = HeaderCondition::new_all("Cache-Control", "^(public|max-age=\\d+)$").unwrap();
+    ///
+    /// let request = Request::builder()
+    ///     .uri("/")
+    ///     .header("Cache-Control", "public")
+    ///     .header("Cache-Control", "max-age=60")
+    ///     .body(())
+    ///     .unwrap();
+    /// assert!(cacheable.matches(&request));
+    ///
+    /// let request = Request::builder()
+    ///     .uri("/")
+    ///     .header("Cache-Control", "public")
+    ///     .header("Cache-Control", "no-store")
+    ///     .body(())
+    ///     .unwrap();
+    /// assert!(!cacheable.matches(&request));
+    /// ```
+    pub fn new_all(
+        name: impl Into<String>,
+        pattern: impl AsRef<str>,
+    ) -> Result<Self, regex::Error> {
+        Ok(Self {
+            all: true,
+            ..Self::new(name, pattern)?
+        })
+    }
+}
+
+impl Condition for HeaderCondition {
+    fn matches<B>(&self, request: &Request<B>) -> bool {
+        let mut values = request
+            .headers()
+            .get_all(&self.name)
+            .iter()
+            .map(|value| match &self.matcher {
+                HeaderMatcher::Pattern(pattern) => {
+                    value.to_str().is_ok_and(|value| pattern.is_match(value))
+                }
+                HeaderMatcher::Exact(expected) => value.as_bytes() == expected.as_bytes(),
+            })
+            .peekable();
+
+        if self.all {
+            values.peek().is_some() && values.all(|matched| matched)
+        } else {
+            values.any(|matched| matched)
+        }
+    }
+
+    fn patterns(&self) -> Vec<&str> {
+        match &self.matcher {
+            HeaderMatcher::Pattern(pattern) => vec![pattern.as_str()],
+            HeaderMatcher::Exact(_) => Vec::new(),
+        }
+    }
+}
+
+/// Condition that matches if a specific header is NOT present on the request
+///
+/// This condition is the header counterpart of [`NonExistenceCondition`] - it
+/// matches when the request carries no value for the named header. Header names
+/// are case-insensitive. This is useful for supplying a default header only when
+/// the client didn't send one.
+///
+/// # Examples
+///
+/// ```
+/// use http_rewriter::{Condition, HeaderAbsentCondition, Rewriter, RewriterExt, RewriteError};
+/// use http::Request;
+///
+/// // Add an X-Request-Id header only when the client didn't send one
+/// let add_request_id = |mut request: Request<()>| -> Result<Request<()>, RewriteError> {
+///     request.headers_mut().insert("X-Request-Id", "generated-id".parse().unwrap());
+///     Ok(request)
+/// };
+/// let rewriter = add_request_id.when(HeaderAbsentCondition::new("X-Request-Id"));
+///
+/// let request = Request::builder()
+///     .uri("/api/users")
+///     .body(())
+///     .unwrap();
+/// let result = rewriter.rewrite(request).unwrap();
+/// assert_eq!(result.headers().get("x-request-id").unwrap(), "generated-id");
+///
+/// // An existing request id is left untouched
+/// let request = Request::builder()
+///     .uri("/api/users")
+///     .header("x-request-id", "client-id")
+///     .body(())
+///     .unwrap();
+/// let result = rewriter.rewrite(request).unwrap();
+/// assert_eq!(result.headers().get("x-request-id").unwrap(), "client-id");
+/// ```
+#[derive(Debug, Clone)]
+pub struct HeaderAbsentCondition {
+    name: String,
+}
+
+impl HeaderAbsentCondition {
+    /// Create a new header absence condition
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The header name which must be missing (case-insensitive)
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use http_rewriter::HeaderAbsentCondition;
+    ///
+    /// let condition = HeaderAbsentCondition::new("Authorization");
+    /// ```
+    pub fn new(name: impl Into<String>) -> Self {
+        Self { name: name.into() }
+    }
+}
+
+impl Condition for HeaderAbsentCondition {
+    fn matches<B>(&self, request: &Request<B>) -> bool {
+        !request.headers().contains_key(&self.name)
+    }
+}
+
+/// Condition that matches when a header appears more than once
+///
+/// Headers such as `Content-Length`, `Transfer-Encoding` and `Host` must
+/// appear at most once. When a request carries two of them, a proxy and the
+/// backend may each pick a different one, which is how request smuggling
+/// attacks work, so such requests are best rejected outright. The condition
+/// counts the header lines with the given name, whatever their values, even
+/// when the values are identical. Values folded into a single line with
+/// commas count as one.
+///
+/// # Examples
+///
+/// ```
+/// use http_rewriter::{Condition, DuplicateHeaderCondition};
+/// use http::Request;
+///
+/// let duplicated = DuplicateHeaderCondition::new("Content-Length");
+///
+/// let request = Request::builder()
+///     .method("POST")
+///     .uri("/upload")
+///     .header("Content-Length", "5")
+///     .header("Content-Length", "500")
+///     .body(())
+///     .unwrap();
+/// assert!(duplicated.matches(&request));
+///
+/// let request = Request::builder()
+///     .method("POST")
+///     .uri("/upload")
+///     .header("Content-Length", "5")
+///     .body(())
+///     .unwrap();
+/// assert!(!duplicated.matches(&request));
+/// ```
+#[derive(Debug, Clone)]
+pub struct DuplicateHeaderCondition {
+    name: String,
+}
+
+impl DuplicateHeaderCondition {
+    /// Create a new duplicate header condition
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The header name which must appear at most once (case-insensitive)
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use http_rewriter::DuplicateHeaderCondition;
+    ///
+    /// let condition = DuplicateHeaderCondition::new("Host");
+    /// ```
+    pub fn new(name: impl Into<String>) -> Self {
+        Self { name: name.into() }
+    }
+}
+
+impl Condition for DuplicateHeaderCondition {
+    fn matches<B>(&self, request: &Request<B>) -> bool {
+        request
+            .headers()
+            .get_all(&self.name)
+            .iter()
+            .nth(1)
+            .is_some()
+    }
+}
+
+/// Condition that matches when any of a set of required headers is missing
+///
+/// This is meant for validation rules, rewriting or rejecting requests which
+/// lack a header they must carry. The condition matches as soon as one of the
+/// headers is absent, and only fails to match when every one of them is
+/// present. To match only when all of them are missing instead, combine
+/// [`HeaderAbsentCondition`]s with `and()`. Header names are
+/// case-insensitive, and an empty list never matches.
+///
+/// # Examples
+///
+/// ```
+/// use http_rewriter::{Condition, RequiredHeadersCondition};
+/// use http::Request;
+///
+/// let missing = RequiredHeadersCondition::new(&["Authorization", "X-Tenant-Id"]);
+///
+/// let request = Request::builder()
+///     .uri("/api/users")
+///     .header("Authorization", "Bearer abc123")
+///     .body(())
+///     .unwrap();
+/// assert!(missing.matches(&request));
+///
+/// let request = Request::builder()
+///     .uri("/api/users")
+///     .header("Authorization", "Bearer abc123")
+///     .header("X-Tenant-Id", "acme")
+///     .body(())
+///     .unwrap();
+/// assert!(!missing.matches(&request));
+/// ```
+#[derive(Debug, Clone)]
+pub struct RequiredHeadersCondition {
+    names: Vec<String>,
+}
+
+impl RequiredHeadersCondition {
+    /// Create a new required headers condition
+    ///
+    /// # Arguments
+    ///
+    /// * `names` - The header names which must all be present (case-insensitive)
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use http_rewriter::RequiredHeadersCondition;
+    ///
+    /// let condition = RequiredHeadersCondition::new(&["Host", "User-Agent"]);
+    /// ```
+    pub fn new<S: AsRef<str>>(names: &[S]) -> Self {
+        Self {
+            names: names.iter().map(|name| name.as_ref().to_string()).collect(),
+        }
+    }
+}
+
+impl Condition for RequiredHeadersCondition {
+    fn matches<B>(&self, request: &Request<B>) -> bool {
+        self.names
+            .iter()
+            .any(|name| !request.headers().contains_key(name.as_str()))
+    }
+}
+
+/// Condition that matches when two headers have the same value
+///
+/// This is meant for consistency checks, such as a `Host` header which
+/// should agree with `X-Forwarded-Host`. The first value of each header is
+/// compared, byte for byte by default or ignoring ASCII case with
+/// [`HeaderCompareCondition::with_case_insensitive`]. The condition does not
+/// match when either header is absent.
+///
+/// # Examples
+///
+/// ```
+/// use http_rewriter::{Condition, HeaderCompareCondition};
+/// use http::Request;
+///
+/// let consistent = HeaderCompareCondition::new("Host", "X-Forwarded-Host");
+///
+/// let request = Request::builder()
+///     .uri("/")
+///     .header("Host", "example.com")
+///     .header("X-Forwarded-Host", "example.com")
+///     .body(())
+///     .unwrap();
+/// assert!(consistent.matches(&request));
+///
+/// let request = Request::builder()
+///     .uri("/")
+///     .header("Host", "example.com")
+///     .header("X-Forwarded-Host", "attacker.example")
+///     .body(())
+///     .unwrap();
+/// assert!(!consistent.matches(&request));
+/// ```
+#[derive(Debug, Clone)]
+pub struct HeaderCompareCondition {
+    first: String,
+    second: String,
+    case_insensitive: bool,
+}
+
+impl HeaderCompareCondition {
+    /// Create a new header comparison condition
+    ///
+    /// # Arguments
+    ///
+    /// * `first` - The name of the first header to compare (case-insensitive)
+    /// * `second` - The name of the second header to compare (case-insensitive)
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use http_rewriter::HeaderCompareCondition;
+    ///
+    /// let condition = HeaderCompareCondition::new("Origin", "X-Expected-Origin");
+    /// ```
+    pub fn new(first: impl Into<String>, second: impl Into<String>) -> Self {
+        Self {
+            first: first.into(),
+            second: second.into(),
+            case_insensitive: false,
+        }
+    }
+
+    /// Set whether values are compared ignoring ASCII case
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use http_rewriter::{Condition, HeaderCompareCondition};
+    /// use http::Request;
+    ///
+    /// let condition = HeaderCompareCondition::new("Host", "X-Forwarded-Host")
+    ///     .with_case_insensitive(true);
+    ///
+    /// let request = Request::builder()
+    ///     .uri("/")
+    ///     .header("Host", "Example.com")
+    ///     .header("X-Forwarded-Host", "example.COM")
+    ///     .body(())
+    ///     .unwrap();
+    /// assert!(condition.matches(&request));
+    /// ```
+    pub fn with_case_insensitive(mut self, case_insensitive: bool) -> Self {
+        self.case_insensitive = case_insensitive;
+        self
+    }
+}
+
+impl Condition for HeaderCompareCondition {
+    fn matches<B>(&self, request: &Request<B>) -> bool {
+        let headers = request.headers();
+        let (Some(first), Some(second)) = (
+            headers.get(self.first.as_str()),
+            headers.get(self.second.as_str()),
+        ) else {
+            return false;
+        };
+        if self.case_insensitive {
+            first.as_bytes().eq_ignore_ascii_case(second.as_bytes())
+        } else {
+            first == second
+        }
+    }
+}
+
+/// Condition that matches when any header name matches a pattern
+///
+/// Every header name is matched against the pattern until one matches, so
+/// the cost grows with the number of distinct headers on the request, unlike
+/// conditions which look up a single header by name. Header names are always
+/// lowercase, so write the pattern in lowercase or make it case-insensitive.
+/// Header values are not looked at.
+///
+/// # Examples
+///
+/// ```
+/// use http_rewriter::{Condition, HeaderNameExistsCondition};
+/// use http::Request;
+///
+/// let debug = HeaderNameExistsCondition::new("^x-debug-").unwrap();
+///
+/// let request = Request::builder()
+///     .uri("/")
+///     .header("X-Debug-Level", "verbose")
+///     .body(())
+///     .unwrap();
+/// assert!(debug.matches(&request));
+///
+/// let request = Request::builder()
+///     .uri("/")
+///     .header("X-Request-Id", "abc")
+///     .body(())
+///     .unwrap();
+/// assert!(!debug.matches(&request));
+/// ```
+#[derive(Debug, Clone)]
+pub struct HeaderNameExistsCondition {
+    pattern: Regex,
+}
+
+impl HeaderNameExistsCondition {
+    /// Create a new header name condition
+    ///
+    /// # Arguments
+    ///
+    /// * `pattern` - A regular expression pattern to match against each header name
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the pattern is not a valid regular expression
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use http_rewriter::HeaderNameExistsCondition;
+    ///
+    /// let condition = HeaderNameExistsCondition::new("^x-forwarded-").unwrap();
+    /// ```
+    pub fn new(pattern: impl AsRef<str>) -> Result<Self, regex::Error> {
+        Ok(Self::from_regex(Regex::new(pattern.as_ref())?))
+    }
+
+    /// Create a new header name condition from an already compiled regular expression
+    ///
+    /// # Arguments
+    ///
+    /// * `pattern` - Compiled regular expression to match against each header name
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use http_rewriter::HeaderNameExistsCondition;
+    /// use regex::Regex;
+    ///
+    /// let condition = HeaderNameExistsCondition::from_regex(Regex::new("^x-debug-").unwrap());
+    /// ```
+    pub fn from_regex(pattern: Regex) -> Self {
+        Self { pattern }
+    }
+}
+
+impl Condition for HeaderNameExistsCondition {
+    fn matches<B>(&self, request: &Request<B>) -> bool {
+        request
+            .headers()
+            .keys()
+            .any(|name| self.pattern.is_match(name.as_str()))
+    }
+
+    fn patterns(&self) -> Vec<&str> {
+        vec![self.pattern.as_str()]
+    }
+}
+
+/// Condition that matches on the scheme of the `Authorization` header
+///
+/// This condition extracts the authentication scheme from the `Authorization`
+/// header - the token before the first space, such as `Bearer` or `Basic` - and
+/// compares it to the expected scheme case-insensitively. It does not match
+/// when the header is absent or can not be read as text.
+///
+/// # Examples
+///
+/// ```
+/// use http_rewriter::{AuthSchemeCondition, Condition};
+/// use http::Request;
+///
+/// let condition = AuthSchemeCondition::new("Bearer");
+///
+/// let request = Request::builder()
+///     .uri("/api/users")
+///     .header("Authorization", "bearer abc123")
+///     .body(())
+///     .unwrap();
+/// assert!(condition.matches(&request));
+///
+/// let request = Request::builder()
+///     .uri("/api/users")
+///     .header("Authorization", "Basic dXNlcjpwYXNz")
+///     .body(())
+///     .unwrap();
+/// assert!(!condition.matches(&request));
+/// ```
+#[derive(Debug, Clone)]
+pub struct AuthSchemeCondition {
+    scheme: String,
+}
+
+impl AuthSchemeCondition {
+    /// Create a new authorization scheme condition
+    ///
+    /// # Arguments
+    ///
+    /// * `scheme` - The authentication scheme to match (case-insensitive)
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use http_rewriter::AuthSchemeCondition;
+    ///
+    /// let condition = AuthSchemeCondition::new("Basic");
+    /// ```
+    pub fn new(scheme: impl Into<String>) -> Self {
+        Self {
+            scheme: scheme.into(),
+        }
+    }
+}
+
+impl Condition for AuthSchemeCondition {
+    fn matches<B>(&self, request: &Request<B>) -> bool {
+        request
+            .headers()
+            .get(http::header::AUTHORIZATION)
+            .and_then(|value| value.to_str().ok())
+            .map(|value| value.split_once(' ').map_or(value, |(scheme, _)| scheme))
+            .is_some_and(|scheme| scheme.eq_ignore_ascii_case(&self.scheme))
+    }
+}
+
+/// Condition that matches the request query string against a regular expression pattern
+///
+/// The pattern is matched against the raw (still-encoded) query string,
+/// without the leading `?`. Requests without a query string are matched as if
+/// the query string was empty.
+///
+/// # Examples
+///
+/// ```
+/// use http_rewriter::{Condition, QueryCondition};
+/// use http::Request;
+///
+/// let condition = QueryCondition::new("(^|&)debug=1(&|$)").unwrap();
+///
+/// let request = Request::builder()
+///     .uri("/search?q=rust&debug=1")
+///     .body(())
+///     .unwrap();
+/// assert!(condition.matches(&request));
+///
+/// let request = Request::builder()
+///     .uri("/search?q=rust")
+///     .body(())
+///     .unwrap();
+/// assert!(!condition.matches(&request));
+/// ```
+#[derive(Debug, Clone)]
+pub struct QueryCondition {
+    pattern: Regex,
+}
+
+impl QueryCondition {
+    /// Create a new query condition
+    ///
+    /// # Arguments
+    ///
+    /// * `pattern` - A regular expression pattern to match against the query string
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the pattern is not a valid regular expression
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use http_rewriter::QueryCondition;
+    ///
+    /// // Match requests without a query string
+    /// let condition = QueryCondition::new("^$").unwrap();
+    /// ```
+    pub fn new(pattern: impl AsRef<str>) -> Result<Self, regex::Error> {
+        Ok(Self::from_regex(Regex::new(pattern.as_ref())?))
+    }
+
+    /// Create a new query condition from an already compiled regular expression
+    ///
+    /// This allows sharing a compiled pattern, or tuning it with
+    /// [`RegexBuilder`](regex::RegexBuilder), for example to set size limits.
+    ///
+    /// # Arguments
+    ///
+    /// * `pattern` - Compiled regular expression to match against the query string
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use http_rewriter::QueryCondition;
+    /// use regex::Regex;
+    ///
+    /// let condition = QueryCondition::from_regex(Regex::new("(^|&)debug=").unwrap());
+    /// ```
+    pub fn from_regex(pattern: Regex) -> Self {
+        Self { pattern }
+    }
+}
+
+impl Condition for QueryCondition {
+    fn matches<B>(&self, request: &Request<B>) -> bool {
+        self.pattern
+            .is_match(request.uri().query().unwrap_or_default())
+    }
+
+    fn patterns(&self) -> Vec<&str> {
+        vec![self.pattern.as_str()]
+    }
+}
+
+/// Condition that matches a single query parameter
+///
+/// Query parameter names and values are decoded before matching, with `+`
+/// treated as a space. Parameters which do not decode to valid UTF-8 are
+/// skipped. The condition matches if any parameter with the given name is
+/// present, or if a pattern is given, if any of their values matches it.
+///
+/// # Examples
+///
+/// ```
+/// use http_rewriter::{Condition, QueryParamCondition};
+/// use http::Request;
+///
+/// let condition = QueryParamCondition::new("q", "^hello world$").unwrap();
+///
+/// let request = Request::builder()
+///     .uri("/search?q=hello+world")
+///     .body(())
+///     .unwrap();
+/// assert!(condition.matches(&request));
+///
+/// // Only check that the parameter is present
+/// let condition = QueryParamCondition::present("page");
+/// assert!(!condition.matches(&request));
+/// ```
+#[derive(Debug, Clone)]
+pub struct QueryParamCondition {
+    name: String,
+    pattern: Option<Regex>,
+}
+
+impl QueryParamCondition {
+    /// Create a new query parameter condition matching the parameter value
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The query parameter name to check
+    /// * `pattern` - A regular expression pattern to match against the decoded value
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the pattern is not a valid regular expression
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use http_rewriter::QueryParamCondition;
+    ///
+    /// let condition = QueryParamCondition::new("format", "^(json|xml)$").unwrap();
+    /// ```
+    pub fn new(name: impl Into<String>, pattern: impl AsRef<str>) -> Result<Self, regex::Error> {
+        Ok(Self::from_regex(name, Regex::new(pattern.as_ref())?))
+    }
+
+    /// Create a new query parameter condition from an already compiled regular expression
+    ///
+    /// This allows sharing a compiled pattern, or tuning it with
+    /// [`RegexBuilder`](regex::RegexBuilder), for example to set size limits.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The query parameter name to check
+    /// * `pattern` - Compiled regular expression to match against the decoded value
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use http_rewriter::QueryParamCondition;
+    /// use regex::Regex;
+    ///
+    /// let condition = QueryParamCondition::from_regex("format", Regex::new("^json$").unwrap());
+    /// ```
+    pub fn from_regex(name: impl Into<String>, pattern: Regex) -> Self {
+        Self {
+            name: name.into(),
+            pattern: Some(pattern),
+        }
+    }
+
+    /// Create a new query parameter condition matching if the parameter is present
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The query parameter name to check
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use http_rewriter::QueryParamCondition;
+    ///
+    /// let condition = QueryParamCondition::present("debug");
+    /// ```
+    pub fn present(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            pattern: None,
+        }
+    }
+}
+
+impl Condition for QueryParamCondition {
+    fn matches<B>(&self, request: &Request<B>) -> bool {
+        let Some(query) = request.uri().query() else {
+            return false;
+        };
+        query
+            .split('&')
+            .filter_map(|pair| {
+                let (name, value) = pair.split_once('=').unwrap_or((pair, ""));
+                Some((
+                    decode_query_component(name)?,
+                    decode_query_component(value)?,
+                ))
+            })
+            .any(|(name, value)| {
+                name == self.name
+                    && self
+                        .pattern
+                        .as_ref()
+                        .is_none_or(|pattern| pattern.is_match(&value))
+            })
+    }
+
+    fn patterns(&self) -> Vec<&str> {
+        self.pattern.iter().map(Regex::as_str).collect()
+    }
+}
+
+/// Condition that matches when a query parameter is set as a flag
+///
+/// This suits feature toggles driven by the query, where `?beta`, `?beta=`
+/// and `?beta=true` should all turn the feature on. The condition matches
+/// when the parameter is present with an empty value, or with one of `1`,
+/// `true`, `yes` or `on`, ignoring case. Any other value, such as `0` or
+/// `false`, does not match, and neither does a missing parameter.
+///
+/// Names and values are decoded like for [`QueryParamCondition`]. When the
+/// parameter is repeated, only its first occurrence is considered.
+///
+/// # Examples
+///
+/// ```
+/// use http_rewriter::{Condition, QueryFlagCondition};
+/// use http::Request;
+///
+/// let condition = QueryFlagCondition::new("beta");
+///
+/// for uri in ["/?beta", "/?beta=", "/?beta=1", "/?beta=TRUE", "/?beta=on"] {
+///     let request = Request::builder().uri(uri).body(()).unwrap();
+///     assert!(condition.matches(&request), "{uri}");
+/// }
+///
+/// for uri in ["/", "/?beta=0", "/?beta=false", "/?beta=maybe", "/?alpha"] {
+///     let request = Request::builder().uri(uri).body(()).unwrap();
+///     assert!(!condition.matches(&request), "{uri}");
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct QueryFlagCondition {
+    name: String,
+}
+
+impl QueryFlagCondition {
+    /// Create a new query flag condition
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The query parameter name to check
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use http_rewriter::QueryFlagCondition;
+    ///
+    /// let condition = QueryFlagCondition::new("debug");
+    /// ```
+    pub fn new(name: impl Into<String>) -> Self {
+        Self { name: name.into() }
+    }
+}
+
+impl Condition for QueryFlagCondition {
+    fn matches<B>(&self, request: &Request<B>) -> bool {
+        let Some(query) = request.uri().query() else {
+            return false;
+        };
+        query
+            .split('&')
+            .filter_map(|pair| {
+                let (name, value) = pair.split_once('=').unwrap_or((pair, ""));
+                Some((
+                    decode_query_component(name)?,
+                    decode_query_component(value)?,
+                ))
+            })
+            .find(|(name, _)| *name == self.name)
+            .is_some_and(|(_, value)| {
+                value.is_empty()
+                    || ["1", "true", "yes", "on"]
+                        .iter()
+                        .any(|truthy| value.eq_ignore_ascii_case(truthy))
+            })
+    }
+}
+
+/// Condition that matches the request host against a regular expression pattern
+///
+/// The host is taken from the request URI when it is absolute, and from the
+/// `Host` header otherwise. The port is not part of the matched host.
+///
+/// # Examples
+///
+/// ```
+/// use http_rewriter::{Condition, HostCondition};
+/// use http::Request;
+///
+/// let condition = HostCondition::new(r"^api\.example\.com$").unwrap();
+///
+/// let request = Request::builder()
+///     .uri("/users")
+///     .header("Host", "api.example.com:8080")
+///     .body(())
+///     .unwrap();
+/// assert!(condition.matches(&request));
+///
+/// let request = Request::builder()
+///     .uri("http://www.example.com/users")
+///     .body(())
+///     .unwrap();
+/// assert!(!condition.matches(&request));
+/// ```
+#[derive(Debug, Clone)]
+pub struct HostCondition {
+    pattern: Regex,
+}
+
+impl HostCondition {
+    /// Create a new host condition
+    ///
+    /// # Arguments
+    ///
+    /// * `pattern` - A regular expression pattern to match against the request host
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the pattern is not a valid regular expression
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use http_rewriter::HostCondition;
+    ///
+    /// // Match any subdomain of example.com
+    /// let condition = HostCondition::new(r"\.example\.com$").unwrap();
+    /// ```
+    pub fn new(pattern: impl AsRef<str>) -> Result<Self, regex::Error> {
+        Ok(Self::from_regex(Regex::new(pattern.as_ref())?))
+    }
+
+    /// Create a new host condition from an already compiled regular expression
+    ///
+    /// This allows sharing a compiled pattern, or tuning it with
+    /// [`RegexBuilder`](regex::RegexBuilder), for example to set size limits.
+    ///
+    /// # Arguments
+    ///
+    /// * `pattern` - Compiled regular expression to match against the request host
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use http_rewriter::HostCondition;
+    /// use regex::Regex;
+    ///
+    /// let condition = HostCondition::from_regex(Regex::new(r"^api\.").unwrap());
+    /// ```
+    pub fn from_regex(pattern: Regex) -> Self {
+        Self { pattern }
+    }
+}
+
+impl Condition for HostCondition {
+    fn matches<B>(&self, request: &Request<B>) -> bool {
+        request_authority(request).is_some_and(|authority| self.pattern.is_match(authority.host()))
+    }
+
+    fn patterns(&self) -> Vec<&str> {
+        vec![self.pattern.as_str()]
+    }
+}
+
+/// Get the authority a request was sent to, from the URI or else the `Host` header
+pub(crate) fn request_authority<B>(request: &Request<B>) -> Option<http::uri::Authority> {
+    if let Some(authority) = request.uri().authority() {
+        return Some(authority.clone());
+    }
+    request
+        .headers()
+        .get(http::header::HOST)?
+        .to_str()
+        .ok()?
+        .parse()
+        .ok()
+}
+
+/// How a [`HostAllowlistCondition`] treats its list of hosts
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum HostListMode {
+    /// Only the listed hosts are allowed, so the condition matches every other host
+    Allow,
+    /// The listed hosts are denied, so the condition matches them
+    Deny,
+}
+
+/// Condition that matches requests for hosts which should be rejected
+///
+/// This guards a gateway against requests for hosts it should not serve, such
+/// as server-side request forgery through absolute-form URIs. In
+/// [`HostListMode::Allow`] mode the condition matches every host outside the
+/// list, and in [`HostListMode::Deny`] mode it matches the hosts in it, so in
+/// both modes a rule rejecting the request can fire when it matches.
+///
+/// The host is taken like for [`HostCondition`]: from the request URI when it
+/// is absolute, and from the `Host` header otherwise, without the port.
+/// Requests whose host cannot be determined always match, so they are
+/// rejected whichever the mode.
+///
+/// Entries are compared ignoring case and a trailing dot on the host. An
+/// entry starting with `*.` matches any subdomain, at any depth, but not the
+/// domain itself. A `*` anywhere else matches any characters within a single
+/// label. All other characters match themselves.
+///
+/// # Examples
+///
+/// ```
+/// use http_rewriter::{Condition, HostAllowlistCondition, HostListMode};
+/// use http::Request;
+///
+/// let outside = HostAllowlistCondition::new(
+///     &["example.com", "*.example.com"],
+///     HostListMode::Allow,
+/// )
+/// .unwrap();
+///
+/// let request = Request::builder()
+///     .uri("http://api.example.com/users")
+///     .body(())
+///     .unwrap();
+/// assert!(!outside.matches(&request));
+///
+/// // Requests for any other host match, so they can be rejected
+/// let request = Request::builder()
+///     .uri("http://169.254.169.254/latest/meta-data")
+///     .body(())
+///     .unwrap();
+/// assert!(outside.matches(&request));
+/// ```
+#[derive(Debug, Clone)]
+pub struct HostAllowlistCondition {
+    pattern: Regex,
+    mode: HostListMode,
+}
+
+impl HostAllowlistCondition {
+    /// Create a new host list condition
+    ///
+    /// # Arguments
+    ///
+    /// * `hosts` - Exact hosts, or wildcard hosts such as `*.example.com`
+    /// * `mode` - Whether the hosts are the only ones allowed, or are denied
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the list is too large to compile
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use http_rewriter::{HostAllowlistCondition, HostListMode};
+    ///
+    /// let blocked = HostAllowlistCondition::new(
+    ///     &["localhost", "*.internal"],
+    ///     HostListMode::Deny,
+    /// )
+    /// .unwrap();
+    /// ```
+    pub fn new(hosts: &[impl AsRef<str>], mode: HostListMode) -> Result<Self, regex::Error> {
+        let hosts: Vec<_> = hosts
+            .iter()
+            .map(|host| host_entry_to_regex(host.as_ref().trim_end_matches('.')))
+            .collect();
+        let pattern = Regex::new(&format!("(?i)^(?:{})$", hosts.join("|")))?;
+        Ok(Self { pattern, mode })
+    }
+}
+
+// Translate a host list entry into a regular expression
+fn host_entry_to_regex(host: &str) -> String {
+    let (mut regex, rest) = match host.strip_prefix("*.") {
+        Some(rest) => (String::from(r"(?:[^.]+\.)+"), rest),
+        None => (String::new(), host),
+    };
+    for (index, part) in rest.split('*').enumerate() {
+        if index > 0 {
+            regex.push_str("[^.]*");
+        }
+        regex.push_str(&regex::escape(part));
+    }
+    regex
+}
+
+impl Condition for HostAllowlistCondition {
+    fn matches<B>(&self, request: &Request<B>) -> bool {
+        let Some(authority) = request_authority(request) else {
+            return true;
+        };
+        let listed = self
+            .pattern
+            .is_match(authority.host().trim_end_matches('.'));
+        match self.mode {
+            HostListMode::Allow => !listed,
+            HostListMode::Deny => listed,
+        }
+    }
+
+    fn patterns(&self) -> Vec<&str> {
+        vec![self.pattern.as_str()]
+    }
+}
+
+/// Condition that matches the port the request was sent to
+///
+/// The port is taken from the request URI when it has one, such as for
+/// absolute-form requests, and from the `Host` header otherwise. Only an
+/// explicit port is matched: a request to `example.com` without a port does
+/// not match port 80 or 443, as the default port depends on the connection
+/// rather than the request.
+///
+/// # Examples
+///
+/// ```
+/// use http_rewriter::{Condition, PortCondition};
+/// use http::Request;
+///
+/// let condition = PortCondition::new(8080);
+///
+/// let request = Request::builder()
+///     .uri("/users")
+///     .header("Host", "example.com:8080")
+///     .body(())
+///     .unwrap();
+/// assert!(condition.matches(&request));
+///
+/// let request = Request::builder()
+///     .uri("/users")
+///     .header("Host", "example.com")
+///     .body(())
+///     .unwrap();
+/// assert!(!condition.matches(&request));
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct PortCondition {
+    port: u16,
+}
+
+impl PortCondition {
+    /// Create a new port condition
+    ///
+    /// # Arguments
+    ///
+    /// * `port` - The port to match
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use http_rewriter::PortCondition;
+    ///
+    /// let condition = PortCondition::new(8443);
+    /// ```
+    pub fn new(port: u16) -> Self {
+        Self { port }
+    }
+}
+
+impl Condition for PortCondition {
+    fn matches<B>(&self, request: &Request<B>) -> bool {
+        if request.uri().host().is_some() {
+            return request.uri().port_u16() == Some(self.port);
+        }
+        request
+            .headers()
+            .get(http::header::HOST)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.parse::<http::uri::Authority>().ok())
+            .and_then(|authority| authority.port_u16())
+            == Some(self.port)
+    }
+}
+
+/// Condition that matches the languages the client prefers
+///
+/// The `Accept-Language` header is parsed into language tags ordered by their
+/// quality value, highest first, keeping the header order for equal values.
+/// Tags with a quality of zero are not acceptable to the client and are
+/// ignored, as is the `*` wildcard, which does not name a language.
+///
+/// A condition created with [`LanguageCondition::new`] matches the pattern
+/// against the most preferred tag only, while one created with
+/// [`LanguageCondition::new_any`] matches if any acceptable tag matches. Tags
+/// are matched as sent, so use a case-insensitive pattern such as `(?i)^en`
+/// to ignore case. If the header is absent, or names no language, the
+/// condition does not match.
+///
+/// # Examples
+///
+/// ```
+/// use http_rewriter::{Condition, LanguageCondition};
+/// use http::Request;
+///
+/// let english = LanguageCondition::new("^en").unwrap();
+/// let french = LanguageCondition::new("^fr").unwrap();
+/// let any_french = LanguageCondition::new_any("^fr").unwrap();
+///
+/// let request = Request::builder()
+///     .uri("/")
+///     .header("Accept-Language", "fr;q=0.8, en-US, en;q=0.9")
+///     .body(())
+///     .unwrap();
+/// assert!(english.matches(&request));
+/// assert!(!french.matches(&request));
+/// assert!(any_french.matches(&request));
+/// ```
+#[derive(Debug, Clone)]
+pub struct LanguageCondition {
+    pattern: Regex,
+    any: bool,
+}
+
+impl LanguageCondition {
+    /// Create a new language condition matching the most preferred language
+    ///
+    /// # Arguments
+    ///
+    /// * `pattern` - A regular expression pattern to match against the language tag
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the pattern is not a valid regular expression
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use http_rewriter::LanguageCondition;
+    ///
+    /// let condition = LanguageCondition::new("^de(-|$)").unwrap();
+    /// ```
+    pub fn new(pattern: impl AsRef<str>) -> Result<Self, regex::Error> {
+        Ok(Self {
+            pattern: Regex::new(pattern.as_ref())?,
+            any: false,
+        })
+    }
+
+    /// Create a new language condition matching any acceptable language
+    ///
+    /// # Arguments
+    ///
+    /// * `pattern` - A regular expression pattern to match against the language tags
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the pattern is not a valid regular expression
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use http_rewriter::LanguageCondition;
+    ///
+    /// let condition = LanguageCondition::new_any("^de(-|$)").unwrap();
+    /// ```
+    pub fn new_any(pattern: impl AsRef<str>) -> Result<Self, regex::Error> {
+        Ok(Self {
+            any: true,
+            ..Self::new(pattern)?
+        })
+    }
+
+    /// Create a new language condition from an already compiled regex
+    ///
+    /// # Arguments
+    ///
+    /// * `pattern` - A compiled regular expression to match against the language tags
+    /// * `any` - Whether any acceptable language may match, rather than only the most preferred
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use http_rewriter::LanguageCondition;
+    /// use regex::Regex;
+    ///
+    /// let condition = LanguageCondition::from_regex(Regex::new("^de(-|$)").unwrap(), true);
+    /// ```
+    pub fn from_regex(pattern: Regex, any: bool) -> Self {
+        Self { pattern, any }
+    }
+}
+
+impl Condition for LanguageCondition {
+    fn matches<B>(&self, request: &Request<B>) -> bool {
+        let mut languages = request
+            .headers()
+            .get_all(http::header::ACCEPT_LANGUAGE)
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .flat_map(|value| value.split(','))
+            .filter_map(|entry| {
+                let mut params = entry.split(';');
+                let tag = params.next()?.trim();
+                let quality = params
+                    .filter_map(|param| param.trim().strip_prefix("q="))
+                    .next()
+                    .map_or(Some(1.0), |q| q.trim().parse::<f32>().ok())?;
+                (!tag.is_empty() && tag != "*" && quality > 0.0).then_some((tag, quality))
+            })
+            .collect::<Vec<_>>();
+
+        // Stable, so equally preferred languages keep the header order
+        languages.sort_by(|(_, a), (_, b)| b.total_cmp(a));
+
+        if self.any {
+            languages.iter().any(|(tag, _)| self.pattern.is_match(tag))
+        } else {
+            languages
+                .first()
+                .is_some_and(|(tag, _)| self.pattern.is_match(tag))
+        }
+    }
+
+    fn patterns(&self) -> Vec<&str> {
+        vec![self.pattern.as_str()]
+    }
+}
+
+/// Condition that matches requests whose URI is absolute
+///
+/// Requests sent to a proxy carry the full target in absolute-form, such as
+/// `http://example.com/path`, while requests sent to an origin server carry
+/// only the path in origin-form, such as `/path`. This condition matches when
+/// the request URI has a scheme. Authority-form URIs, used by `CONNECT`, and
+/// the asterisk-form `*` have no scheme and do not match.
+///
+/// # Examples
+///
+/// ```
+/// use http_rewriter::{AbsoluteUriCondition, Condition};
+/// use http::Request;
+///
+/// let condition = AbsoluteUriCondition::new();
+///
+/// let request = Request::builder()
+///     .uri("http://example.com/api/users")
+///     .body(())
+///     .unwrap();
+/// assert!(condition.matches(&request));
+///
+/// let request = Request::builder()
+///     .uri("/api/users")
+///     .body(())
+///     .unwrap();
+/// assert!(!condition.matches(&request));
+/// ```
+#[derive(Debug, Clone, Copy, Default)]
+pub struct AbsoluteUriCondition;
+
+impl AbsoluteUriCondition {
+    /// Create a new absolute URI condition
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use http_rewriter::AbsoluteUriCondition;
+    ///
+    /// let condition = AbsoluteUriCondition::new();
+    /// ```
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl Condition for AbsoluteUriCondition {
+    fn matches<B>(&self, request: &Request<B>) -> bool {
+        request.uri().scheme().is_some()
+    }
+}
+
+/// Condition that matches requests which appear to carry a body
+///
+/// This is a heuristic based on the request headers only, the body itself is
+/// never read. The condition matches when `Content-Length` is present with a
+/// nonzero value, or when `Transfer-Encoding` is present, such as for chunked
+/// bodies. A `Content-Length` which is not a valid number does not count as a
+/// body. Requests with neither header do not match.
+///
+/// # Examples
+///
+/// ```
+/// use http_rewriter::{Condition, HasBodyCondition};
+/// use http::Request;
+///
+/// let condition = HasBodyCondition::new();
+///
+/// let request = Request::builder()
+///     .method("POST")
+///     .uri("/upload")
+///     .header("Transfer-Encoding", "chunked")
+///     .body(())
+///     .unwrap();
+/// assert!(condition.matches(&request));
+///
+/// let request = Request::builder()
+///     .method("POST")
+///     .uri("/upload")
+///     .header("Content-Length", "0")
+///     .body(())
+///     .unwrap();
+/// assert!(!condition.matches(&request));
+/// ```
+#[derive(Debug, Clone, Copy, Default)]
+pub struct HasBodyCondition;
+
+impl HasBodyCondition {
+    /// Create a new has body condition
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use http_rewriter::HasBodyCondition;
+    ///
+    /// let condition = HasBodyCondition::new();
+    /// ```
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl Condition for HasBodyCondition {
+    fn matches<B>(&self, request: &Request<B>) -> bool {
+        let headers = request.headers();
+        if headers.contains_key(http::header::TRANSFER_ENCODING) {
+            return true;
+        }
+        headers
+            .get(http::header::CONTENT_LENGTH)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.trim().parse::<u64>().ok())
+            .is_some_and(|length| length > 0)
+    }
+}
+
+/// Condition that compares the declared `Content-Length` of a request against a limit
+///
+/// Only the header is looked at, the body itself is never read. When the
+/// request carries several `Content-Length` headers, the first one is used.
+/// A value which is not a valid number never matches. Requests without the
+/// header, such as chunked uploads, do not match either, unless
+/// [`with_match_missing`](Self::with_match_missing) is set.
+///
+/// # Examples
+///
+/// ```
+/// use http_rewriter::{Comparison, Condition, ContentLengthCondition};
+/// use http::Request;
+///
+/// let small = ContentLengthCondition::new(Comparison::LessOrEqual, 1024);
+///
+/// let request = Request::builder()
+///     .method("POST")
+///     .uri("/upload")
+///     .header("Content-Length", "512")
+///     .body(())
+///     .unwrap();
+/// assert!(small.matches(&request));
+///
+/// let request = Request::builder()
+///     .method("POST")
+///     .uri("/upload")
+///     .header("Content-Length", "4096")
+///     .body(())
+///     .unwrap();
+/// assert!(!small.matches(&request));
+/// ```
+#[derive(Debug, Clone)]
+pub struct ContentLengthCondition {
+    comparison: Comparison,
+    length: usize,
+    match_missing: bool,
+}
+
+impl ContentLengthCondition {
+    /// Create a new content length condition
+    ///
+    /// # Arguments
+    ///
+    /// * `comparison` - How to compare the declared length against `length`
+    /// * `length` - The length in bytes to compare against
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use http_rewriter::{Comparison, ContentLengthCondition};
+    ///
+    /// // Bodies larger than 1 MiB
+    /// let condition = ContentLengthCondition::new(Comparison::Greater, 1024 * 1024);
+    /// ```
+    pub fn new(comparison: Comparison, length: usize) -> Self {
+        Self {
+            comparison,
+            length,
+            match_missing: false,
+        }
+    }
+
+    /// Set whether requests without a `Content-Length` header match
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use http_rewriter::{Comparison, Condition, ContentLengthCondition};
+    /// use http::Request;
+    ///
+    /// let condition = ContentLengthCondition::new(Comparison::LessOrEqual, 1024)
+    ///     .with_match_missing(true);
+    ///
+    /// let request = Request::builder().uri("/").body(()).unwrap();
+    /// assert!(condition.matches(&request));
+    /// ```
+    pub fn with_match_missing(mut self, match_missing: bool) -> Self {
+        self.match_missing = match_missing;
+        self
+    }
+}
+
+impl Condition for ContentLengthCondition {
+    fn matches<B>(&self, request: &Request<B>) -> bool {
+        let Some(value) = request.headers().get(http::header::CONTENT_LENGTH) else {
+            return self.match_missing;
+        };
+        value
+            .to_str()
+            .ok()
+            .and_then(|value| value.trim().parse::<usize>().ok())
+            .is_some_and(|length| self.comparison.compare(length, self.length))
+    }
+}
+
+/// Condition that matches CORS preflight requests
+///
+/// Browsers send a preflight before a cross-origin request which is not
+/// simple, asking the server whether the real request is allowed. A preflight
+/// is an `OPTIONS` request carrying the `Access-Control-Request-Method`
+/// header, and this condition matches exactly those. Plain `OPTIONS`
+/// requests, such as those asking which methods a resource supports, do not
+/// match.
+///
+/// # Examples
+///
+/// ```
+/// use http_rewriter::{Condition, CorsPreflightCondition};
+/// use http::{Method, Request};
+///
+/// let condition = CorsPreflightCondition::new();
+///
+/// let request = Request::builder()
+///     .method(Method::OPTIONS)
+///     .uri("/api/users")
+///     .header("Origin", "https://app.example.com")
+///     .header("Access-Control-Request-Method", "DELETE")
+///     .body(())
+///     .unwrap();
+/// assert!(condition.matches(&request));
+///
+/// let request = Request::builder()
+///     .method(Method::OPTIONS)
+///     .uri("/api/users")
+///     .body(())
+///     .unwrap();
+/// assert!(!condition.matches(&request));
+/// ```
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CorsPreflightCondition;
+
+impl CorsPreflightCondition {
+    /// Create a new CORS preflight condition
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use http_rewriter::CorsPreflightCondition;
+    ///
+    /// let condition = CorsPreflightCondition::new();
+    /// ```
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl Condition for CorsPreflightCondition {
+    fn matches<B>(&self, request: &Request<B>) -> bool {
+        request.method() == http::Method::OPTIONS
+            && request
+                .headers()
+                .contains_key(http::header::ACCESS_CONTROL_REQUEST_METHOD)
+    }
+}
+
+/// Condition that matches the scheme the client used, as reported by a proxy
+///
+/// Behind a TLS-terminating proxy the request URI is `http` even when the
+/// client connected over HTTPS. This condition reads the scheme from the
+/// `X-Forwarded-Proto` header, taking the first entry when a chain of proxies
+/// added several, and falls back to the scheme of the request URI when the
+/// header is absent. Schemes are compared case-insensitively. If neither is
+/// available the condition does not match.
+///
+/// # Examples
+///
+/// ```
+/// use http_rewriter::{Condition, ForwardedProtoCondition};
+/// use http::Request;
+///
+/// let https = ForwardedProtoCondition::new("https");
+///
+/// let request = Request::builder()
+///     .uri("/login")
+///     .header("X-Forwarded-Proto", "https, http")
+///     .body(())
+///     .unwrap();
+/// assert!(https.matches(&request));
+///
+/// // Without the header, the URI scheme is used
+/// let request = Request::builder()
+///     .uri("http://example.com/login")
+///     .body(())
+///     .unwrap();
+/// assert!(!https.matches(&request));
+/// ```
+#[derive(Debug, Clone)]
+pub struct ForwardedProtoCondition {
+    scheme: String,
+}
+
+impl ForwardedProtoCondition {
+    /// Create a new forwarded proto condition
+    ///
+    /// # Arguments
+    ///
+    /// * `scheme` - The scheme to match (case-insensitive)
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use http_rewriter::ForwardedProtoCondition;
+    ///
+    /// let condition = ForwardedProtoCondition::new("http");
+    /// ```
+    pub fn new(scheme: impl Into<String>) -> Self {
+        Self {
+            scheme: scheme.into(),
+        }
+    }
+}
+
+impl Condition for ForwardedProtoCondition {
+    fn matches<B>(&self, request: &Request<B>) -> bool {
+        forwarded_proto(request)
+            .or_else(|| request.uri().scheme_str())
+            .is_some_and(|scheme| scheme.eq_ignore_ascii_case(&self.scheme))
+    }
+}
+
+/// Get the first scheme in the `X-Forwarded-Proto` header, if any
+pub(crate) fn forwarded_proto<B>(request: &Request<B>) -> Option<&str> {
+    request
+        .headers()
+        .get("x-forwarded-proto")
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.split(',').next())
+        .map(str::trim)
+        .filter(|scheme| !scheme.is_empty())
+}
+
+/// Parameter of the `Forwarded` header matched by a [`ForwardedCondition`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ForwardedParam {
+    /// The `proto` parameter, the scheme the client used
+    Proto,
+    /// The `host` parameter, the `Host` header the client sent
+    Host,
+    /// The `for` parameter, the client address
+    For,
+}
+
+impl ForwardedParam {
+    /// Get the parameter name as it appears in the header
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use http_rewriter::ForwardedParam;
+    ///
+    /// assert_eq!(ForwardedParam::Proto.name(), "proto");
+    /// ```
+    pub fn name(&self) -> &'static str {
+        match self {
+            ForwardedParam::Proto => "proto",
+            ForwardedParam::Host => "host",
+            ForwardedParam::For => "for",
+        }
+    }
+}
+
+/// Condition that matches a parameter of the RFC 7239 `Forwarded` header
+///
+/// Proxies add an element to the `Forwarded` header for every hop, either
+/// by appending to a comma-separated list or by sending another header line.
+/// The condition takes the chosen parameter from the first element which
+/// carries it, as that is the one closest to the client, and matches the
+/// pattern against its value. Quoted values are unquoted before matching, so
+/// `for="[2001:db8::1]:4711"` is matched as `[2001:db8::1]:4711`. Parameter
+/// names are case-insensitive. If no element carries the parameter the
+/// condition does not match.
+///
+/// # Examples
+///
+/// ```
+/// use http_rewriter::{Condition, ForwardedCondition, ForwardedParam};
+/// use http::Request;
+///
+/// let https = ForwardedCondition::new(ForwardedParam::Proto, "^https$").unwrap();
+///
+/// let request = Request::builder()
+///     .uri("/login")
+///     .header("Forwarded", "for=192.0.2.60;proto=https;host=example.com")
+///     .body(())
+///     .unwrap();
+/// assert!(https.matches(&request));
+///
+/// let request = Request::builder()
+///     .uri("/login")
+///     .header("Forwarded", "for=192.0.2.60;proto=http")
+///     .body(())
+///     .unwrap();
+/// assert!(!https.matches(&request));
+/// ```
+#[derive(Debug, Clone)]
+pub struct ForwardedCondition {
+    param: ForwardedParam,
+    pattern: Regex,
+}
+
+impl ForwardedCondition {
+    /// Create a new forwarded condition
+    ///
+    /// # Arguments
+    ///
+    /// * `param` - The `Forwarded` parameter to match against
+    /// * `pattern` - A regular expression pattern to match against the parameter value
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the pattern is not a valid regular expression
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use http_rewriter::{ForwardedCondition, ForwardedParam};
+    ///
+    /// // Match clients from the 203.0.113.0/24 documentation range
+    /// let condition = ForwardedCondition::new(ForwardedParam::For, r"^203\.0\.113\.").unwrap();
+    /// ```
+    pub fn new(param: ForwardedParam, pattern: impl AsRef<str>) -> Result<Self, regex::Error> {
+        Ok(Self::from_regex(param, Regex::new(pattern.as_ref())?))
+    }
+
+    /// Create a new forwarded condition from an already compiled regular expression
+    ///
+    /// # Arguments
+    ///
+    /// * `param` - The `Forwarded` parameter to match against
+    /// * `pattern` - Compiled regular expression to match against the parameter value
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use http_rewriter::{ForwardedCondition, ForwardedParam};
+    /// use regex::Regex;
+    ///
+    /// let condition = ForwardedCondition::from_regex(
+    ///     ForwardedParam::Host,
+    ///     Regex::new(r"^api\.").unwrap(),
+    /// );
+    /// ```
+    pub fn from_regex(param: ForwardedParam, pattern: Regex) -> Self {
+        Self { param, pattern }
+    }
+}
+
+impl Condition for ForwardedCondition {
+    fn matches<B>(&self, request: &Request<B>) -> bool {
+        forwarded_param(request, self.param).is_some_and(|value| self.pattern.is_match(&value))
+    }
+
+    fn patterns(&self) -> Vec<&str> {
+        vec![self.pattern.as_str()]
+    }
+}
+
+/// Get a parameter from the first `Forwarded` element which carries it
+fn forwarded_param<B>(request: &Request<B>, param: ForwardedParam) -> Option<String> {
+    request
+        .headers()
+        .get_all("forwarded")
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| split_unquoted(value, ','))
+        .find_map(|element| {
+            split_unquoted(element, ';').find_map(|pair| {
+                let (name, value) = pair.split_once('=')?;
+                name.trim()
+                    .eq_ignore_ascii_case(param.name())
+                    .then(|| unquote(value.trim()))
+            })
+        })
+}
+
+/// Split a header value on a separator, ignoring separators in quoted strings
+fn split_unquoted(value: &str, separator: char) -> impl Iterator<Item = &str> {
+    let mut rest = Some(value);
+    std::iter::from_fn(move || {
+        let value = rest?;
+        let mut quoted = false;
+        let mut escaped = false;
+        for (i, c) in value.char_indices() {
+            match c {
+                _ if escaped => escaped = false,
+                '\\' if quoted => escaped = true,
+                '"' => quoted = !quoted,
+                c if c == separator && !quoted => {
+                    rest = Some(&value[i + 1..]);
+                    return Some(&value[..i]);
+                }
+                _ => {}
+            }
+        }
+        rest = None;
+        Some(value)
+    })
+}
+
+/// Remove the quotes and escapes from a quoted string, if it is one
+fn unquote(value: &str) -> String {
+    let Some(inner) = value
+        .strip_prefix('"')
+        .and_then(|value| value.strip_suffix('"'))
+    else {
+        return value.to_string();
+    };
+    let mut unquoted = String::with_capacity(inner.len());
+    let mut chars = inner.chars();
+    while let Some(c) = chars.next() {
+        if c == '\\' {
+            unquoted.extend(chars.next());
+        } else {
+            unquoted.push(c);
+        }
+    }
+    unquoted
+}
+
+/// Comparison used by conditions which compare a count against a limit
+///
+/// # Examples
+///
+/// ```
+/// use http_rewriter::Comparison;
+///
+/// assert!(Comparison::GreaterOrEqual.compare(3, 3));
+/// assert!(!Comparison::Less.compare(3, 3));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Comparison {
+    /// The value equals the limit
+    Equal,
+    /// The value does not equal the limit
+    NotEqual,
+    /// The value is less than the limit
+    Less,
+    /// The value is less than or equal to the limit
+    LessOrEqual,
+    /// The value is greater than the limit
+    Greater,
+    /// The value is greater than or equal to the limit
+    GreaterOrEqual,
+}
+
+impl Comparison {
+    /// Compare a value against a limit
+    pub fn compare(self, value: usize, limit: usize) -> bool {
+        match self {
+            Comparison::Equal => value == limit,
+            Comparison::NotEqual => value != limit,
+            Comparison::Less => value < limit,
+            Comparison::LessOrEqual => value <= limit,
+            Comparison::Greater => value > limit,
+            Comparison::GreaterOrEqual => value >= limit,
+        }
+    }
+}
+
+/// Condition that matches when the client accepts a content coding
+///
+/// Every `Accept-Encoding` header is parsed into codings with their quality
+/// values. The codec is acceptable when it is listed with a nonzero quality,
+/// or when it is not listed and `*` is, with a nonzero quality. An explicit
+/// `;q=0` excludes the codec even when `*` allows everything else. Codings
+/// and parameter names are compared ignoring ASCII case, and entries with an
+/// invalid quality are skipped.
+///
+/// When the header is present but has no applicable entry, only `identity`
+/// is acceptable. A request without the header matches, as RFC 9110 says
+/// such a client accepts any coding, unless
+/// [`with_match_missing`](Self::with_match_missing) turns that off.
+///
+/// # Examples
+///
+/// ```
+/// use http_rewriter::{AcceptEncodingCondition, Condition};
+/// use http::Request;
+///
+/// let gzip = AcceptEncodingCondition::new("gzip");
+/// let br = AcceptEncodingCondition::new("br");
+///
+/// let request = Request::builder()
+///     .uri("/app.js")
+///     .header("Accept-Encoding", "gzip;q=0, br")
+///     .body(())
+///     .unwrap();
+/// assert!(!gzip.matches(&request));
+/// assert!(br.matches(&request));
+/// ```
+#[derive(Debug, Clone)]
+pub struct AcceptEncodingCondition {
+    codec: String,
+    match_missing: bool,
+}
+
+impl AcceptEncodingCondition {
+    /// Create a new accept encoding condition
+    ///
+    /// # Arguments
+    ///
+    /// * `codec` - The content coding to check for, such as `gzip`, `br` or `zstd`
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use http_rewriter::AcceptEncodingCondition;
+    ///
+    /// let condition = AcceptEncodingCondition::new("zstd");
+    /// ```
+    pub fn new(codec: impl Into<String>) -> Self {
+        Self {
+            codec: codec.into(),
+            match_missing: true,
+        }
+    }
+
+    /// Set whether requests without an `Accept-Encoding` header match
+    ///
+    /// They match by default, following RFC 9110. Turning this off deviates
+    /// from the RFC, but avoids sending encoded responses to clients such as
+    /// scripts which never ask for them.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use http_rewriter::{AcceptEncodingCondition, Condition};
+    /// use http::Request;
+    ///
+    /// let condition = AcceptEncodingCondition::new("gzip").with_match_missing(false);
+    ///
+    /// let request = Request::builder().uri("/app.js").body(()).unwrap();
+    /// assert!(!condition.matches(&request));
+    /// ```
+    pub fn with_match_missing(mut self, match_missing: bool) -> Self {
+        self.match_missing = match_missing;
+        self
+    }
+}
+
+impl Condition for AcceptEncodingCondition {
+    fn matches<B>(&self, request: &Request<B>) -> bool {
+        if !request
+            .headers()
+            .contains_key(http::header::ACCEPT_ENCODING)
+        {
+            return self.match_missing;
+        }
+        let mut explicit = None;
+        let mut wildcard = None;
+        let codings = request
+            .headers()
+            .get_all(http::header::ACCEPT_ENCODING)
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .flat_map(|value| value.split(','))
+            .filter_map(|entry| {
+                let mut params = entry.split(';');
+                let coding = params.next()?.trim();
+                let quality = params
+                    .filter_map(|param| param.split_once('='))
+                    .find(|(name, _)| name.trim().eq_ignore_ascii_case("q"))
+                    .map(|(_, q)| q)
+                    .map_or(Some(1.0), |q| q.trim().parse::<f32>().ok())?;
+                Some((coding, quality))
+            });
+        for (coding, quality) in codings {
+            if coding.eq_ignore_ascii_case(&self.codec) {
+                explicit = Some(quality);
+            } else if coding == "*" {
+                wildcard = Some(quality);
+            }
+        }
+
+        // An explicit entry overrides the wildcard, which covers the rest
+        match explicit.or(wildcard) {
+            Some(quality) => quality > 0.0,
+            None => self.codec.eq_ignore_ascii_case("identity"),
+        }
+    }
+}
+
+/// Condition that matches on the number of proxy hops in `X-Forwarded-For`
+///
+/// Each proxy in a chain appends the address it received the request from to
+/// the `X-Forwarded-For` header, so the number of comma-separated entries is
+/// the number of hops the request took. Entries are counted across every
+/// `X-Forwarded-For` header, ignoring empty ones. A request without the header
+/// has a count of zero.
+///
+/// # Examples
+///
+/// ```
+/// use http_rewriter::{Comparison, Condition, ForwardedForCountCondition};
+/// use http::Request;
+///
+/// // Detect requests which passed through more than one proxy
+/// let multi_hop = ForwardedForCountCondition::new(Comparison::Greater, 1);
+///
+/// let request = Request::builder()
+///     .uri("/")
+///     .header("X-Forwarded-For", "203.0.113.7, 10.0.0.2")
+///     .body(())
+///     .unwrap();
+/// assert!(multi_hop.matches(&request));
+///
+/// let request = Request::builder().uri("/").body(()).unwrap();
+/// assert!(!multi_hop.matches(&request));
+/// ```
+#[derive(Debug, Clone)]
+pub struct ForwardedForCountCondition {
+    comparison: Comparison,
+    count: usize,
+}
+
+impl ForwardedForCountCondition {
+    /// Create a new forwarded for count condition
+    ///
+    /// # Arguments
+    ///
+    /// * `comparison` - How to compare the number of hops against `count`
+    /// * `count` - The number of hops to compare against
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use http_rewriter::{Comparison, ForwardedForCountCondition};
+    ///
+    /// // Only requests sent directly to us
+    /// let condition = ForwardedForCountCondition::new(Comparison::Equal, 0);
+    /// ```
+    pub fn new(comparison: Comparison, count: usize) -> Self {
+        Self { comparison, count }
+    }
+}
+
+impl Condition for ForwardedForCountCondition {
+    fn matches<B>(&self, request: &Request<B>) -> bool {
+        let hops = request
+            .headers()
+            .get_all("x-forwarded-for")
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .flat_map(|value| value.split(','))
+            .filter(|entry| !entry.trim().is_empty())
+            .count();
+        self.comparison.compare(hops, self.count)
+    }
+}
+
+/// Condition that matches on the length of the request target
+///
+/// The target is the path and query of the request URI, such as
+/// `/search?q=rust`, and its length is counted in bytes as sent, without
+/// decoding. The scheme and authority of absolute URIs are not counted, so
+/// the limit applies the same way to requests sent directly and through a
+/// proxy. Authority-form URIs, used by `CONNECT`, have a length of zero.
+///
+/// This is useful for rejecting or rerouting requests with oversized targets
+/// before they reach a backend with a lower limit.
+///
+/// # Examples
+///
+/// ```
+/// use http_rewriter::{Comparison, Condition, TargetLengthCondition};
+/// use http::Request;
+///
+/// let too_long = TargetLengthCondition::new(Comparison::Greater, 16);
+///
+/// let request = Request::builder().uri("/search?q=rust").body(()).unwrap();
+/// assert!(!too_long.matches(&request));
+///
+/// let request = Request::builder().uri("/search?q=http+rewriter").body(()).unwrap();
+/// assert!(too_long.matches(&request));
+/// ```
+#[derive(Debug, Clone)]
+pub struct TargetLengthCondition {
+    comparison: Comparison,
+    length: usize,
+}
+
+impl TargetLengthCondition {
+    /// Create a new target length condition
+    ///
+    /// # Arguments
+    ///
+    /// * `comparison` - How to compare the target length against `length`
+    /// * `length` - The length in bytes to compare against
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use http_rewriter::{Comparison, TargetLengthCondition};
+    ///
+    /// // Targets longer than 8 KiB
+    /// let condition = TargetLengthCondition::new(Comparison::Greater, 8192);
+    /// ```
+    pub fn new(comparison: Comparison, length: usize) -> Self {
+        Self { comparison, length }
+    }
+}
+
+impl Condition for TargetLengthCondition {
+    fn matches<B>(&self, request: &Request<B>) -> bool {
+        let length = request
+            .uri()
+            .path_and_query()
+            .map_or(0, |target| target.as_str().len());
+        self.comparison.compare(length, self.length)
+    }
+}
+
+/// Condition that matches request targets containing path traversal sequences
+///
+/// The raw path and query are scanned for a `..` segment, bounded by `/`,
+/// `\`, `?`, `&`, `=` or the ends of the target. A `;` also ends the segment,
+/// since servers such as Tomcat strip segment parameters and treat `..;/` as
+/// `../`. The percent-encoded forms of `.`, `/`, `\` and `;` are decoded
+/// first, so `%2e%2e%2f`, `..%2F` and `..%3b/` match as well as a literal
+/// `../`. Nothing else is decoded, and segments such as `...` or `..foo` do
+/// not match.
+///
+/// Path normalization may collapse these sequences, or decode them in a later
+/// stage, so pair this condition with a rule rejecting the request rather than
+/// relying on the rewritten path.
+///
+/// # Examples
+///
+/// ```
+/// use http_rewriter::{Condition, TraversalCondition};
+/// use http::Request;
+///
+/// let condition = TraversalCondition::new();
+///
+/// let request = Request::builder().uri("/static/%2e%2e%2fsecret").body(()).unwrap();
+/// assert!(condition.matches(&request));
+///
+/// let request = Request::builder().uri("/static/app.v2..min.js").body(()).unwrap();
+/// assert!(!condition.matches(&request));
+/// ```
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TraversalCondition;
+
+impl TraversalCondition {
+    /// Create a new traversal condition
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use http_rewriter::TraversalCondition;
+    ///
+    /// let condition = TraversalCondition::new();
+    /// ```
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl Condition for TraversalCondition {
+    fn matches<B>(&self, request: &Request<B>) -> bool {
+        let Some(target) = request.uri().path_and_query() else {
+            return false;
+        };
+        decode_traversal_escapes(target.as_str())
+            .split(['/', '\\', '?', '&', '=', ';'])
+            .any(|segment| segment == "..")
+    }
+}
+
+// Decode the escapes of `.`, `/`, `\` and `;`, leaving every other byte as sent
+fn decode_traversal_escapes(target: &str) -> Cow<'_, str> {
+    if !target.contains('%') {
+        return Cow::Borrowed(target);
+    }
+    let mut decoded = String::with_capacity(target.len());
+    let mut rest = target;
+    while let Some(position) = rest.find('%') {
+        decoded.push_str(&rest[..position]);
+        rest = &rest[position..];
+        let escaped = match rest.get(1..3) {
+            Some(hex) if hex.eq_ignore_ascii_case("2e") => Some('.'),
+            Some(hex) if hex.eq_ignore_ascii_case("2f") => Some('/'),
+            Some(hex) if hex.eq_ignore_ascii_case("5c") => Some('\\'),
+            Some(hex) if hex.eq_ignore_ascii_case("3b") => Some(';'),
+            _ => None,
+        };
+        match escaped {
+            Some(c) => {
+                decoded.push(c);
+                rest = &rest[3..];
+            }
+            None => {
+                decoded.push('%');
+                rest = &rest[1..];
+            }
+        }
+    }
+    decoded.push_str(rest);
+    Cow::Owned(decoded)
+}
+
+type CookieMap = dyn Fn(&str) -> Option<String> + Send + Sync;
+
+/// Condition that matches a request cookie against a regular expression pattern
+///
+/// Cookies are read from every `Cookie` header on the request. The condition
+/// matches if any cookie with the given name has a value matching the pattern.
+/// Cookie names are case-sensitive, and values are matched as sent, without
+/// decoding.
+///
+/// # Examples
+///
+/// ```
+/// use http_rewriter::{Condition, CookieCondition};
+/// use http::Request;
+///
+/// let condition = CookieCondition::new("theme", "^dark$").unwrap();
+///
+/// let request = Request::builder()
+///     .uri("/")
+///     .header("Cookie", "session=abc123; theme=dark")
+///     .body(())
+///     .unwrap();
+/// assert!(condition.matches(&request));
+/// ```
+#[derive(Clone)]
+pub struct CookieCondition {
+    name: String,
+    pattern: Regex,
+    map: Option<Arc<CookieMap>>,
+}
+
+impl CookieCondition {
+    /// Create a new cookie condition
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The cookie name to check (case-sensitive)
+    /// * `pattern` - A regular expression pattern to match against the cookie value
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the pattern is not a valid regular expression
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use http_rewriter::CookieCondition;
+    ///
+    /// let condition = CookieCondition::new("beta", "^(1|true)$").unwrap();
+    /// ```
+    pub fn new(name: impl Into<String>, pattern: impl AsRef<str>) -> Result<Self, regex::Error> {
+        Ok(Self {
+            name: name.into(),
+            pattern: Regex::new(pattern.as_ref())?,
+            map: None,
+        })
+    }
+
+    /// Create a new cookie condition which transforms the cookie value before matching
+    ///
+    /// The transform can extract or decode part of the value, such as a claim
+    /// from a token. If it returns `None` the cookie does not match.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The cookie name to check (case-sensitive)
+    /// * `map` - Transform applied to the cookie value before matching
+    /// * `pattern` - A regular expression pattern to match against the transformed value
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the pattern is not a valid regular expression
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use http_rewriter::{Condition, CookieCondition};
+    /// use http::Request;
+    ///
+    /// // Match the payload segment of a `header.payload.signature` token
+    /// let condition = CookieCondition::new_mapped(
+    ///     "token",
+    ///     |value| value.split('.').nth(1).map(str::to_string),
+    ///     "^admin$",
+    /// )
+    /// .unwrap();
+    ///
+    /// let request = Request::builder()
+    ///     .uri("/")
+    ///     .header("Cookie", "token=eyJhbGciOiJIUzI1NiJ9.admin.c2lnbmF0dXJl")
+    ///     .body(())
+    ///     .unwrap();
+    /// assert!(condition.matches(&request));
+    ///
+    /// // A token without a payload segment never matches
+    /// let request = Request::builder()
+    ///     .uri("/")
+    ///     .header("Cookie", "token=admin")
+    ///     .body(())
+    ///     .unwrap();
+    /// assert!(!condition.matches(&request));
+    /// ```
+    pub fn new_mapped<F>(
+        name: impl Into<String>,
+        map: F,
+        pattern: impl AsRef<str>,
+    ) -> Result<Self, regex::Error>
+    where
+        F: Fn(&str) -> Option<String> + Send + Sync + 'static,
+    {
+        Ok(Self {
+            name: name.into(),
+            pattern: Regex::new(pattern.as_ref())?,
+            map: Some(Arc::new(map)),
+        })
+    }
+}
+
+impl Debug for CookieCondition {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        f.debug_struct("CookieCondition")
+            .field("name", &self.name)
+            .field("pattern", &self.pattern)
+            .field("mapped", &self.map.is_some())
+            .finish()
+    }
+}
+
+impl Condition for CookieCondition {
+    fn matches<B>(&self, request: &Request<B>) -> bool {
+        request
+            .headers()
+            .get_all(http::header::COOKIE)
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .flat_map(|value| value.split(';'))
+            .filter_map(|cookie| cookie.trim().split_once('='))
+            .filter(|(name, _)| *name == self.name)
+            .any(|(_, value)| match &self.map {
+                Some(map) => map(value).is_some_and(|value| self.pattern.is_match(&value)),
+                None => self.pattern.is_match(value),
+            })
+    }
+
+    fn patterns(&self) -> Vec<&str> {
+        vec![self.pattern.as_str()]
+    }
+}
+
+/// Condition that matches if a file exists on the filesystem
 ///
 /// This condition checks if the request path, when resolved relative to the
 /// document root stored in the request extensions, corresponds to an existing
@@ -364,8 +3277,9 @@ impl Condition for HeaderCondition {
 /// static file serving.
 ///
 /// The document root must be set in the request extensions using the
-/// `DocumentRoot` type. If no document root is set, the condition will
-/// not match.
+/// `DocumentRoot` type, or given up front with [`ExistenceCondition::with_root`].
+/// A document root in the request extensions takes precedence over the default
+/// one. If neither is set, the condition will not match.
 ///
 /// # Security Note
 ///
@@ -391,37 +3305,90 @@ impl Condition for HeaderCondition {
 /// // This would check for /var/www/html/index.html
 /// let exists = condition.matches(&request);
 /// ```
-#[derive(Debug, Clone, Copy, Default)]
-pub struct ExistenceCondition;
+#[derive(Debug, Clone)]
+pub struct ExistenceCondition {
+    root: Option<PathBuf>,
+    follow_symlinks: bool,
+}
+
+impl Default for ExistenceCondition {
+    fn default() -> Self {
+        Self {
+            root: None,
+            follow_symlinks: true,
+        }
+    }
+}
 
 impl ExistenceCondition {
     /// Create a new existence condition
     ///
-    /// The document root must be provided via the request extensions
-    /// using the `DocumentRoot` type.
+    /// The document root must be provided via the request extensions
+    /// using the `DocumentRoot` type.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use http_rewriter::ExistenceCondition;
+    ///
+    /// let condition = ExistenceCondition::new();
+    /// ```
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a new existence condition with a default document root
+    ///
+    /// The given document root is used for requests which do not carry
+    /// their own `DocumentRoot` in the request extensions.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use http_rewriter::{Condition, ExistenceCondition};
+    /// use http::Request;
+    ///
+    /// let condition = ExistenceCondition::with_root("/var/www/html".to_string().into());
+    ///
+    /// let request = Request::builder()
+    ///     .uri("/index.html")
+    ///     .body(())
+    ///     .unwrap();
+    ///
+    /// // This would check for /var/www/html/index.html
+    /// let exists = condition.matches(&request);
+    /// ```
+    pub fn with_root(root: DocumentRoot) -> Self {
+        Self {
+            root: Some(root.path),
+            ..Self::default()
+        }
+    }
+
+    /// Set whether symbolic links are followed when checking the path
+    ///
+    /// Symbolic links are followed by default. When disabled, the condition
+    /// only matches if no component of the path below the document root is a
+    /// symbolic link, so a link can not lead outside of the document root.
+    /// The document root itself may still be a symbolic link.
     ///
     /// # Examples
     ///
     /// ```
     /// use http_rewriter::ExistenceCondition;
     ///
-    /// let condition = ExistenceCondition::new();
+    /// let condition = ExistenceCondition::new().with_follow_symlinks(false);
     /// ```
-    pub fn new() -> Self {
-        Self
+    pub fn with_follow_symlinks(mut self, follow_symlinks: bool) -> Self {
+        self.follow_symlinks = follow_symlinks;
+        self
     }
 }
 
 impl Condition for ExistenceCondition {
     fn matches<B>(&self, request: &Request<B>) -> bool {
-        if let Some(doc_root) = request.document_root() {
-            let path = request.uri().path();
-            let stripped = path.strip_prefix('/').unwrap_or(path);
-            doc_root.join(stripped).exists()
-        } else {
-            // No document root set, cannot check existence
-            false
-        }
+        // No document root set, cannot check existence
+        path_exists(request, self.root.as_deref(), self.follow_symlinks) == Some(true)
     }
 }
 
@@ -433,8 +3400,9 @@ impl Condition for ExistenceCondition {
 /// file is missing, such as routing to a front controller.
 ///
 /// The document root must be set in the request extensions using the
-/// `DocumentRoot` type. If no document root is set, the condition will
-/// not match.
+/// `DocumentRoot` type, or given up front with [`NonExistenceCondition::with_root`].
+/// A document root in the request extensions takes precedence over the default
+/// one. If neither is set, the condition will not match.
 ///
 /// # Examples
 ///
@@ -459,37 +3427,610 @@ impl Condition for ExistenceCondition {
 /// // Returns true if /var/www/html/some/route doesn't exist
 /// let should_rewrite = condition.matches(&request);
 /// ```
-#[derive(Debug, Clone, Copy, Default)]
-pub struct NonExistenceCondition;
+#[derive(Debug, Clone)]
+pub struct NonExistenceCondition {
+    root: Option<PathBuf>,
+    follow_symlinks: bool,
+}
+
+impl Default for NonExistenceCondition {
+    fn default() -> Self {
+        Self {
+            root: None,
+            follow_symlinks: true,
+        }
+    }
+}
+
+impl NonExistenceCondition {
+    /// Create a new non-existence condition
+    ///
+    /// The document root must be provided via the request extensions
+    /// using the `DocumentRoot` type.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use http_rewriter::NonExistenceCondition;
+    ///
+    /// let condition = NonExistenceCondition::new();
+    /// ```
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a new non-existence condition with a default document root
+    ///
+    /// The given document root is used for requests which do not carry
+    /// their own `DocumentRoot` in the request extensions.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use http_rewriter::{Condition, NonExistenceCondition};
+    /// use http::Request;
+    ///
+    /// let condition = NonExistenceCondition::with_root("/var/www/html".to_string().into());
+    ///
+    /// let request = Request::builder()
+    ///     .uri("/some/route")
+    ///     .body(())
+    ///     .unwrap();
+    ///
+    /// // Returns true if /var/www/html/some/route doesn't exist
+    /// let missing = condition.matches(&request);
+    /// ```
+    pub fn with_root(root: DocumentRoot) -> Self {
+        Self {
+            root: Some(root.path),
+            ..Self::default()
+        }
+    }
+
+    /// Set whether symbolic links are followed when checking the path
+    ///
+    /// Symbolic links are followed by default. When disabled, the path counts
+    /// as missing if any component of it below the document root is a
+    /// symbolic link, so a link can not lead outside of the document root.
+    /// The document root itself may still be a symbolic link.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use http_rewriter::NonExistenceCondition;
+    ///
+    /// let condition = NonExistenceCondition::new().with_follow_symlinks(false);
+    /// ```
+    pub fn with_follow_symlinks(mut self, follow_symlinks: bool) -> Self {
+        self.follow_symlinks = follow_symlinks;
+        self
+    }
+}
+
+impl Condition for NonExistenceCondition {
+    fn matches<B>(&self, request: &Request<B>) -> bool {
+        // No document root set, cannot check existence
+        path_exists(request, self.root.as_deref(), self.follow_symlinks) == Some(false)
+    }
+}
+
+/// Condition that matches the document root of the request against a pattern
+///
+/// The document root is read from the `DocumentRoot` type in the request
+/// extensions, as set by the framework, and its path is matched against a
+/// regular expression. This lets rules apply to some roots only, such as the
+/// root of one tenant when each tenant is served from its own directory.
+///
+/// The condition does not match when the request has no document root, or
+/// when its path is not valid UTF-8. Unlike [`ExistenceCondition`], it never
+/// touches the filesystem.
+///
+/// # Examples
+///
+/// ```
+/// use http_handler::RequestBuilderExt;
+/// use http_rewriter::{Condition, DocumentRootCondition};
+/// use http::Request;
+///
+/// let condition = DocumentRootCondition::new("^/srv/tenants/acme(/|$)").unwrap();
+///
+/// let request = Request::builder()
+///     .uri("/index.php")
+///     .document_root("/srv/tenants/acme/public".to_string().into())
+///     .body(())
+///     .unwrap();
+/// assert!(condition.matches(&request));
+///
+/// // Requests without a document root never match
+/// let request = Request::builder().uri("/index.php").body(()).unwrap();
+/// assert!(!condition.matches(&request));
+/// ```
+#[derive(Debug, Clone)]
+pub struct DocumentRootCondition {
+    pattern: Regex,
+}
+
+impl DocumentRootCondition {
+    /// Create a new document root condition with the given regular expression pattern
+    ///
+    /// # Arguments
+    ///
+    /// * `pattern` - A regular expression pattern to match against the document root path
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the pattern is not a valid regular expression
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use http_rewriter::DocumentRootCondition;
+    ///
+    /// let condition = DocumentRootCondition::new("^/var/www/").unwrap();
+    /// assert!(DocumentRootCondition::new("[unclosed").is_err());
+    /// ```
+    pub fn new(pattern: impl AsRef<str>) -> Result<Self, regex::Error> {
+        Ok(Self::from_regex(Regex::new(pattern.as_ref())?))
+    }
+
+    /// Create a new document root condition from an already compiled regular expression
+    ///
+    /// # Arguments
+    ///
+    /// * `pattern` - Compiled regular expression to match against the document root path
+    pub fn from_regex(pattern: Regex) -> Self {
+        Self { pattern }
+    }
+}
+
+impl Condition for DocumentRootCondition {
+    fn matches<B>(&self, request: &Request<B>) -> bool {
+        request
+            .document_root()
+            .and_then(|root| root.to_str())
+            .is_some_and(|root| self.pattern.is_match(root))
+    }
+
+    fn patterns(&self) -> Vec<&str> {
+        vec![self.pattern.as_str()]
+    }
+}
+
+/// Check if the request path exists below the document root from the request
+/// extensions, falling back to the given default document root
+///
+/// Returns `None` if there is no document root to resolve the path against.
+fn path_exists<B>(
+    request: &Request<B>,
+    default_root: Option<&Path>,
+    follow_symlinks: bool,
+) -> Option<bool> {
+    let path = request.uri().path();
+    let relative = Path::new(path.strip_prefix('/').unwrap_or(path));
+    let root: &Path = match request.document_root() {
+        Some(doc_root) => doc_root,
+        None => default_root?,
+    };
+
+    if follow_symlinks {
+        return Some(root.join(relative).exists());
+    }
+
+    // Check each component below the root, so that a symlinked directory
+    // along the way can't lead outside of the document root either
+    if !root.exists() {
+        return Some(false);
+    }
+    let mut current = root.to_path_buf();
+    for component in relative.components() {
+        current.push(component);
+        match current.symlink_metadata() {
+            Ok(metadata) if !metadata.file_type().is_symlink() => {}
+            _ => return Some(false),
+        }
+    }
+    Some(true)
+}
+
+/// Condition that assigns requests to weighted buckets and matches one of them
+///
+/// This is meant for A/B and multivariate testing. Each request is assigned
+/// to a bucket with a probability proportional to the bucket's weight, and
+/// the condition matches when that is the selected bucket.
+///
+/// With [`BucketCondition::with_header`] the assignment is derived from a
+/// header, such as a user ID, so the same value always lands in the same
+/// bucket, across restarts and across every instance running the same
+/// configuration. Using one keyed condition per bucket with the same
+/// weights and seed then splits traffic between them, each request matching
+/// exactly one. [`BucketCondition::with_seed`] changes the derived
+/// assignment, so independent experiments keyed on the same header do not
+/// split users the same way.
+///
+/// Without a header, or when a request lacks it, every call to `matches`
+/// draws a new random bucket. Separate conditions draw independently, so a
+/// request may match several of them or none: each condition only matches
+/// its share of requests on average.
+///
+/// # Examples
+///
+/// ```
+/// use http_rewriter::{BucketCondition, Condition};
+/// use http::Request;
+///
+/// // 90% of users stay on A, 10% try B
+/// let variant_b = BucketCondition::new(1, vec![90, 10])
+///     .unwrap()
+///     .with_header("X-User-Id");
+///
+/// let request = Request::builder()
+///     .uri("/")
+///     .header("X-User-Id", "user-42")
+///     .body(())
+///     .unwrap();
+///
+/// // The same user always gets the same answer
+/// let first = variant_b.matches(&request);
+/// assert!((0..10).all(|_| variant_b.matches(&request) == first));
+/// ```
+#[derive(Debug, Clone)]
+pub struct BucketCondition {
+    bucket: usize,
+    weights: Vec<u32>,
+    total: u64,
+    header: Option<String>,
+    seed: u64,
+}
+
+impl BucketCondition {
+    /// Create a new bucket condition
+    ///
+    /// Returns `None` if `bucket` is not an index into `weights`, or if every
+    /// weight is zero.
+    ///
+    /// # Arguments
+    ///
+    /// * `bucket` - Index of the bucket to match
+    /// * `weights` - Relative weight of each bucket
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use http_rewriter::BucketCondition;
+    ///
+    /// // Match the third of three equal buckets
+    /// let condition = BucketCondition::new(2, vec![1, 1, 1]).unwrap();
+    ///
+    /// assert!(BucketCondition::new(3, vec![1, 1, 1]).is_none());
+    /// ```
+    pub fn new(bucket: usize, weights: Vec<u32>) -> Option<Self> {
+        let total = weights.iter().map(|&weight| u64::from(weight)).sum();
+        if bucket >= weights.len() || total == 0 {
+            return None;
+        }
+        Some(Self {
+            bucket,
+            weights,
+            total,
+            header: None,
+            seed: 0,
+        })
+    }
+
+    /// Derive the bucket from a header, so requests with the same value stick to one bucket
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The header name to key the assignment on (case-insensitive)
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use http_rewriter::BucketCondition;
+    ///
+    /// let condition = BucketCondition::new(0, vec![50, 50])
+    ///     .unwrap()
+    ///     .with_header("X-User-Id");
+    /// ```
+    pub fn with_header(mut self, name: impl Into<String>) -> Self {
+        self.header = Some(name.into());
+        self
+    }
+
+    /// Set the seed mixed into header-derived assignments, zero by default
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use http_rewriter::BucketCondition;
+    ///
+    /// let condition = BucketCondition::new(0, vec![50, 50])
+    ///     .unwrap()
+    ///     .with_header("X-User-Id")
+    ///     .with_seed(2024);
+    /// ```
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    /// Pick the bucket a hash value falls into
+    fn bucket_for(&self, hash: u64) -> usize {
+        let mut point = hash % self.total;
+        for (index, &weight) in self.weights.iter().enumerate() {
+            let weight = u64::from(weight);
+            if point < weight {
+                return index;
+            }
+            point -= weight;
+        }
+        unreachable!("point is below the total weight")
+    }
+}
+
+impl Condition for BucketCondition {
+    fn matches<B>(&self, request: &Request<B>) -> bool {
+        let key = self
+            .header
+            .as_ref()
+            .and_then(|name| request.headers().get(name.as_str()));
+        let hash = match key {
+            Some(value) => stable_hash(self.seed, value.as_bytes()),
+            None => random_u64(),
+        };
+        self.bucket_for(hash) == self.bucket
+    }
+}
+
+/// Hash bytes the same way in every process, unlike the std hashers
+///
+/// This is FNV-1a followed by the SplitMix64 finalizer, which spreads the
+/// weak low bits of FNV across the whole value.
+fn stable_hash(seed: u64, bytes: &[u8]) -> u64 {
+    let mut hash = 0xcbf2_9ce4_8422_2325 ^ seed;
+    for &byte in bytes {
+        hash ^= u64::from(byte);
+        hash = hash.wrapping_mul(0x0100_0000_01b3);
+    }
+    hash ^= hash >> 30;
+    hash = hash.wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    hash ^= hash >> 27;
+    hash = hash.wrapping_mul(0x94d0_49bb_1331_11eb);
+    hash ^ (hash >> 31)
+}
+
+/// Get a random value from the randomly keyed std hasher
+fn random_u64() -> u64 {
+    static COUNTER: AtomicU64 = AtomicU64::new(0);
+    RandomState::new().hash_one(COUNTER.fetch_add(1, Ordering::Relaxed))
+}
+
+/// Condition that matches when a marker of type `T` is present in the request extensions
+///
+/// Markers are inserted by [`MarkRewriter`](crate::MarkRewriter). This lets a
+/// later stage of a rewrite pipeline fire only when an earlier stage ran,
+/// without having to recompute the earlier stage's conditions.
+///
+/// # Examples
+///
+/// ```
+/// use http_rewriter::{
+///     Condition, MarkRewriter, MarkerCondition, PathCondition, PathRewriter, Rewriter, RewriterExt,
+/// };
+/// use http::Request;
+///
+/// #[derive(Clone)]
+/// struct Legacy;
+///
+/// // Stage one tags legacy requests, stage two acts on the tag
+/// let rewriter = MarkRewriter::new(Legacy)
+///     .when(PathCondition::new("^/old/").unwrap())
+///     .then(PathRewriter::new("^/old/", "/new/").unwrap().when(MarkerCondition::<Legacy>::new()));
+///
+/// let request = Request::builder()
+///     .uri("/old/page")
+///     .body(())
+///     .unwrap();
+/// let result = rewriter.rewrite(request).unwrap();
+/// assert_eq!(result.uri().path(), "/new/page");
+/// assert!(MarkerCondition::<Legacy>::new().matches(&result));
+/// ```
+pub struct MarkerCondition<T>(PhantomData<fn() -> T>);
+
+impl<T: Send + Sync + 'static> MarkerCondition<T> {
+    /// Create a new marker condition
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use http_rewriter::MarkerCondition;
+    ///
+    /// struct Authenticated;
+    ///
+    /// let condition = MarkerCondition::<Authenticated>::new();
+    /// ```
+    pub fn new() -> Self {
+        Self(PhantomData)
+    }
+}
+
+impl<T: Send + Sync + 'static> Default for MarkerCondition<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Clone for MarkerCondition<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for MarkerCondition<T> {}
+
+impl<T> Debug for MarkerCondition<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        f.debug_tuple("MarkerCondition")
+            .field(&std::any::type_name::<T>())
+            .finish()
+    }
+}
+
+impl<T: Send + Sync + 'static> Condition for MarkerCondition<T> {
+    fn matches<B>(&self, request: &Request<B>) -> bool {
+        request.extensions().get::<T>().is_some()
+    }
+}
+
+/// TLS protocol version, ordered from oldest to newest
+///
+/// # Examples
+///
+/// ```
+/// use http_rewriter::TlsVersion;
+///
+/// assert!(TlsVersion::Tls1_3 > TlsVersion::Tls1_2);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum TlsVersion {
+    /// TLS 1.0
+    Tls1_0,
+    /// TLS 1.1
+    Tls1_1,
+    /// TLS 1.2
+    Tls1_2,
+    /// TLS 1.3
+    Tls1_3,
+}
+
+/// Details of the TLS connection a request arrived on
+///
+/// The server inserts this into the request extensions for requests received
+/// over TLS, so conditions such as [`TlsCondition`] can inspect the
+/// connection without depending on a particular TLS library.
+///
+/// # Examples
+///
+/// ```
+/// use http_rewriter::{TlsInfo, TlsVersion};
+/// use http::Request;
+///
+/// let mut request = Request::builder().uri("/").body(()).unwrap();
+/// request.extensions_mut().insert(TlsInfo {
+///     version: TlsVersion::Tls1_3,
+///     cipher: "TLS_AES_128_GCM_SHA256".to_string(),
+/// });
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct TlsInfo {
+    /// The negotiated protocol version
+    pub version: TlsVersion,
+    /// The negotiated cipher suite, by its IANA name
+    pub cipher: String,
+}
+
+/// Condition that matches on the TLS connection a request arrived on
+///
+/// The connection details are read from a [`TlsInfo`] in the request
+/// extensions. Requests without one, such as plain HTTP requests or requests
+/// from a server which does not record it, never match. With no requirements
+/// set the condition matches any request carrying a [`TlsInfo`].
+///
+/// # Examples
+///
+/// ```
+/// use http_rewriter::{Condition, TlsCondition, TlsInfo, TlsVersion};
+/// use http::Request;
+///
+/// let condition = TlsCondition::new().min_version(TlsVersion::Tls1_2);
+///
+/// let mut request = Request::builder().uri("/admin").body(()).unwrap();
+/// request.extensions_mut().insert(TlsInfo {
+///     version: TlsVersion::Tls1_3,
+///     cipher: "TLS_AES_128_GCM_SHA256".to_string(),
+/// });
+/// assert!(condition.matches(&request));
+///
+/// // No TLS information, no match
+/// let request = Request::builder().uri("/admin").body(()).unwrap();
+/// assert!(!condition.matches(&request));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct TlsCondition {
+    min_version: Option<TlsVersion>,
+    cipher: Option<Regex>,
+}
 
-impl NonExistenceCondition {
-    /// Create a new non-existence condition
-    ///
-    /// The document root must be provided via the request extensions
-    /// using the `DocumentRoot` type.
+impl TlsCondition {
+    /// Create a new TLS condition matching any TLS connection
     ///
     /// # Examples
     ///
     /// ```
-    /// use http_rewriter::NonExistenceCondition;
+    /// use http_rewriter::TlsCondition;
     ///
-    /// let condition = NonExistenceCondition::new();
+    /// let condition = TlsCondition::new();
     /// ```
     pub fn new() -> Self {
-        Self
+        Self::default()
+    }
+
+    /// Require at least the given protocol version
+    ///
+    /// # Arguments
+    ///
+    /// * `version` - The oldest protocol version to match
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use http_rewriter::{TlsCondition, TlsVersion};
+    ///
+    /// let condition = TlsCondition::new().min_version(TlsVersion::Tls1_2);
+    /// ```
+    pub fn min_version(mut self, version: TlsVersion) -> Self {
+        self.min_version = Some(version);
+        self
+    }
+
+    /// Require a cipher suite matching a regular expression pattern
+    ///
+    /// # Arguments
+    ///
+    /// * `pattern` - A regular expression pattern to match against the cipher suite name
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the pattern is not a valid regular expression
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use http_rewriter::TlsCondition;
+    ///
+    /// // Only AEAD cipher suites
+    /// let condition = TlsCondition::new().cipher("(GCM|CHACHA20_POLY1305)").unwrap();
+    /// ```
+    pub fn cipher(mut self, pattern: impl AsRef<str>) -> Result<Self, regex::Error> {
+        self.cipher = Some(Regex::new(pattern.as_ref())?);
+        Ok(self)
     }
 }
 
-impl Condition for NonExistenceCondition {
+impl Condition for TlsCondition {
     fn matches<B>(&self, request: &Request<B>) -> bool {
-        if let Some(doc_root) = request.document_root() {
-            let path = request.uri().path();
-            let stripped = path.strip_prefix('/').unwrap_or(path);
-            !doc_root.path.join(stripped).exists()
-        } else {
-            // No document root set, cannot check existence
-            false
-        }
+        let Some(tls) = request.extensions().get::<TlsInfo>() else {
+            return false;
+        };
+        self.min_version.is_none_or(|min| tls.version >= min)
+            && self
+                .cipher
+                .as_ref()
+                .is_none_or(|pattern| pattern.is_match(&tls.cipher))
+    }
+
+    fn patterns(&self) -> Vec<&str> {
+        self.cipher.iter().map(Regex::as_str).collect()
     }
 }
 
@@ -587,6 +4128,48 @@ where
     }
 }
 
+impl<A: Condition, B: Condition> GroupCondition<A, B> {
+    /// Negate this group by De Morgan's laws
+    ///
+    /// `not (a and b)` becomes `not a or not b`, and `not (a or b)` becomes
+    /// `not a and not b`. The result matches exactly the requests that
+    /// `self.not()` matches, and checks the same conditions in the same order,
+    /// stopping as soon as the outcome is known; only the shape changes. This
+    /// is an optimization of structure rather than a change of meaning: the
+    /// negation sits on the leaves, so the result is itself a group which can
+    /// be combined or negated again without nesting [`NotCondition`]s.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use http_rewriter::{Condition, ConditionExt, MethodCondition, PathCondition};
+    /// use http::{Method, Request};
+    ///
+    /// let admin_write = PathCondition::new("^/admin/").unwrap()
+    ///     .and(MethodCondition::new(Method::POST).unwrap());
+    ///
+    /// // Anything but a POST to /admin/
+    /// let other = admin_write.negated();
+    ///
+    /// let request = Request::builder()
+    ///     .method(Method::GET)
+    ///     .uri("/admin/users")
+    ///     .body(())
+    ///     .unwrap();
+    /// assert!(other.matches(&request));
+    /// ```
+    pub fn negated(self) -> GroupCondition<NotCondition<A>, NotCondition<B>> {
+        match self {
+            GroupCondition::And(a, b) => {
+                GroupCondition::Or(Box::new(NotCondition(a)), Box::new(NotCondition(b)))
+            }
+            GroupCondition::Or(a, b) => {
+                GroupCondition::And(Box::new(NotCondition(a)), Box::new(NotCondition(b)))
+            }
+        }
+    }
+}
+
 impl<A, B> Condition for GroupCondition<A, B>
 where
     A: Condition + ?Sized,
@@ -598,6 +4181,13 @@ where
             GroupCondition::Or(a, b) => a.matches(request) || b.matches(request),
         }
     }
+
+    fn patterns(&self) -> Vec<&str> {
+        let (GroupCondition::And(a, b) | GroupCondition::Or(a, b)) = self;
+        let mut patterns = a.patterns();
+        patterns.extend(b.patterns());
+        patterns
+    }
 }
 
 // If conditions A and B implement Clone, GroupCondition should also
@@ -636,6 +4226,241 @@ where
     }
 }
 
+/// Condition that matches when another condition does not match
+///
+/// NotCondition is typically created using the [`ConditionExt`] trait's
+/// `not()` method rather than directly.
+///
+/// # Examples
+///
+/// ```
+/// use http_rewriter::{Condition, ConditionExt, MethodCondition, PathCondition};
+/// use http::{Method, Request};
+///
+/// // Match API requests which are not reads
+/// let condition = PathCondition::new("^/api/").unwrap()
+///     .and(MethodCondition::new(Method::GET).unwrap().not());
+///
+/// let request = Request::builder()
+///     .method(Method::POST)
+///     .uri("/api/users")
+///     .body(())
+///     .unwrap();
+/// assert!(condition.matches(&request));
+///
+/// let request = Request::builder()
+///     .method(Method::GET)
+///     .uri("/api/users")
+///     .body(())
+///     .unwrap();
+/// assert!(!condition.matches(&request));
+/// ```
+#[derive(Debug, Clone)]
+pub struct NotCondition<C>(Box<C>);
+
+impl<C: Condition> NotCondition<C> {
+    /// Create a new condition negating the given condition
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use http_rewriter::{NotCondition, PathCondition};
+    ///
+    /// let condition = NotCondition::new(PathCondition::new("^/static/").unwrap());
+    /// ```
+    pub fn new(condition: C) -> Self {
+        Self(Box::new(condition))
+    }
+}
+
+impl<C: Condition> Condition for NotCondition<C> {
+    fn matches<B>(&self, request: &Request<B>) -> bool {
+        !self.0.matches(request)
+    }
+
+    fn patterns(&self) -> Vec<&str> {
+        self.0.patterns()
+    }
+}
+
+/// Condition that borrows another condition
+///
+/// Conditions can not be implemented for references directly, as that would
+/// overlap with the implementation for closures. This wrapper lets a
+/// condition be used without moving it, such as one condition shared by
+/// several conditional rewriters. It is `Copy`, so it can be passed around
+/// freely.
+///
+/// Usually created with [`ConditionExt::by_ref`]. As [`ConditionExt`] is only
+/// implemented for `'static` conditions, combine borrowed conditions with
+/// [`GroupCondition`] directly.
+///
+/// # Examples
+///
+/// ```
+/// use http_rewriter::{ConditionExt, MethodCondition, PathRewriter, Rewriter, RewriterExt};
+/// use http::{Method, Request};
+///
+/// let post = MethodCondition::new(Method::POST).unwrap();
+///
+/// // Share one condition between two rewriters
+/// let rewriter = PathRewriter::new("^/form/", "/submit/")
+///     .unwrap()
+///     .when(post.by_ref())
+///     .then(PathRewriter::new("^/submit/", "/v2/submit/").unwrap().when(post.by_ref()));
+///
+/// let request = Request::builder()
+///     .method(Method::POST)
+///     .uri("/form/contact")
+///     .body(())
+///     .unwrap();
+/// let result = rewriter.rewrite(request).unwrap();
+/// assert_eq!(result.uri().path(), "/v2/submit/contact");
+/// ```
+#[derive(Debug)]
+pub struct ConditionRef<'a, C: ?Sized>(&'a C);
+
+impl<'a, C: Condition + ?Sized> ConditionRef<'a, C> {
+    /// Create a new condition borrowing the given condition
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use http_rewriter::{ConditionRef, PathCondition};
+    ///
+    /// let condition = PathCondition::new("^/api/").unwrap();
+    /// let borrowed = ConditionRef::new(&condition);
+    /// ```
+    pub fn new(condition: &'a C) -> Self {
+        Self(condition)
+    }
+
+    /// Get the borrowed condition
+    pub fn get(&self) -> &'a C {
+        self.0
+    }
+}
+
+impl<C: ?Sized> Clone for ConditionRef<'_, C> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<C: ?Sized> Copy for ConditionRef<'_, C> {}
+
+impl<C: Condition + ?Sized> Condition for ConditionRef<'_, C> {
+    fn matches<B>(&self, request: &Request<B>) -> bool {
+        self.0.matches(request)
+    }
+
+    fn patterns(&self) -> Vec<&str> {
+        self.0.patterns()
+    }
+}
+
+/// Condition that matches when at least `k` of its conditions match
+///
+/// This sits between AND and OR: with `k` equal to the number of conditions
+/// it behaves like AND, and with `k` of one like OR. A threshold of zero
+/// always matches. Conditions are checked in order and checking stops as
+/// soon as `k` of them have matched.
+///
+/// All conditions share one type. To mix condition types, convert them into
+/// [`config::Condition`](crate::config::Condition) first.
+///
+/// # Examples
+///
+/// ```
+/// use http_rewriter::{Condition, HeaderCondition, ThresholdCondition};
+/// use http::Request;
+///
+/// // Treat a request as coming from a bot when two of three signals are present
+/// let bot = ThresholdCondition::new(
+///     2,
+///     vec![
+///         HeaderCondition::new("User-Agent", "(?i)bot").unwrap(),
+///         HeaderCondition::new("Accept", r"^\*/\*$").unwrap(),
+///         HeaderCondition::new("From", "@").unwrap(),
+///     ],
+/// )
+/// .unwrap();
+///
+/// let request = Request::builder()
+///     .uri("/")
+///     .header("User-Agent", "ExampleBot/1.0")
+///     .header("Accept", "*/*")
+///     .body(())
+///     .unwrap();
+/// assert!(bot.matches(&request));
+///
+/// let request = Request::builder()
+///     .uri("/")
+///     .header("User-Agent", "ExampleBot/1.0")
+///     .body(())
+///     .unwrap();
+/// assert!(!bot.matches(&request));
+/// ```
+#[derive(Debug, Clone)]
+pub struct ThresholdCondition<C> {
+    threshold: usize,
+    conditions: Vec<C>,
+}
+
+impl<C: Condition> ThresholdCondition<C> {
+    /// Create a new threshold condition
+    ///
+    /// # Arguments
+    ///
+    /// * `threshold` - How many of the conditions must match
+    /// * `conditions` - The conditions to check, in order
+    ///
+    /// Returns `None` if the threshold is larger than the number of
+    /// conditions, as such a condition could never match.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use http_rewriter::{PathCondition, ThresholdCondition};
+    ///
+    /// let conditions = vec![
+    ///     PathCondition::new("^/api/").unwrap(),
+    ///     PathCondition::new(r"\.json$").unwrap(),
+    /// ];
+    /// assert!(ThresholdCondition::new(1, conditions.clone()).is_some());
+    /// assert!(ThresholdCondition::new(3, conditions).is_none());
+    /// ```
+    pub fn new(threshold: usize, conditions: impl IntoIterator<Item = C>) -> Option<Self> {
+        let conditions: Vec<C> = conditions.into_iter().collect();
+        (threshold <= conditions.len()).then_some(Self {
+            threshold,
+            conditions,
+        })
+    }
+}
+
+impl<C: Condition> Condition for ThresholdCondition<C> {
+    fn matches<B>(&self, request: &Request<B>) -> bool {
+        if self.threshold == 0 {
+            return true;
+        }
+        let mut matched = 0;
+        self.conditions.iter().any(|condition| {
+            if condition.matches(request) {
+                matched += 1;
+            }
+            matched == self.threshold
+        })
+    }
+
+    fn patterns(&self) -> Vec<&str> {
+        self.conditions
+            .iter()
+            .flat_map(|condition| condition.patterns())
+            .collect()
+    }
+}
+
 /// Extension trait for combining conditions with boolean logic
 ///
 /// This trait provides convenient methods for combining conditions using
@@ -721,11 +4546,175 @@ pub trait ConditionExt: Condition + Sized + 'static {
     fn or<C: Condition + 'static>(self, other: C) -> GroupCondition<Self, C> {
         GroupCondition::Or(Box::new(self), Box::new(other))
     }
+
+    /// Create a new condition that matches when this condition does not
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use http_rewriter::{ConditionExt, PathCondition};
+    ///
+    /// // Match everything outside /static/*
+    /// let condition = PathCondition::new("^/static/").unwrap().not();
+    /// ```
+    fn not(self) -> NotCondition<Self> {
+        NotCondition::new(self)
+    }
+
+    /// Borrow this condition as a condition
+    ///
+    /// Creates a [`ConditionRef`], so this condition can be shared by
+    /// several rewriters without being moved or cloned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use http_rewriter::{ConditionExt, PathCondition, PathRewriter, RewriterExt};
+    ///
+    /// let api = PathCondition::new("^/api/").unwrap();
+    /// let rewriter = PathRewriter::new("^/api/", "/v2/").unwrap().when(api.by_ref());
+    /// ```
+    fn by_ref(&self) -> ConditionRef<'_, Self> {
+        ConditionRef::new(self)
+    }
 }
 
 // Implement ConditionExt for all types that implement Condition
 impl<T: Condition + 'static> ConditionExt for T {}
 
+/// Borrowed view of the request metadata, as seen by [`fn_condition`] closures
+///
+/// The fields mirror those of the request [`Parts`](http::request::Parts),
+/// but borrow them from the request, so nothing is cloned to build the view.
+#[derive(Debug, Clone, Copy)]
+pub struct RequestView<'a> {
+    /// The request method
+    pub method: &'a Method,
+    /// The request URI
+    pub uri: &'a Uri,
+    /// The HTTP version
+    pub version: Version,
+    /// The request headers
+    pub headers: &'a HeaderMap,
+    /// The request extensions
+    pub extensions: &'a Extensions,
+}
+
+impl<'a> RequestView<'a> {
+    /// Borrow the metadata of a request
+    pub fn new<B>(request: &'a Request<B>) -> Self {
+        Self {
+            method: request.method(),
+            uri: request.uri(),
+            version: request.version(),
+            headers: request.headers(),
+            extensions: request.extensions(),
+        }
+    }
+}
+
+/// Condition that matches requests with a closure over a [`RequestView`]
+///
+/// Usually created with [`fn_condition`]. Unlike the closure implementation of
+/// [`Condition`], this works for any body type without reinterpreting the
+/// request. The closure borrows the request metadata, so matching allocates
+/// nothing.
+#[derive(Clone, Copy)]
+pub struct FnCondition<F>(F);
+
+impl<F> Debug for FnCondition<F> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        f.debug_tuple("FnCondition").finish_non_exhaustive()
+    }
+}
+
+impl<F> Condition for FnCondition<F>
+where
+    F: Fn(&RequestView<'_>) -> bool + Send + Sync,
+{
+    fn matches<B>(&self, request: &Request<B>) -> bool {
+        (self.0)(&RequestView::new(request))
+    }
+}
+
+/// Create a condition from a closure over a [`RequestView`] of the request
+///
+/// This is a safe way to write ad-hoc conditions which inspect any of the
+/// request metadata, regardless of the request body type.
+///
+/// # Examples
+///
+/// ```
+/// use http_rewriter::{Condition, fn_condition};
+/// use http::{Method, Request};
+///
+/// let json_post = fn_condition(|parts| {
+///     parts.method == Method::POST
+///         && parts
+///             .headers
+///             .get("content-type")
+///             .is_some_and(|value| value == "application/json")
+/// });
+///
+/// let request = Request::builder()
+///     .method(Method::POST)
+///     .uri("/api/users")
+///     .header("Content-Type", "application/json")
+///     .body(vec![1, 2, 3])
+///     .unwrap();
+/// assert!(json_post.matches(&request));
+///
+/// let request = Request::builder()
+///     .method(Method::GET)
+///     .uri("/api/users")
+///     .header("Content-Type", "application/json")
+///     .body(vec![1, 2, 3])
+///     .unwrap();
+/// assert!(!json_post.matches(&request));
+/// ```
+pub fn fn_condition<F>(f: F) -> FnCondition<F>
+where
+    F: Fn(&RequestView<'_>) -> bool + Send + Sync,
+{
+    FnCondition(f)
+}
+
+/// Create a condition matching write requests without a `Content-Type` header
+///
+/// The condition matches `POST`, `PUT` and `PATCH` requests which have no
+/// `Content-Type` header, so their body can not be interpreted reliably.
+/// This is a common validation rule, for rerouting such requests to an error
+/// handler or rejecting them. It is an AND of a [`MethodCondition`] and a
+/// [`HeaderAbsentCondition`], so it combines like any other condition.
+///
+/// # Examples
+///
+/// ```
+/// use http_rewriter::{Condition, missing_content_type_on_write};
+/// use http::{Method, Request};
+///
+/// let condition = missing_content_type_on_write();
+///
+/// let request = Request::builder()
+///     .method(Method::POST)
+///     .uri("/api/users")
+///     .body(())
+///     .unwrap();
+/// assert!(condition.matches(&request));
+///
+/// let request = Request::builder()
+///     .method(Method::POST)
+///     .uri("/api/users")
+///     .header("Content-Type", "application/json")
+///     .body(())
+///     .unwrap();
+/// assert!(!condition.matches(&request));
+/// ```
+pub fn missing_content_type_on_write() -> GroupCondition<MethodCondition, HeaderAbsentCondition> {
+    let writes = Regex::new("^(?:POST|PUT|PATCH)$").expect("write method pattern is valid");
+    MethodCondition::from_regex(writes).and(HeaderAbsentCondition::new("Content-Type"))
+}
+
 /// Implementation of Condition for closures
 ///
 /// Any closure that takes a `&Request<()>` and returns a `bool` can be used
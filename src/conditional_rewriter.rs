@@ -34,7 +34,8 @@
 
 use super::{
     condition::Condition,
-    rewriter::{RewriteError, Rewriter},
+    context::RewriteContext,
+    rewriter::{Change, RewriteError, Rewriter},
 };
 use http::Request;
 
@@ -137,14 +138,46 @@ impl<R: Rewriter, C: Condition> ConditionalRewriter<R, C> {
     pub fn new(rewriter: Box<R>, condition: Box<C>) -> Self {
         Self(rewriter, condition)
     }
+
+    /// Get the condition which guards the rewriter
+    pub(crate) fn condition(&self) -> &C {
+        &self.1
+    }
 }
 
 impl<R: Rewriter, C: Condition> Rewriter for ConditionalRewriter<R, C> {
     fn rewrite<B>(&self, request: Request<B>) -> Result<Request<B>, RewriteError> {
+        self.rewrite_with_context(request, &mut RewriteContext::new())
+    }
+
+    fn rewrite_with_context<B>(
+        &self,
+        request: Request<B>,
+        context: &mut RewriteContext,
+    ) -> Result<Request<B>, RewriteError> {
+        if self.1.matches(&request) {
+            self.0.rewrite_with_context(request, context)
+        } else {
+            Ok(request)
+        }
+    }
+
+    fn rewrite_with_context_and_log<B>(
+        &self,
+        request: Request<B>,
+        context: &mut RewriteContext,
+        log: &mut Vec<Change>,
+    ) -> Result<Request<B>, RewriteError> {
         if self.1.matches(&request) {
-            self.0.rewrite(request)
+            self.0.rewrite_with_context_and_log(request, context, log)
         } else {
             Ok(request)
         }
     }
+
+    fn patterns(&self) -> Vec<&str> {
+        let mut patterns = self.1.patterns();
+        patterns.extend(self.0.patterns());
+        patterns
+    }
 }
@@ -0,0 +1,2446 @@
+//! Configuration-driven rewriters
+//!
+//! This module describes rewrite rules as plain data, so they can be loaded
+//! from configuration files or assembled at runtime, and turns them into a
+//! type-erased [`Rewriter`].
+//!
+//! # Overview
+//!
+//! A rule is a [`ConditionalRewriterConfig`]: a set of [`ConditionConfig`]s
+//! combined with a [`ConditionOperation`], and a list of [`RewriterConfig`]s
+//! which are applied in order when the conditions match. A list of rules
+//! converts into a single [`Rewriter`] which applies every rule in sequence.
+//!
+//! The N-API `Rewriter` accepts the same configuration from JavaScript and
+//! converts it into these types.
+//!
+//! # Examples
+//!
+//! ```
+//! use http_rewriter::Rewriter as _;
+//! use http_rewriter::config::{
+//!     ConditionConfig, ConditionOperation, ConditionType, ConditionalRewriterConfig,
+//!     Rewriter, RewriterConfig, RewriterType,
+//! };
+//! use http::{Method, Request};
+//!
+//! let rules = vec![
+//!     ConditionalRewriterConfig::new(
+//!         ConditionOperation::And,
+//!         vec![
+//!             ConditionConfig::new(ConditionType::Path, &["^/api/.*"]),
+//!             ConditionConfig::new(ConditionType::Method, &["POST"]),
+//!         ],
+//!         vec![RewriterConfig::new(RewriterType::Path, &["^/api/v1/", "/api/v2/"])],
+//!     ),
+//!     ConditionalRewriterConfig::default()
+//!         .with_rewriter(RewriterConfig::new(RewriterType::Header, &["X-Proxy", ".*", "rewriter"])),
+//! ];
+//!
+//! let rewriter = Rewriter::try_from(rules).unwrap();
+//!
+//! let request = Request::builder()
+//!     .method(Method::POST)
+//!     .uri("/api/v1/users")
+//!     .header("X-Proxy", "none")
+//!     .body(())
+//!     .unwrap();
+//!
+//! let result = rewriter.rewrite(request).unwrap();
+//! assert_eq!(result.uri().path(), "/api/v2/users");
+//! assert_eq!(result.headers().get("x-proxy").unwrap(), "rewriter");
+//! ```
+
+#[cfg(feature = "napi-support")]
+use napi_derive::napi;
+
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use http::Request;
+use regex::{Regex, RegexBuilder};
+
+use crate::{Condition as _, ConditionExt, RewriteError, Rewriter as _, RewriterExt};
+
+/// Error type for invalid configurations
+///
+/// This error is returned when a configuration can not be turned into a
+/// condition or rewriter, such as when the wrong number of arguments is
+/// given or a pattern is not a valid regular expression.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConfigError(String);
+
+impl ConfigError {
+    /// Create a new config error with the given message
+    pub fn new(message: impl Into<String>) -> Self {
+        Self(message.into())
+    }
+
+    /// Get the error message
+    pub fn message(&self) -> &str {
+        &self.0
+    }
+}
+
+impl std::fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Config error: {}", self.0)
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+//
+// Regex limits
+//
+
+/// Limits applied when compiling the regular expressions in a configuration
+///
+/// Patterns may come from untrusted configuration. The `regex` crate never
+/// backtracks, so matching always runs in linear time, but a large pattern
+/// such as a huge alternation can still use a lot of memory. Patterns which
+/// exceed the limits are rejected with a [`ConfigError`] when the
+/// configuration is converted.
+///
+/// The defaults are lower than those of the `regex` crate, while leaving
+/// plenty of room for typical routing patterns.
+///
+/// # Examples
+///
+/// ```
+/// use http_rewriter::config::{ConditionConfig, ConditionType, Condition, RegexLimits};
+///
+/// let limits = RegexLimits {
+///     size_limit: 64 * 1024,
+///     ..RegexLimits::default()
+/// };
+///
+/// let config = ConditionConfig::new(ConditionType::Path, &[r"^/(\w+/){100}$"])
+///     .with_regex_limits(limits);
+/// assert!(Condition::try_from(config).is_err());
+/// ```
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub struct RegexLimits {
+    /// Approximate maximum size in bytes of a compiled pattern
+    pub size_limit: usize,
+    /// Approximate maximum size in bytes of the lazy DFA cache of a pattern
+    pub dfa_size_limit: usize,
+}
+
+impl Default for RegexLimits {
+    fn default() -> Self {
+        Self {
+            size_limit: 1024 * 1024,
+            dfa_size_limit: 2 * 1024 * 1024,
+        }
+    }
+}
+
+impl RegexLimits {
+    /// Compile a pattern within these limits
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the pattern is not a valid regular expression or
+    /// exceeds the limits
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use http_rewriter::config::RegexLimits;
+    ///
+    /// let regex = RegexLimits::default().compile("^/api/").unwrap();
+    /// assert!(regex.is_match("/api/users"));
+    /// ```
+    pub fn compile(&self, pattern: &str) -> Result<Regex, ConfigError> {
+        RegexBuilder::new(pattern)
+            .size_limit(self.size_limit)
+            .dfa_size_limit(self.dfa_size_limit)
+            .build()
+            .map_err(|e| ConfigError::new(e.to_string()))
+    }
+}
+
+//
+// Conditions
+//
+
+/// Describe if a condition set is combined with AND or OR logic
+#[cfg_attr(feature = "napi-support", napi(string_enum = "lowercase"))]
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Hash)]
+pub enum ConditionOperation {
+    /// All conditions must match for the rewriters to be applied
+    #[default]
+    And,
+    /// At least one condition must match for the rewriters to be applied
+    Or,
+}
+
+/// The types of conditions which may be used in a `ConditionConfig`.
+#[cfg_attr(feature = "napi-support", napi(string_enum = "snake_case"))]
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub enum ConditionType {
+    /// Matches based on the request path
+    Path,
+    /// Matches based on the request header
+    Header,
+    /// Matches based on the request method
+    Method,
+    /// Matches if a file exists at the given path
+    Exists,
+    /// Matches if a file does not exist at the given path
+    NotExists,
+    /// Matches if the given request header is not present
+    HeaderAbsent,
+    /// Matches based on the scheme of the `Authorization` header
+    AuthScheme,
+    /// Matches based on the request query string
+    Query,
+    /// Matches based on a single query parameter
+    QueryParam,
+    /// Matches based on the request host
+    Host,
+    /// Matches based on the languages in the `Accept-Language` header
+    Language,
+    /// Matches based on the number of proxy hops in `X-Forwarded-For`
+    ForwardedForCount,
+    /// Matches the request path against a glob pattern
+    PathGlob,
+    /// Matches if the request URI is absolute
+    AbsoluteUri,
+    /// Matches a parameter of the `Forwarded` header against a regex pattern
+    Forwarded,
+    /// Matches the explicit port from the request URI or `Host` header
+    Port,
+    /// Matches when any header name matches a regex pattern
+    HeaderNameExists,
+    /// Matches when any of the listed headers is missing
+    RequiredHeaders,
+    /// Matches when two headers have the same first value
+    HeaderCompare,
+    /// Matches on whether the path ends with a slash
+    TrailingSlash,
+    /// Matches on the length of the request path and query
+    TargetLength,
+    /// Matches when a query parameter is present and empty or truthy
+    QueryFlag,
+    /// Matches CORS preflight requests
+    CorsPreflight,
+    /// Matches hosts outside an allow-list, or inside a deny-list
+    HostAllowlist,
+    /// Matches the document root from the request extensions against a pattern
+    DocumentRoot,
+    /// Matches when the named header appears more than once
+    DuplicateHeader,
+    /// Matches if the request target contains a plain or percent-encoded `..` segment
+    Traversal,
+    /// Matches if the `Accept-Encoding` header accepts a content coding
+    AcceptEncoding,
+}
+
+/// Configuration for a condition that can be used in a `ConditionalRewriterConfig`.
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+pub struct ConditionConfig {
+    /// The type of condition to apply
+    pub condition: ConditionType,
+    /// The arguments for the condition, such as the path or header name
+    pub args: Option<Vec<String>>,
+    /// Limits for compiling the patterns in the arguments.
+    /// Defaults to [`RegexLimits::default`].
+    pub regex_limits: Option<RegexLimits>,
+    /// Compare the value literally rather than as a pattern.
+    /// Only supported by `Header` conditions.
+    pub exact: Option<bool>,
+}
+
+impl ConditionConfig {
+    /// Create a new condition configuration
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use http_rewriter::config::{ConditionConfig, ConditionType};
+    ///
+    /// let path = ConditionConfig::new(ConditionType::Path, &["^/api/.*"]);
+    /// let exists = ConditionConfig::new(ConditionType::Exists, &[]);
+    /// ```
+    pub fn new(condition: ConditionType, args: &[&str]) -> Self {
+        Self {
+            condition,
+            args: Some(args.iter().map(|arg| arg.to_string()).collect()),
+            regex_limits: None,
+            exact: None,
+        }
+    }
+
+    /// Set the limits for compiling the patterns in the arguments
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use http_rewriter::config::{ConditionConfig, ConditionType, RegexLimits};
+    ///
+    /// let path = ConditionConfig::new(ConditionType::Path, &["^/api/.*"])
+    ///     .with_regex_limits(RegexLimits::default());
+    /// ```
+    pub fn with_regex_limits(mut self, regex_limits: RegexLimits) -> Self {
+        self.regex_limits = Some(regex_limits);
+        self
+    }
+
+    /// Set whether the value is compared literally rather than as a pattern
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use http_rewriter::config::{ConditionConfig, ConditionType};
+    ///
+    /// let json = ConditionConfig::new(ConditionType::Header, &["Accept", "application/json"])
+    ///     .with_exact(true);
+    /// ```
+    pub fn with_exact(mut self, exact: bool) -> Self {
+        self.exact = Some(exact);
+        self
+    }
+}
+
+impl TryFrom<ConditionConfig> for crate::PathCondition {
+    type Error = ConfigError;
+
+    fn try_from(config: ConditionConfig) -> Result<Self, ConfigError> {
+        if config.condition != ConditionType::Path {
+            return Err(ConfigError::new("Expected Path condition type"));
+        }
+        let args = config.args.unwrap_or_default();
+        if args.len() != 1 {
+            return Err(ConfigError::new(
+                "Path condition requires exactly one argument",
+            ));
+        }
+        let pattern = args[0].clone();
+        let pattern = config.regex_limits.unwrap_or_default().compile(&pattern)?;
+        Ok(crate::PathCondition::from_regex(pattern))
+    }
+}
+
+impl TryFrom<ConditionConfig> for crate::HeaderCondition {
+    type Error = ConfigError;
+
+    fn try_from(config: ConditionConfig) -> Result<Self, ConfigError> {
+        if config.condition != ConditionType::Header {
+            return Err(ConfigError::new("Expected Header condition type"));
+        }
+        let args = config.args.unwrap_or_default();
+        if args.len() != 2 {
+            return Err(ConfigError::new(
+                "Header condition requires exactly two arguments",
+            ));
+        }
+        let header = args[0].clone();
+        let value = args[1].clone();
+        if config.exact.unwrap_or_default() {
+            return Ok(crate::HeaderCondition::exact(header, value));
+        }
+        let value = config.regex_limits.unwrap_or_default().compile(&value)?;
+        Ok(crate::HeaderCondition::from_regex(header, value))
+    }
+}
+
+impl TryFrom<ConditionConfig> for crate::MethodCondition {
+    type Error = ConfigError;
+
+    fn try_from(config: ConditionConfig) -> Result<Self, ConfigError> {
+        if config.condition != ConditionType::Method {
+            return Err(ConfigError::new("Expected Method condition type"));
+        }
+        let args = config.args.unwrap_or_default();
+        if args.len() != 1 {
+            return Err(ConfigError::new(
+                "Method condition requires exactly one argument",
+            ));
+        }
+        let method = args[0].clone();
+        let method = config.regex_limits.unwrap_or_default().compile(&method)?;
+        Ok(crate::MethodCondition::from_regex(method))
+    }
+}
+
+impl TryFrom<ConditionConfig> for crate::ExistenceCondition {
+    type Error = ConfigError;
+
+    fn try_from(config: ConditionConfig) -> Result<Self, ConfigError> {
+        if config.condition != ConditionType::Exists {
+            return Err(ConfigError::new("Expected Exists condition type"));
+        }
+        if !config.args.unwrap_or_default().is_empty() {
+            return Err(ConfigError::new("Exists condition requires no arguments"));
+        }
+        Ok(crate::ExistenceCondition::new())
+    }
+}
+
+impl TryFrom<ConditionConfig> for crate::NonExistenceCondition {
+    type Error = ConfigError;
+
+    fn try_from(config: ConditionConfig) -> Result<Self, ConfigError> {
+        if config.condition != ConditionType::NotExists {
+            return Err(ConfigError::new("Expected NotExists condition type"));
+        }
+        if !config.args.unwrap_or_default().is_empty() {
+            return Err(ConfigError::new(
+                "NotExists condition requires no arguments",
+            ));
+        }
+        Ok(crate::NonExistenceCondition::new())
+    }
+}
+
+impl TryFrom<ConditionConfig> for crate::HeaderAbsentCondition {
+    type Error = ConfigError;
+
+    fn try_from(config: ConditionConfig) -> Result<Self, ConfigError> {
+        if config.condition != ConditionType::HeaderAbsent {
+            return Err(ConfigError::new("Expected HeaderAbsent condition type"));
+        }
+        let args = config.args.unwrap_or_default();
+        if args.len() != 1 {
+            return Err(ConfigError::new(
+                "HeaderAbsent condition requires exactly one argument",
+            ));
+        }
+        let header = args[0].clone();
+        Ok(crate::HeaderAbsentCondition::new(header))
+    }
+}
+
+impl TryFrom<ConditionConfig> for crate::AuthSchemeCondition {
+    type Error = ConfigError;
+
+    fn try_from(config: ConditionConfig) -> Result<Self, ConfigError> {
+        if config.condition != ConditionType::AuthScheme {
+            return Err(ConfigError::new("Expected AuthScheme condition type"));
+        }
+        let args = config.args.unwrap_or_default();
+        if args.len() != 1 {
+            return Err(ConfigError::new(
+                "AuthScheme condition requires exactly one argument",
+            ));
+        }
+        let scheme = args[0].clone();
+        Ok(crate::AuthSchemeCondition::new(scheme))
+    }
+}
+
+impl TryFrom<ConditionConfig> for crate::QueryCondition {
+    type Error = ConfigError;
+
+    fn try_from(config: ConditionConfig) -> Result<Self, ConfigError> {
+        if config.condition != ConditionType::Query {
+            return Err(ConfigError::new("Expected Query condition type"));
+        }
+        let args = config.args.unwrap_or_default();
+        if args.len() != 1 {
+            return Err(ConfigError::new(
+                "Query condition requires exactly one argument",
+            ));
+        }
+        let pattern = args[0].clone();
+        let pattern = config.regex_limits.unwrap_or_default().compile(&pattern)?;
+        Ok(crate::QueryCondition::from_regex(pattern))
+    }
+}
+
+impl TryFrom<ConditionConfig> for crate::QueryParamCondition {
+    type Error = ConfigError;
+
+    fn try_from(config: ConditionConfig) -> Result<Self, ConfigError> {
+        if config.condition != ConditionType::QueryParam {
+            return Err(ConfigError::new("Expected QueryParam condition type"));
+        }
+        let args = config.args.unwrap_or_default();
+        match args.as_slice() {
+            [name] => Ok(crate::QueryParamCondition::present(name.clone())),
+            [name, pattern] => {
+                let pattern = config.regex_limits.unwrap_or_default().compile(pattern)?;
+                Ok(crate::QueryParamCondition::from_regex(
+                    name.clone(),
+                    pattern,
+                ))
+            }
+            _ => Err(ConfigError::new(
+                "QueryParam condition requires one or two arguments",
+            )),
+        }
+    }
+}
+
+impl TryFrom<ConditionConfig> for crate::HostCondition {
+    type Error = ConfigError;
+
+    fn try_from(config: ConditionConfig) -> Result<Self, ConfigError> {
+        if config.condition != ConditionType::Host {
+            return Err(ConfigError::new("Expected Host condition type"));
+        }
+        let args = config.args.unwrap_or_default();
+        if args.len() != 1 {
+            return Err(ConfigError::new(
+                "Host condition requires exactly one argument",
+            ));
+        }
+        let pattern = args[0].clone();
+        let pattern = config.regex_limits.unwrap_or_default().compile(&pattern)?;
+        Ok(crate::HostCondition::from_regex(pattern))
+    }
+}
+
+impl TryFrom<ConditionConfig> for crate::LanguageCondition {
+    type Error = ConfigError;
+
+    fn try_from(config: ConditionConfig) -> Result<Self, ConfigError> {
+        if config.condition != ConditionType::Language {
+            return Err(ConfigError::new("Expected Language condition type"));
+        }
+        let args = config.args.unwrap_or_default();
+        if args.is_empty() || args.len() > 2 {
+            return Err(ConfigError::new(
+                "Language condition requires one or two arguments",
+            ));
+        }
+        let any = match args.get(1).map(String::as_str) {
+            None | Some("first") => false,
+            Some("any") => true,
+            Some(_) => {
+                return Err(ConfigError::new(
+                    "Language condition mode must be \"first\" or \"any\"",
+                ));
+            }
+        };
+        let pattern = config.regex_limits.unwrap_or_default().compile(&args[0])?;
+        Ok(crate::LanguageCondition::from_regex(pattern, any))
+    }
+}
+
+impl TryFrom<ConditionConfig> for crate::ForwardedForCountCondition {
+    type Error = ConfigError;
+
+    fn try_from(config: ConditionConfig) -> Result<Self, ConfigError> {
+        if config.condition != ConditionType::ForwardedForCount {
+            return Err(ConfigError::new(
+                "Expected ForwardedForCount condition type",
+            ));
+        }
+        let args = config.args.unwrap_or_default();
+        if args.len() != 2 {
+            return Err(ConfigError::new(
+                "ForwardedForCount condition requires exactly two arguments",
+            ));
+        }
+        let comparison = parse_comparison(&args[0])?;
+        let count = args[1].parse().map_err(|_| {
+            ConfigError::new(format!(
+                "Invalid hop count for ForwardedForCount: {}",
+                args[1]
+            ))
+        })?;
+        Ok(crate::ForwardedForCountCondition::new(comparison, count))
+    }
+}
+
+/// Parse a comparison operator name such as `gt` into a [`crate::Comparison`]
+pub(crate) fn parse_comparison(op: &str) -> Result<crate::Comparison, ConfigError> {
+    use crate::Comparison;
+
+    match op {
+        "eq" => Ok(Comparison::Equal),
+        "ne" => Ok(Comparison::NotEqual),
+        "lt" => Ok(Comparison::Less),
+        "le" => Ok(Comparison::LessOrEqual),
+        "gt" => Ok(Comparison::Greater),
+        "ge" => Ok(Comparison::GreaterOrEqual),
+        _ => Err(ConfigError::new(format!(
+            "Invalid comparison {op:?}, expected one of eq, ne, lt, le, gt or ge"
+        ))),
+    }
+}
+
+impl TryFrom<ConditionConfig> for crate::PathGlobCondition {
+    type Error = ConfigError;
+
+    fn try_from(config: ConditionConfig) -> Result<Self, ConfigError> {
+        if config.condition != ConditionType::PathGlob {
+            return Err(ConfigError::new("Expected PathGlob condition type"));
+        }
+        let args = config.args.unwrap_or_default();
+        if args.len() != 1 {
+            return Err(ConfigError::new(
+                "PathGlob condition requires exactly one argument",
+            ));
+        }
+        let glob = args[0].clone();
+        let pattern = config
+            .regex_limits
+            .unwrap_or_default()
+            .compile(&crate::condition::glob_to_regex(&glob))?;
+        Ok(crate::PathGlobCondition::from_parts(glob, pattern))
+    }
+}
+
+impl TryFrom<ConditionConfig> for crate::AbsoluteUriCondition {
+    type Error = ConfigError;
+
+    fn try_from(config: ConditionConfig) -> Result<Self, ConfigError> {
+        if config.condition != ConditionType::AbsoluteUri {
+            return Err(ConfigError::new("Expected AbsoluteUri condition type"));
+        }
+        if !config.args.unwrap_or_default().is_empty() {
+            return Err(ConfigError::new(
+                "AbsoluteUri condition requires no arguments",
+            ));
+        }
+        Ok(crate::AbsoluteUriCondition::new())
+    }
+}
+
+impl TryFrom<ConditionConfig> for crate::ForwardedCondition {
+    type Error = ConfigError;
+
+    fn try_from(config: ConditionConfig) -> Result<Self, ConfigError> {
+        if config.condition != ConditionType::Forwarded {
+            return Err(ConfigError::new("Expected Forwarded condition type"));
+        }
+        let args = config.args.unwrap_or_default();
+        if args.len() != 2 {
+            return Err(ConfigError::new(
+                "Forwarded condition requires exactly two arguments",
+            ));
+        }
+        let param = parse_forwarded_param(&args[0])?;
+        let pattern = config.regex_limits.unwrap_or_default().compile(&args[1])?;
+        Ok(crate::ForwardedCondition::from_regex(param, pattern))
+    }
+}
+
+/// Parse a `Forwarded` parameter name such as `proto` into a [`crate::ForwardedParam`]
+pub(crate) fn parse_forwarded_param(name: &str) -> Result<crate::ForwardedParam, ConfigError> {
+    use crate::ForwardedParam;
+
+    match name.to_ascii_lowercase().as_str() {
+        "proto" => Ok(ForwardedParam::Proto),
+        "host" => Ok(ForwardedParam::Host),
+        "for" => Ok(ForwardedParam::For),
+        _ => Err(ConfigError::new(format!(
+            "Invalid Forwarded parameter {name:?}, expected one of proto, host or for"
+        ))),
+    }
+}
+
+impl TryFrom<ConditionConfig> for crate::PortCondition {
+    type Error = ConfigError;
+
+    fn try_from(config: ConditionConfig) -> Result<Self, ConfigError> {
+        if config.condition != ConditionType::Port {
+            return Err(ConfigError::new("Expected Port condition type"));
+        }
+        let args = config.args.unwrap_or_default();
+        if args.len() != 1 {
+            return Err(ConfigError::new(
+                "Port condition requires exactly one argument",
+            ));
+        }
+        let port = args[0]
+            .parse()
+            .map_err(|_| ConfigError::new(format!("Invalid port for Port: {}", args[0])))?;
+        Ok(crate::PortCondition::new(port))
+    }
+}
+
+impl TryFrom<ConditionConfig> for crate::HeaderNameExistsCondition {
+    type Error = ConfigError;
+
+    fn try_from(config: ConditionConfig) -> Result<Self, ConfigError> {
+        if config.condition != ConditionType::HeaderNameExists {
+            return Err(ConfigError::new("Expected HeaderNameExists condition type"));
+        }
+        let args = config.args.unwrap_or_default();
+        if args.len() != 1 {
+            return Err(ConfigError::new(
+                "HeaderNameExists condition requires exactly one argument",
+            ));
+        }
+        let pattern = config.regex_limits.unwrap_or_default().compile(&args[0])?;
+        Ok(crate::HeaderNameExistsCondition::from_regex(pattern))
+    }
+}
+
+impl TryFrom<ConditionConfig> for crate::RequiredHeadersCondition {
+    type Error = ConfigError;
+
+    fn try_from(config: ConditionConfig) -> Result<Self, ConfigError> {
+        if config.condition != ConditionType::RequiredHeaders {
+            return Err(ConfigError::new("Expected RequiredHeaders condition type"));
+        }
+        let names = config.args.unwrap_or_default();
+        Ok(crate::RequiredHeadersCondition::new(&names))
+    }
+}
+
+impl TryFrom<ConditionConfig> for crate::HeaderCompareCondition {
+    type Error = ConfigError;
+
+    fn try_from(config: ConditionConfig) -> Result<Self, ConfigError> {
+        if config.condition != ConditionType::HeaderCompare {
+            return Err(ConfigError::new("Expected HeaderCompare condition type"));
+        }
+        let args = config.args.unwrap_or_default();
+        if args.len() < 2 || args.len() > 3 {
+            return Err(ConfigError::new(
+                "HeaderCompare condition requires two or three arguments",
+            ));
+        }
+        let case_insensitive = match args.get(2).map(String::as_str) {
+            None | Some("exact") => false,
+            Some("ignore_case") => true,
+            Some(_) => {
+                return Err(ConfigError::new(
+                    "HeaderCompare condition mode must be \"exact\" or \"ignore_case\"",
+                ));
+            }
+        };
+        Ok(crate::HeaderCompareCondition::new(&args[0], &args[1])
+            .with_case_insensitive(case_insensitive))
+    }
+}
+
+impl TryFrom<ConditionConfig> for crate::TrailingSlashCondition {
+    type Error = ConfigError;
+
+    fn try_from(config: ConditionConfig) -> Result<Self, ConfigError> {
+        if config.condition != ConditionType::TrailingSlash {
+            return Err(ConfigError::new("Expected TrailingSlash condition type"));
+        }
+        let args = config.args.unwrap_or_default();
+        if args.len() != 1 {
+            return Err(ConfigError::new(
+                "TrailingSlash condition requires exactly one argument",
+            ));
+        }
+        let present = match args[0].as_str() {
+            "present" => true,
+            "absent" => false,
+            _ => {
+                return Err(ConfigError::new(
+                    "TrailingSlash condition argument must be \"present\" or \"absent\"",
+                ));
+            }
+        };
+        Ok(crate::TrailingSlashCondition::new(present))
+    }
+}
+
+impl TryFrom<ConditionConfig> for crate::TargetLengthCondition {
+    type Error = ConfigError;
+
+    fn try_from(config: ConditionConfig) -> Result<Self, ConfigError> {
+        if config.condition != ConditionType::TargetLength {
+            return Err(ConfigError::new("Expected TargetLength condition type"));
+        }
+        let args = config.args.unwrap_or_default();
+        if args.len() != 2 {
+            return Err(ConfigError::new(
+                "TargetLength condition requires exactly two arguments",
+            ));
+        }
+        let comparison = parse_comparison(&args[0])?;
+        let length = args[1].parse().map_err(|_| {
+            ConfigError::new(format!("Invalid length for TargetLength: {}", args[1]))
+        })?;
+        Ok(crate::TargetLengthCondition::new(comparison, length))
+    }
+}
+
+impl TryFrom<ConditionConfig> for crate::QueryFlagCondition {
+    type Error = ConfigError;
+
+    fn try_from(config: ConditionConfig) -> Result<Self, ConfigError> {
+        if config.condition != ConditionType::QueryFlag {
+            return Err(ConfigError::new("Expected QueryFlag condition type"));
+        }
+        let args = config.args.unwrap_or_default();
+        if args.len() != 1 {
+            return Err(ConfigError::new(
+                "QueryFlag condition requires exactly one argument",
+            ));
+        }
+        Ok(crate::QueryFlagCondition::new(&args[0]))
+    }
+}
+
+impl TryFrom<ConditionConfig> for crate::CorsPreflightCondition {
+    type Error = ConfigError;
+
+    fn try_from(config: ConditionConfig) -> Result<Self, ConfigError> {
+        if config.condition != ConditionType::CorsPreflight {
+            return Err(ConfigError::new("Expected CorsPreflight condition type"));
+        }
+        if !config.args.unwrap_or_default().is_empty() {
+            return Err(ConfigError::new(
+                "CorsPreflight condition requires no arguments",
+            ));
+        }
+        Ok(crate::CorsPreflightCondition::new())
+    }
+}
+
+impl TryFrom<ConditionConfig> for crate::HostAllowlistCondition {
+    type Error = ConfigError;
+
+    fn try_from(config: ConditionConfig) -> Result<Self, ConfigError> {
+        if config.condition != ConditionType::HostAllowlist {
+            return Err(ConfigError::new("Expected HostAllowlist condition type"));
+        }
+        let args = config.args.unwrap_or_default();
+        let Some((mode, hosts)) = args.split_first().filter(|(_, hosts)| !hosts.is_empty()) else {
+            return Err(ConfigError::new(
+                "HostAllowlist condition requires a mode and at least one host",
+            ));
+        };
+        let mode = parse_host_list_mode(mode)?;
+        crate::HostAllowlistCondition::new(hosts, mode)
+            .map_err(|e| ConfigError::new(format!("Invalid host list: {e}")))
+    }
+}
+
+/// Parse a host list mode, `allow` or `deny`, into a [`crate::HostListMode`]
+pub(crate) fn parse_host_list_mode(mode: &str) -> Result<crate::HostListMode, ConfigError> {
+    match mode.to_ascii_lowercase().as_str() {
+        "allow" => Ok(crate::HostListMode::Allow),
+        "deny" => Ok(crate::HostListMode::Deny),
+        _ => Err(ConfigError::new(format!(
+            "Invalid host list mode {mode:?}, expected allow or deny"
+        ))),
+    }
+}
+
+impl TryFrom<ConditionConfig> for crate::DocumentRootCondition {
+    type Error = ConfigError;
+
+    fn try_from(config: ConditionConfig) -> Result<Self, ConfigError> {
+        if config.condition != ConditionType::DocumentRoot {
+            return Err(ConfigError::new("Expected DocumentRoot condition type"));
+        }
+        let args = config.args.unwrap_or_default();
+        if args.len() != 1 {
+            return Err(ConfigError::new(
+                "DocumentRoot condition requires exactly one argument",
+            ));
+        }
+        let pattern = config.regex_limits.unwrap_or_default().compile(&args[0])?;
+        Ok(crate::DocumentRootCondition::from_regex(pattern))
+    }
+}
+
+impl TryFrom<ConditionConfig> for crate::DuplicateHeaderCondition {
+    type Error = ConfigError;
+
+    fn try_from(config: ConditionConfig) -> Result<Self, ConfigError> {
+        if config.condition != ConditionType::DuplicateHeader {
+            return Err(ConfigError::new("Expected DuplicateHeader condition type"));
+        }
+        let args = config.args.unwrap_or_default();
+        if args.len() != 1 {
+            return Err(ConfigError::new(
+                "DuplicateHeader condition requires exactly one argument",
+            ));
+        }
+        let header = args[0].clone();
+        Ok(crate::DuplicateHeaderCondition::new(header))
+    }
+}
+
+impl TryFrom<ConditionConfig> for crate::TraversalCondition {
+    type Error = ConfigError;
+
+    fn try_from(config: ConditionConfig) -> Result<Self, ConfigError> {
+        if config.condition != ConditionType::Traversal {
+            return Err(ConfigError::new("Expected Traversal condition type"));
+        }
+        if !config.args.unwrap_or_default().is_empty() {
+            return Err(ConfigError::new(
+                "Traversal condition requires no arguments",
+            ));
+        }
+        Ok(crate::TraversalCondition::new())
+    }
+}
+
+impl TryFrom<ConditionConfig> for crate::AcceptEncodingCondition {
+    type Error = ConfigError;
+
+    fn try_from(config: ConditionConfig) -> Result<Self, ConfigError> {
+        if config.condition != ConditionType::AcceptEncoding {
+            return Err(ConfigError::new("Expected AcceptEncoding condition type"));
+        }
+        let args = config.args.unwrap_or_default();
+        if args.len() != 1 {
+            return Err(ConfigError::new(
+                "AcceptEncoding condition requires exactly one argument",
+            ));
+        }
+        let codec = args[0].clone();
+        Ok(crate::AcceptEncodingCondition::new(codec))
+    }
+}
+
+// Since Condition traits have generic methods, we need to create a type-erased
+// wrapper that can hold any condition. Groups nest recursively through
+// `Condition`, so a new condition type only needs a single variant here.
+#[derive(Clone, Debug)]
+enum AnyCondition {
+    Path(crate::PathCondition),
+    Header(crate::HeaderCondition),
+    Method(crate::MethodCondition),
+    Existence(crate::ExistenceCondition),
+    NonExistence(crate::NonExistenceCondition),
+    HeaderAbsent(crate::HeaderAbsentCondition),
+    AuthScheme(crate::AuthSchemeCondition),
+    Query(crate::QueryCondition),
+    QueryParam(crate::QueryParamCondition),
+    Host(crate::HostCondition),
+    Language(crate::LanguageCondition),
+    ForwardedForCount(crate::ForwardedForCountCondition),
+    PathGlob(crate::PathGlobCondition),
+    AbsoluteUri(crate::AbsoluteUriCondition),
+    Forwarded(crate::ForwardedCondition),
+    Port(crate::PortCondition),
+    HeaderNameExists(crate::HeaderNameExistsCondition),
+    Not(crate::NotCondition<Condition>),
+    RequiredHeaders(crate::RequiredHeadersCondition),
+    HeaderCompare(crate::HeaderCompareCondition),
+    TrailingSlash(crate::TrailingSlashCondition),
+    TargetLength(crate::TargetLengthCondition),
+    QueryFlag(crate::QueryFlagCondition),
+    CorsPreflight(crate::CorsPreflightCondition),
+    HostAllowlist(crate::HostAllowlistCondition),
+    DocumentRoot(crate::DocumentRootCondition),
+    DuplicateHeader(crate::DuplicateHeaderCondition),
+    Traversal(crate::TraversalCondition),
+    AcceptEncoding(crate::AcceptEncodingCondition),
+    Group(crate::GroupCondition<Condition, Condition>),
+}
+
+/// A type-erased condition which can hold any of the built-in conditions
+///
+/// This is what a [`ConditionConfig`] is turned into. Any built-in condition
+/// can also be converted into it with `From`.
+///
+/// # Examples
+///
+/// ```
+/// use http_rewriter::{Condition as _, PathCondition};
+/// use http_rewriter::config::{Condition, ConditionConfig, ConditionType};
+/// use http::Request;
+///
+/// let from_config = Condition::try_from(ConditionConfig::new(ConditionType::Path, &["^/api"])).unwrap();
+/// let from_type = Condition::from(PathCondition::new("^/api").unwrap());
+///
+/// let request = Request::builder().uri("/api/users").body(()).unwrap();
+/// assert!(from_config.matches(&request));
+/// assert!(from_type.matches(&request));
+/// ```
+#[derive(Clone, Debug)]
+pub struct Condition(AnyCondition);
+
+impl crate::Condition for Condition {
+    fn matches<B>(&self, request: &Request<B>) -> bool {
+        match &self.0 {
+            AnyCondition::Path(c) => c.matches(request),
+            AnyCondition::Header(c) => c.matches(request),
+            AnyCondition::Method(c) => c.matches(request),
+            AnyCondition::Existence(c) => c.matches(request),
+            AnyCondition::NonExistence(c) => c.matches(request),
+            AnyCondition::HeaderAbsent(c) => c.matches(request),
+            AnyCondition::AuthScheme(c) => c.matches(request),
+            AnyCondition::Query(c) => c.matches(request),
+            AnyCondition::QueryParam(c) => c.matches(request),
+            AnyCondition::Host(c) => c.matches(request),
+            AnyCondition::Language(c) => c.matches(request),
+            AnyCondition::ForwardedForCount(c) => c.matches(request),
+            AnyCondition::PathGlob(c) => c.matches(request),
+            AnyCondition::AbsoluteUri(c) => c.matches(request),
+            AnyCondition::Forwarded(c) => c.matches(request),
+            AnyCondition::Port(c) => c.matches(request),
+            AnyCondition::HeaderNameExists(c) => c.matches(request),
+            AnyCondition::Not(c) => c.matches(request),
+            AnyCondition::RequiredHeaders(c) => c.matches(request),
+            AnyCondition::HeaderCompare(c) => c.matches(request),
+            AnyCondition::TrailingSlash(c) => c.matches(request),
+            AnyCondition::TargetLength(c) => c.matches(request),
+            AnyCondition::QueryFlag(c) => c.matches(request),
+            AnyCondition::CorsPreflight(c) => c.matches(request),
+            AnyCondition::HostAllowlist(c) => c.matches(request),
+            AnyCondition::DocumentRoot(c) => c.matches(request),
+            AnyCondition::DuplicateHeader(c) => c.matches(request),
+            AnyCondition::Traversal(c) => c.matches(request),
+            AnyCondition::AcceptEncoding(c) => c.matches(request),
+            AnyCondition::Group(c) => c.matches(request),
+        }
+    }
+
+    fn patterns(&self) -> Vec<&str> {
+        match &self.0 {
+            AnyCondition::Path(c) => c.patterns(),
+            AnyCondition::Header(c) => c.patterns(),
+            AnyCondition::Method(c) => c.patterns(),
+            AnyCondition::Existence(c) => c.patterns(),
+            AnyCondition::NonExistence(c) => c.patterns(),
+            AnyCondition::HeaderAbsent(c) => c.patterns(),
+            AnyCondition::AuthScheme(c) => c.patterns(),
+            AnyCondition::Query(c) => c.patterns(),
+            AnyCondition::QueryParam(c) => c.patterns(),
+            AnyCondition::Host(c) => c.patterns(),
+            AnyCondition::Language(c) => c.patterns(),
+            AnyCondition::ForwardedForCount(c) => c.patterns(),
+            AnyCondition::PathGlob(c) => c.patterns(),
+            AnyCondition::AbsoluteUri(c) => c.patterns(),
+            AnyCondition::Forwarded(c) => c.patterns(),
+            AnyCondition::Port(c) => c.patterns(),
+            AnyCondition::HeaderNameExists(c) => c.patterns(),
+            AnyCondition::Not(c) => c.patterns(),
+            AnyCondition::RequiredHeaders(c) => c.patterns(),
+            AnyCondition::HeaderCompare(c) => c.patterns(),
+            AnyCondition::TrailingSlash(c) => c.patterns(),
+            AnyCondition::TargetLength(c) => c.patterns(),
+            AnyCondition::QueryFlag(c) => c.patterns(),
+            AnyCondition::CorsPreflight(c) => c.patterns(),
+            AnyCondition::HostAllowlist(c) => c.patterns(),
+            AnyCondition::DocumentRoot(c) => c.patterns(),
+            AnyCondition::DuplicateHeader(c) => c.patterns(),
+            AnyCondition::Traversal(c) => c.patterns(),
+            AnyCondition::AcceptEncoding(c) => c.patterns(),
+            AnyCondition::Group(c) => c.patterns(),
+        }
+    }
+}
+
+macro_rules! impl_from_condition {
+    ($type:ty, $variant:ident) => {
+        impl From<$type> for Condition {
+            fn from(condition: $type) -> Self {
+                Condition(AnyCondition::$variant(condition))
+            }
+        }
+
+        impl TryFrom<Condition> for $type {
+            type Error = ConfigError;
+
+            fn try_from(value: Condition) -> Result<Self, ConfigError> {
+                match value.0 {
+                    AnyCondition::$variant(c) => Ok(c),
+                    _ => Err(ConfigError::new(format!(
+                        "Expected {} condition, found {:?}",
+                        stringify!($variant),
+                        value
+                    ))),
+                }
+            }
+        }
+    };
+}
+
+impl_from_condition!(crate::PathCondition, Path);
+impl_from_condition!(crate::HeaderCondition, Header);
+impl_from_condition!(crate::MethodCondition, Method);
+impl_from_condition!(crate::ExistenceCondition, Existence);
+impl_from_condition!(crate::NonExistenceCondition, NonExistence);
+impl_from_condition!(crate::HeaderAbsentCondition, HeaderAbsent);
+impl_from_condition!(crate::AuthSchemeCondition, AuthScheme);
+impl_from_condition!(crate::QueryCondition, Query);
+impl_from_condition!(crate::QueryParamCondition, QueryParam);
+impl_from_condition!(crate::HostCondition, Host);
+impl_from_condition!(crate::LanguageCondition, Language);
+impl_from_condition!(crate::ForwardedForCountCondition, ForwardedForCount);
+impl_from_condition!(crate::PathGlobCondition, PathGlob);
+impl_from_condition!(crate::AbsoluteUriCondition, AbsoluteUri);
+impl_from_condition!(crate::ForwardedCondition, Forwarded);
+impl_from_condition!(crate::PortCondition, Port);
+impl_from_condition!(crate::HeaderNameExistsCondition, HeaderNameExists);
+impl_from_condition!(crate::NotCondition<Condition>, Not);
+impl_from_condition!(crate::RequiredHeadersCondition, RequiredHeaders);
+impl_from_condition!(crate::HeaderCompareCondition, HeaderCompare);
+impl_from_condition!(crate::TrailingSlashCondition, TrailingSlash);
+impl_from_condition!(crate::TargetLengthCondition, TargetLength);
+impl_from_condition!(crate::QueryFlagCondition, QueryFlag);
+impl_from_condition!(crate::CorsPreflightCondition, CorsPreflight);
+impl_from_condition!(crate::HostAllowlistCondition, HostAllowlist);
+impl_from_condition!(crate::DocumentRootCondition, DocumentRoot);
+impl_from_condition!(crate::DuplicateHeaderCondition, DuplicateHeader);
+impl_from_condition!(crate::TraversalCondition, Traversal);
+impl_from_condition!(crate::AcceptEncodingCondition, AcceptEncoding);
+impl_from_condition!(crate::GroupCondition<Condition, Condition>, Group);
+
+impl TryFrom<ConditionConfig> for Condition {
+    type Error = ConfigError;
+
+    fn try_from(config: ConditionConfig) -> Result<Self, ConfigError> {
+        if config.exact.is_some() && config.condition != ConditionType::Header {
+            return Err(ConfigError::new(format!(
+                "{:?} condition does not support exact",
+                config.condition
+            )));
+        }
+        Ok(Condition(match config.condition {
+            ConditionType::Path => AnyCondition::Path(config.try_into()?),
+            ConditionType::Header => AnyCondition::Header(config.try_into()?),
+            ConditionType::Method => AnyCondition::Method(config.try_into()?),
+            ConditionType::Exists => AnyCondition::Existence(config.try_into()?),
+            ConditionType::NotExists => AnyCondition::NonExistence(config.try_into()?),
+            ConditionType::HeaderAbsent => AnyCondition::HeaderAbsent(config.try_into()?),
+            ConditionType::AuthScheme => AnyCondition::AuthScheme(config.try_into()?),
+            ConditionType::Query => AnyCondition::Query(config.try_into()?),
+            ConditionType::QueryParam => AnyCondition::QueryParam(config.try_into()?),
+            ConditionType::Host => AnyCondition::Host(config.try_into()?),
+            ConditionType::Language => AnyCondition::Language(config.try_into()?),
+            ConditionType::ForwardedForCount => AnyCondition::ForwardedForCount(config.try_into()?),
+            ConditionType::PathGlob => AnyCondition::PathGlob(config.try_into()?),
+            ConditionType::AbsoluteUri => AnyCondition::AbsoluteUri(config.try_into()?),
+            ConditionType::Forwarded => AnyCondition::Forwarded(config.try_into()?),
+            ConditionType::Port => AnyCondition::Port(config.try_into()?),
+            ConditionType::HeaderNameExists => AnyCondition::HeaderNameExists(config.try_into()?),
+            ConditionType::RequiredHeaders => AnyCondition::RequiredHeaders(config.try_into()?),
+            ConditionType::HeaderCompare => AnyCondition::HeaderCompare(config.try_into()?),
+            ConditionType::TrailingSlash => AnyCondition::TrailingSlash(config.try_into()?),
+            ConditionType::TargetLength => AnyCondition::TargetLength(config.try_into()?),
+            ConditionType::QueryFlag => AnyCondition::QueryFlag(config.try_into()?),
+            ConditionType::CorsPreflight => AnyCondition::CorsPreflight(config.try_into()?),
+            ConditionType::HostAllowlist => AnyCondition::HostAllowlist(config.try_into()?),
+            ConditionType::DocumentRoot => AnyCondition::DocumentRoot(config.try_into()?),
+            ConditionType::DuplicateHeader => AnyCondition::DuplicateHeader(config.try_into()?),
+            ConditionType::Traversal => AnyCondition::Traversal(config.try_into()?),
+            ConditionType::AcceptEncoding => AnyCondition::AcceptEncoding(config.try_into()?),
+        }))
+    }
+}
+
+impl TryFrom<(ConditionOperation, Vec<Condition>)> for Condition {
+    type Error = ConfigError;
+
+    fn try_from(
+        (operation, conditions): (ConditionOperation, Vec<Condition>),
+    ) -> Result<Self, ConfigError> {
+        if conditions.is_empty() {
+            return Err(ConfigError::new("At least one condition is required"));
+        }
+
+        Ok(conditions
+            .into_iter()
+            .reduce(|a, b| match operation {
+                ConditionOperation::And => a.and(b).into(),
+                ConditionOperation::Or => a.or(b).into(),
+            })
+            .unwrap())
+    }
+}
+
+//
+// Rewriters
+//
+
+/// The types of rewriters which may be used in a `RewriterConfig`.
+#[cfg_attr(feature = "napi-support", napi(string_enum = "snake_case"))]
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub enum RewriterType {
+    /// Rewrites the request path
+    Path,
+    /// Rewrites a request header
+    Header,
+    /// Rewrites the request method
+    Method,
+    /// Rewrites the request href
+    Href,
+    /// Removes the request query string
+    StripQuery,
+    /// Keeps only the query parameters named in the arguments
+    AllowQueryParams,
+    /// Rewrites the request path and query in one step
+    PathAndQuery,
+    /// Sets a header to a default value when the request lacks it
+    DefaultHeader,
+    /// Sets the HTTP version of the request
+    Version,
+    /// Sets the port of the request URI authority
+    Port,
+    /// Sorts the query parameters by name
+    SortQuery,
+    /// Removes hop-by-hop headers, including those named in `Connection`
+    StripHopByHop,
+    /// Removes query parameters whose names match a pattern
+    RemoveQueryParams,
+    /// Removes or replaces the userinfo of the request URI authority
+    UserInfo,
+    /// Takes the request method from the header named in the argument
+    MethodOverride,
+    /// Uppercases standard methods sent in the wrong case
+    NormalizeMethod,
+    /// Sets several headers, with arguments alternating names and values
+    SetHeaders,
+}
+
+/// Configuration for a rewriter that can be used in a `ConditionalRewriterConfig`.
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+pub struct RewriterConfig {
+    /// The type of rewriter to apply
+    pub rewriter_type: RewriterType,
+    /// The arguments for the rewriter, such as the pattern and replacement
+    pub args: Option<Vec<String>>,
+    /// Replace every match of the pattern rather than only the first.
+    /// Only supported by the path, header, href and path and query rewriters.
+    pub replace_all: Option<bool>,
+    /// Limits for compiling the patterns in the arguments.
+    /// Defaults to [`RegexLimits::default`].
+    pub regex_limits: Option<RegexLimits>,
+}
+
+impl RewriterConfig {
+    /// Create a new rewriter configuration
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use http_rewriter::config::{RewriterConfig, RewriterType};
+    ///
+    /// let path = RewriterConfig::new(RewriterType::Path, &["^/old/", "/new/"]);
+    /// let method = RewriterConfig::new(RewriterType::Method, &["POST"]);
+    /// ```
+    pub fn new(rewriter_type: RewriterType, args: &[&str]) -> Self {
+        Self {
+            rewriter_type,
+            args: Some(args.iter().map(|arg| arg.to_string()).collect()),
+            replace_all: None,
+            regex_limits: None,
+        }
+    }
+
+    /// Set whether every match of the pattern is replaced, rather than only the first
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use http_rewriter::config::{RewriterConfig, RewriterType};
+    ///
+    /// let path = RewriterConfig::new(RewriterType::Path, &["/x/", "/y/"]).with_replace_all(true);
+    /// ```
+    pub fn with_replace_all(mut self, replace_all: bool) -> Self {
+        self.replace_all = Some(replace_all);
+        self
+    }
+
+    /// Set the limits for compiling the patterns in the arguments
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use http_rewriter::config::{RegexLimits, RewriterConfig, RewriterType};
+    ///
+    /// let path = RewriterConfig::new(RewriterType::Path, &["^/old/", "/new/"])
+    ///     .with_regex_limits(RegexLimits::default());
+    /// ```
+    pub fn with_regex_limits(mut self, regex_limits: RegexLimits) -> Self {
+        self.regex_limits = Some(regex_limits);
+        self
+    }
+}
+
+//
+// Convert `RewriterConfig` into specific rewriter types.
+//
+
+impl TryFrom<RewriterConfig> for crate::PathRewriter {
+    type Error = ConfigError;
+
+    fn try_from(config: RewriterConfig) -> Result<Self, ConfigError> {
+        if config.rewriter_type != RewriterType::Path {
+            return Err(ConfigError::new("Expected Path rewriter type"));
+        }
+        let args = config.args.unwrap_or_default();
+        if args.len() != 2 {
+            return Err(ConfigError::new(
+                "Path rewriter requires exactly two arguments",
+            ));
+        }
+        let pattern = args[0].clone();
+        let replacement = args[1].clone();
+        let pattern = config.regex_limits.unwrap_or_default().compile(&pattern)?;
+        check_replacement(&pattern, &replacement)?;
+        let rewriter = crate::PathRewriter::from_regex(pattern, replacement);
+        Ok(rewriter.with_replace_all(config.replace_all.unwrap_or_default()))
+    }
+}
+
+/// Check that every capture group a replacement refers to exists in the pattern
+///
+/// References follow the syntax of [`Regex::replace`]: `$1` and `${1}` refer
+/// to groups by index, `$name` and `${name}` by name, and `$$` is a literal
+/// dollar sign. The regex crate quietly replaces a reference to a missing
+/// group with nothing, which hides typos in configurations.
+fn check_replacement(pattern: &Regex, replacement: &str) -> Result<(), ConfigError> {
+    let mut rest = replacement;
+    while let Some(position) = rest.find('$') {
+        rest = &rest[position + 1..];
+        let name = if let Some(after) = rest.strip_prefix('$') {
+            rest = after;
+            continue;
+        } else if let Some(braced) = rest.strip_prefix('{')
+            && let Some(end) = braced.find('}')
+        {
+            rest = &braced[end + 1..];
+            &braced[..end]
+        } else {
+            let end = rest
+                .find(|c: char| !(c == '_' || c.is_ascii_alphanumeric()))
+                .unwrap_or(rest.len());
+            let name = &rest[..end];
+            rest = &rest[end..];
+            name
+        };
+        let exists = match name.parse::<usize>() {
+            Ok(index) => index < pattern.captures_len(),
+            Err(_) => name.is_empty() || pattern.capture_names().any(|n| n == Some(name)),
+        };
+        if !exists {
+            return Err(ConfigError::new(format!(
+                "Replacement refers to unknown capture group `{name}`"
+            )));
+        }
+    }
+    Ok(())
+}
+
+impl TryFrom<RewriterConfig> for crate::HeaderRewriter {
+    type Error = ConfigError;
+
+    fn try_from(config: RewriterConfig) -> Result<Self, ConfigError> {
+        if config.rewriter_type != RewriterType::Header {
+            return Err(ConfigError::new("Expected Header rewriter type"));
+        }
+        let args = config.args.unwrap_or_default();
+        if args.len() != 3 {
+            return Err(ConfigError::new(
+                "Header rewriter requires exactly three arguments",
+            ));
+        }
+        let header = args[0].clone();
+        let pattern = args[1].clone();
+        let replacement = args[2].clone();
+        let pattern = config.regex_limits.unwrap_or_default().compile(&pattern)?;
+        check_replacement(&pattern, &replacement)?;
+        let rewriter = crate::HeaderRewriter::from_regex(header, pattern, replacement);
+        Ok(rewriter.with_replace_all(config.replace_all.unwrap_or_default()))
+    }
+}
+
+impl TryFrom<RewriterConfig> for crate::MethodRewriter {
+    type Error = ConfigError;
+
+    fn try_from(config: RewriterConfig) -> Result<Self, ConfigError> {
+        if config.rewriter_type != RewriterType::Method {
+            return Err(ConfigError::new("Expected Method rewriter type"));
+        }
+        if config.replace_all.is_some() {
+            return Err(ConfigError::new(
+                "Method rewriter does not support replace_all",
+            ));
+        }
+        let args = config.args.unwrap_or_default();
+        if args.len() != 1 {
+            return Err(ConfigError::new(
+                "Method rewriter requires exactly one argument",
+            ));
+        }
+        let method = args[0].clone();
+        crate::MethodRewriter::new(method.as_str()).map_err(|e| ConfigError::new(e.to_string()))
+    }
+}
+
+impl TryFrom<RewriterConfig> for crate::HrefRewriter {
+    type Error = ConfigError;
+
+    fn try_from(config: RewriterConfig) -> Result<Self, ConfigError> {
+        if config.rewriter_type != RewriterType::Href {
+            return Err(ConfigError::new("Expected Href rewriter type"));
+        }
+        let args = config.args.unwrap_or_default();
+        if args.len() != 2 {
+            return Err(ConfigError::new(
+                "Href rewriter requires exactly two arguments",
+            ));
+        }
+        let pattern = args[0].clone();
+        let replacement = args[1].clone();
+        let pattern = config.regex_limits.unwrap_or_default().compile(&pattern)?;
+        check_replacement(&pattern, &replacement)?;
+        let rewriter = crate::HrefRewriter::from_regex(pattern, replacement);
+        Ok(rewriter.with_replace_all(config.replace_all.unwrap_or_default()))
+    }
+}
+
+impl TryFrom<RewriterConfig> for crate::StripQueryRewriter {
+    type Error = ConfigError;
+
+    fn try_from(config: RewriterConfig) -> Result<Self, ConfigError> {
+        if config.rewriter_type != RewriterType::StripQuery {
+            return Err(ConfigError::new("Expected StripQuery rewriter type"));
+        }
+        if config.replace_all.is_some() {
+            return Err(ConfigError::new(
+                "StripQuery rewriter does not support replace_all",
+            ));
+        }
+        if !config.args.unwrap_or_default().is_empty() {
+            return Err(ConfigError::new(
+                "StripQuery rewriter requires no arguments",
+            ));
+        }
+        Ok(crate::StripQueryRewriter::new())
+    }
+}
+
+impl TryFrom<RewriterConfig> for crate::AllowQueryParamsRewriter {
+    type Error = ConfigError;
+
+    fn try_from(config: RewriterConfig) -> Result<Self, ConfigError> {
+        if config.rewriter_type != RewriterType::AllowQueryParams {
+            return Err(ConfigError::new("Expected AllowQueryParams rewriter type"));
+        }
+        if config.replace_all.is_some() {
+            return Err(ConfigError::new(
+                "AllowQueryParams rewriter does not support replace_all",
+            ));
+        }
+        let names = config.args.unwrap_or_default();
+        Ok(crate::AllowQueryParamsRewriter::new(&names))
+    }
+}
+
+impl TryFrom<RewriterConfig> for crate::PathAndQueryRewriter {
+    type Error = ConfigError;
+
+    fn try_from(config: RewriterConfig) -> Result<Self, ConfigError> {
+        if config.rewriter_type != RewriterType::PathAndQuery {
+            return Err(ConfigError::new("Expected PathAndQuery rewriter type"));
+        }
+        let args = config.args.unwrap_or_default();
+        if args.len() != 2 {
+            return Err(ConfigError::new(
+                "PathAndQuery rewriter requires exactly two arguments",
+            ));
+        }
+        let pattern = config.regex_limits.unwrap_or_default().compile(&args[0])?;
+        let replacement = args[1].clone();
+        check_replacement(&pattern, &replacement)?;
+        let rewriter = crate::PathAndQueryRewriter::from_regex(pattern, replacement);
+        Ok(rewriter.with_replace_all(config.replace_all.unwrap_or_default()))
+    }
+}
+
+impl TryFrom<RewriterConfig> for crate::DefaultHeaderRewriter {
+    type Error = ConfigError;
+
+    fn try_from(config: RewriterConfig) -> Result<Self, ConfigError> {
+        if config.rewriter_type != RewriterType::DefaultHeader {
+            return Err(ConfigError::new("Expected DefaultHeader rewriter type"));
+        }
+        if config.replace_all.is_some() {
+            return Err(ConfigError::new(
+                "DefaultHeader rewriter does not support replace_all",
+            ));
+        }
+        let args = config.args.unwrap_or_default();
+        if args.len() != 2 {
+            return Err(ConfigError::new(
+                "DefaultHeader rewriter requires exactly two arguments",
+            ));
+        }
+        crate::DefaultHeaderRewriter::new(&args[0], &args[1])
+            .map_err(|e| ConfigError::new(e.to_string()))
+    }
+}
+
+impl TryFrom<RewriterConfig> for crate::VersionRewriter {
+    type Error = ConfigError;
+
+    fn try_from(config: RewriterConfig) -> Result<Self, ConfigError> {
+        if config.rewriter_type != RewriterType::Version {
+            return Err(ConfigError::new("Expected Version rewriter type"));
+        }
+        if config.replace_all.is_some() {
+            return Err(ConfigError::new(
+                "Version rewriter does not support replace_all",
+            ));
+        }
+        let args = config.args.unwrap_or_default();
+        if args.len() != 1 {
+            return Err(ConfigError::new(
+                "Version rewriter requires exactly one argument",
+            ));
+        }
+        crate::VersionRewriter::new(&args[0]).map_err(|e| ConfigError::new(e.to_string()))
+    }
+}
+
+impl TryFrom<RewriterConfig> for crate::PortRewriter {
+    type Error = ConfigError;
+
+    fn try_from(config: RewriterConfig) -> Result<Self, ConfigError> {
+        if config.rewriter_type != RewriterType::Port {
+            return Err(ConfigError::new("Expected Port rewriter type"));
+        }
+        if config.replace_all.is_some() {
+            return Err(ConfigError::new(
+                "Port rewriter does not support replace_all",
+            ));
+        }
+        let args = config.args.unwrap_or_default();
+        if args.len() != 1 {
+            return Err(ConfigError::new(
+                "Port rewriter requires exactly one argument",
+            ));
+        }
+        let port = args[0]
+            .parse()
+            .map_err(|_| ConfigError::new(format!("Invalid port for Port: {}", args[0])))?;
+        Ok(crate::PortRewriter::new(port))
+    }
+}
+
+impl TryFrom<RewriterConfig> for crate::SortQueryRewriter {
+    type Error = ConfigError;
+
+    fn try_from(config: RewriterConfig) -> Result<Self, ConfigError> {
+        if config.rewriter_type != RewriterType::SortQuery {
+            return Err(ConfigError::new("Expected SortQuery rewriter type"));
+        }
+        if config.replace_all.is_some() {
+            return Err(ConfigError::new(
+                "SortQuery rewriter does not support replace_all",
+            ));
+        }
+        if !config.args.unwrap_or_default().is_empty() {
+            return Err(ConfigError::new("SortQuery rewriter requires no arguments"));
+        }
+        Ok(crate::SortQueryRewriter::new())
+    }
+}
+
+impl TryFrom<RewriterConfig> for crate::StripHopByHopRewriter {
+    type Error = ConfigError;
+
+    fn try_from(config: RewriterConfig) -> Result<Self, ConfigError> {
+        if config.rewriter_type != RewriterType::StripHopByHop {
+            return Err(ConfigError::new("Expected StripHopByHop rewriter type"));
+        }
+        if config.replace_all.is_some() {
+            return Err(ConfigError::new(
+                "StripHopByHop rewriter does not support replace_all",
+            ));
+        }
+        if !config.args.unwrap_or_default().is_empty() {
+            return Err(ConfigError::new(
+                "StripHopByHop rewriter requires no arguments",
+            ));
+        }
+        Ok(crate::StripHopByHopRewriter::new())
+    }
+}
+
+impl TryFrom<RewriterConfig> for crate::RemoveQueryParamsRewriter {
+    type Error = ConfigError;
+
+    fn try_from(config: RewriterConfig) -> Result<Self, ConfigError> {
+        if config.rewriter_type != RewriterType::RemoveQueryParams {
+            return Err(ConfigError::new("Expected RemoveQueryParams rewriter type"));
+        }
+        if config.replace_all.is_some() {
+            return Err(ConfigError::new(
+                "RemoveQueryParams rewriter does not support replace_all",
+            ));
+        }
+        let args = config.args.unwrap_or_default();
+        if args.len() != 1 {
+            return Err(ConfigError::new(
+                "RemoveQueryParams rewriter requires exactly one argument",
+            ));
+        }
+        let pattern = config.regex_limits.unwrap_or_default().compile(&args[0])?;
+        Ok(crate::RemoveQueryParamsRewriter::from_regex(pattern))
+    }
+}
+
+impl TryFrom<RewriterConfig> for crate::UserInfoRewriter {
+    type Error = ConfigError;
+
+    fn try_from(config: RewriterConfig) -> Result<Self, ConfigError> {
+        if config.rewriter_type != RewriterType::UserInfo {
+            return Err(ConfigError::new("Expected UserInfo rewriter type"));
+        }
+        if config.replace_all.is_some() {
+            return Err(ConfigError::new(
+                "UserInfo rewriter does not support replace_all",
+            ));
+        }
+        let args = config.args.unwrap_or_default();
+        match args.iter().map(String::as_str).collect::<Vec<_>>()[..] {
+            ["strip"] => Ok(crate::UserInfoRewriter::strip()),
+            ["set", userinfo] => {
+                crate::UserInfoRewriter::set(userinfo).map_err(|e| ConfigError::new(e.to_string()))
+            }
+            _ => Err(ConfigError::new(
+                "UserInfo rewriter requires \"strip\", or \"set\" and a userinfo",
+            )),
+        }
+    }
+}
+
+impl TryFrom<RewriterConfig> for crate::MethodOverrideRewriter {
+    type Error = ConfigError;
+
+    fn try_from(config: RewriterConfig) -> Result<Self, ConfigError> {
+        if config.rewriter_type != RewriterType::MethodOverride {
+            return Err(ConfigError::new("Expected MethodOverride rewriter type"));
+        }
+        if config.replace_all.is_some() {
+            return Err(ConfigError::new(
+                "MethodOverride rewriter does not support replace_all",
+            ));
+        }
+        let args = config.args.unwrap_or_default();
+        if args.len() != 1 {
+            return Err(ConfigError::new(
+                "MethodOverride rewriter requires exactly one argument",
+            ));
+        }
+        crate::MethodOverrideRewriter::new(&args[0]).map_err(|e| ConfigError::new(e.to_string()))
+    }
+}
+
+impl TryFrom<RewriterConfig> for crate::NormalizeMethodRewriter {
+    type Error = ConfigError;
+
+    fn try_from(config: RewriterConfig) -> Result<Self, ConfigError> {
+        if config.rewriter_type != RewriterType::NormalizeMethod {
+            return Err(ConfigError::new("Expected NormalizeMethod rewriter type"));
+        }
+        if config.replace_all.is_some() {
+            return Err(ConfigError::new(
+                "NormalizeMethod rewriter does not support replace_all",
+            ));
+        }
+        if !config.args.unwrap_or_default().is_empty() {
+            return Err(ConfigError::new(
+                "NormalizeMethod rewriter requires no arguments",
+            ));
+        }
+        Ok(crate::NormalizeMethodRewriter::new())
+    }
+}
+
+impl TryFrom<RewriterConfig> for crate::CompositeRewriter<crate::HeaderSetRewriter> {
+    type Error = ConfigError;
+
+    fn try_from(config: RewriterConfig) -> Result<Self, ConfigError> {
+        if config.rewriter_type != RewriterType::SetHeaders {
+            return Err(ConfigError::new("Expected SetHeaders rewriter type"));
+        }
+        if config.replace_all.is_some() {
+            return Err(ConfigError::new(
+                "SetHeaders rewriter does not support replace_all",
+            ));
+        }
+        let args = config.args.unwrap_or_default();
+        if args.is_empty() || !args.len().is_multiple_of(2) {
+            return Err(ConfigError::new(
+                "SetHeaders rewriter requires header name and value pairs",
+            ));
+        }
+        let rewriters = args
+            .as_chunks::<2>()
+            .0
+            .iter()
+            .map(|[name, value]| crate::HeaderSetRewriter::new(name, value))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| ConfigError::new(e.to_string()))?;
+        Ok(crate::CompositeRewriter::new(rewriters))
+    }
+}
+
+/// Configuration for a conditional rewriter that can be used in a `Rewriter`.
+#[derive(Clone, Debug, Default, Eq, PartialEq, Hash)]
+pub struct ConditionalRewriterConfig {
+    /// Optional rule name, included in the message of errors raised by this
+    /// rule. Rules in a list without a name are identified by their position,
+    /// starting from `#1`.
+    pub name: Option<String>,
+    /// The logical operation to use when applying the condition set
+    pub operation: Option<ConditionOperation>,
+    /// The conditions that must be met for the rewriters to be applied
+    pub conditions: Option<Vec<ConditionConfig>>,
+    /// The rewriters to apply if the conditions are met
+    pub rewriters: Vec<RewriterConfig>,
+    /// Metadata inserted into the request extensions as a [`RuleMetadata`]
+    /// when the rule matches and its rewriters succeed
+    pub metadata: Option<BTreeMap<String, String>>,
+}
+
+impl ConditionalRewriterConfig {
+    /// Create a new conditional rewriter configuration
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use http_rewriter::config::{
+    ///     ConditionConfig, ConditionOperation, ConditionType, ConditionalRewriterConfig,
+    ///     RewriterConfig, RewriterType,
+    /// };
+    ///
+    /// let rule = ConditionalRewriterConfig::new(
+    ///     ConditionOperation::Or,
+    ///     vec![
+    ///         ConditionConfig::new(ConditionType::Method, &["PUT"]),
+    ///         ConditionConfig::new(ConditionType::Method, &["PATCH"]),
+    ///     ],
+    ///     vec![RewriterConfig::new(RewriterType::Method, &["POST"])],
+    /// );
+    /// ```
+    pub fn new(
+        operation: ConditionOperation,
+        conditions: Vec<ConditionConfig>,
+        rewriters: Vec<RewriterConfig>,
+    ) -> Self {
+        Self {
+            name: None,
+            operation: Some(operation),
+            conditions: Some(conditions),
+            rewriters,
+            metadata: None,
+        }
+    }
+
+    /// Set the name used to identify this rule in error messages
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use http_rewriter::config::{ConditionalRewriterConfig, RewriterConfig, RewriterType};
+    ///
+    /// let rule = ConditionalRewriterConfig::default()
+    ///     .with_name("api-v2")
+    ///     .with_rewriter(RewriterConfig::new(RewriterType::Path, &["^/api/", "/v2/"]));
+    ///
+    /// assert_eq!(rule.name.as_deref(), Some("api-v2"));
+    /// ```
+    pub fn with_name(mut self, name: impl Into<String>) -> Self {
+        self.name = Some(name.into());
+        self
+    }
+
+    /// Add a condition to this configuration
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use http_rewriter::config::{ConditionConfig, ConditionType, ConditionalRewriterConfig};
+    ///
+    /// let rule = ConditionalRewriterConfig::default()
+    ///     .with_condition(ConditionConfig::new(ConditionType::Path, &["^/api/.*"]))
+    ///     .with_condition(ConditionConfig::new(ConditionType::Method, &["GET"]));
+    ///
+    /// assert_eq!(rule.conditions.unwrap().len(), 2);
+    /// ```
+    pub fn with_condition(mut self, condition: ConditionConfig) -> Self {
+        self.conditions.get_or_insert_with(Vec::new).push(condition);
+        self
+    }
+
+    /// Add a rewriter to this configuration
+    ///
+    /// Rewriters are applied in the order they are added.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use http_rewriter::config::{ConditionalRewriterConfig, RewriterConfig, RewriterType};
+    ///
+    /// let rule = ConditionalRewriterConfig::default()
+    ///     .with_rewriter(RewriterConfig::new(RewriterType::Path, &["^/old/", "/new/"]))
+    ///     .with_rewriter(RewriterConfig::new(RewriterType::Method, &["POST"]));
+    ///
+    /// assert_eq!(rule.rewriters.len(), 2);
+    /// ```
+    pub fn with_rewriter(mut self, rewriter: RewriterConfig) -> Self {
+        self.rewriters.push(rewriter);
+        self
+    }
+
+    /// Add a metadata entry, recorded on the requests this rule rewrites
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use http_rewriter::config::{ConditionalRewriterConfig, RewriterConfig, RewriterType};
+    ///
+    /// let rule = ConditionalRewriterConfig::default()
+    ///     .with_rewriter(RewriterConfig::new(RewriterType::Path, &["^/billing/", "/v2/billing/"]))
+    ///     .with_metadata("owner", "team-billing");
+    ///
+    /// assert_eq!(rule.metadata.unwrap()["owner"], "team-billing");
+    /// ```
+    pub fn with_metadata(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.metadata
+            .get_or_insert_with(BTreeMap::new)
+            .insert(key.into(), value.into());
+        self
+    }
+}
+
+/// Request extension holding the metadata of the rule which rewrote the request
+///
+/// A rule built from a [`ConditionalRewriterConfig`] with metadata inserts
+/// this after its rewriters succeed, and only when its conditions match, so
+/// handlers further down can attribute traffic to the rule. When several such
+/// rules apply, the last one wins. Rules without metadata leave it as it is.
+///
+/// # Examples
+///
+/// ```
+/// use http_rewriter::Rewriter as _;
+/// use http_rewriter::config::{
+///     ConditionConfig, ConditionType, ConditionalRewriterConfig, Rewriter, RewriterConfig,
+///     RewriterType, RuleMetadata,
+/// };
+/// use http::Request;
+///
+/// let rewriter = Rewriter::try_from(vec![
+///     ConditionalRewriterConfig::default()
+///         .with_condition(ConditionConfig::new(ConditionType::Path, &["^/billing/"]))
+///         .with_rewriter(RewriterConfig::new(RewriterType::Path, &["^/billing/", "/v2/billing/"]))
+///         .with_metadata("owner", "team-billing"),
+/// ])
+/// .unwrap();
+///
+/// let request = Request::builder().uri("/billing/invoices").body(()).unwrap();
+/// let result = rewriter.rewrite(request).unwrap();
+/// assert_eq!(result.extensions().get::<RuleMetadata>().unwrap().0["owner"], "team-billing");
+/// ```
+#[derive(Clone, Debug, Default, Eq, PartialEq, Hash)]
+pub struct RuleMetadata(pub BTreeMap<String, String>);
+
+// Since Rewriter traits have generic methods, we need to create a type-erased
+// wrapper that can hold any rewriter. Sequences and conditional rewriters nest
+// recursively through `Rewriter` and `Condition`.
+#[derive(Clone, Debug)]
+enum AnyRewriter {
+    Path(crate::PathRewriter),
+    Header(crate::HeaderRewriter),
+    Method(crate::MethodRewriter),
+    Href(crate::HrefRewriter),
+    StripQuery(crate::StripQueryRewriter),
+    AllowQueryParams(crate::AllowQueryParamsRewriter),
+    PathAndQuery(crate::PathAndQueryRewriter),
+    DefaultHeader(crate::DefaultHeaderRewriter),
+    Named(crate::NamedRewriter<Rewriter>),
+    Version(crate::VersionRewriter),
+    Port(crate::PortRewriter),
+    SortQuery(crate::SortQueryRewriter),
+    StripHopByHop(crate::StripHopByHopRewriter),
+    RemoveQueryParams(crate::RemoveQueryParamsRewriter),
+    UserInfo(crate::UserInfoRewriter),
+    MethodOverride(crate::MethodOverrideRewriter),
+    NormalizeMethod(crate::NormalizeMethodRewriter),
+    SetHeaders(crate::CompositeRewriter<crate::HeaderSetRewriter>),
+    Sequence(crate::SequenceRewriter<Rewriter, Rewriter>),
+    Composite(crate::CompositeRewriter<Rewriter>),
+    Metadata(crate::MarkRewriter<RuleMetadata>),
+    RouteTable(crate::RouteTableRewriter<Rewriter>),
+    Conditional(crate::ConditionalRewriter<Rewriter, Condition>),
+}
+
+/// A type-erased rewriter which can hold any of the built-in rewriters
+///
+/// This is what a list of [`ConditionalRewriterConfig`]s is turned into.
+/// Any built-in rewriter can also be converted into it with `From`.
+///
+/// The compiled pipeline is held behind an [`Arc`], so cloning a rewriter
+/// only bumps a reference count, however many rules it holds. Rewriting takes
+/// `&self`, so one pipeline can be compiled once and its clones handed to as
+/// many threads as needed.
+///
+/// # Examples
+///
+/// ```
+/// use http_rewriter::Rewriter as _;
+/// use http_rewriter::config::{ConditionalRewriterConfig, Rewriter, RewriterConfig, RewriterType};
+/// use http::Request;
+///
+/// let rewriter = Rewriter::try_from(vec![
+///     ConditionalRewriterConfig::default()
+///         .with_rewriter(RewriterConfig::new(RewriterType::Path, &["^/old/", "/new/"])),
+/// ])
+/// .unwrap();
+///
+/// let request = Request::builder().uri("/old/page").body(()).unwrap();
+/// let result = rewriter.rewrite(request).unwrap();
+/// assert_eq!(result.uri().path(), "/new/page");
+/// ```
+#[derive(Clone, Debug)]
+pub struct Rewriter(Arc<AnyRewriter>, Option<Arc<ConditionalRewriterConfig>>);
+
+impl Rewriter {
+    /// Combine two rewriters into one which applies this one and then the other
+    ///
+    /// This is useful for merging rule sets loaded from separate
+    /// configurations, such as a base rule set and a per-tenant one. Every
+    /// rule of this rewriter runs before any rule of the other.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use http_rewriter::Rewriter as _;
+    /// use http_rewriter::config::{ConditionalRewriterConfig, Rewriter, RewriterConfig, RewriterType};
+    /// use http::Request;
+    ///
+    /// let base = Rewriter::try_from(vec![
+    ///     ConditionalRewriterConfig::default()
+    ///         .with_rewriter(RewriterConfig::new(RewriterType::Path, &["^/old/", "/new/"])),
+    /// ])
+    /// .unwrap();
+    /// let tenant = Rewriter::try_from(vec![
+    ///     ConditionalRewriterConfig::default()
+    ///         .with_rewriter(RewriterConfig::new(RewriterType::Path, &["^/new/", "/tenant/new/"])),
+    /// ])
+    /// .unwrap();
+    ///
+    /// let rewriter = base.concat(tenant);
+    ///
+    /// let request = Request::builder().uri("/old/page").body(()).unwrap();
+    /// let result = rewriter.rewrite(request).unwrap();
+    /// assert_eq!(result.uri().path(), "/tenant/new/page");
+    /// ```
+    pub fn concat(self, other: Rewriter) -> Rewriter {
+        self.then(other).into()
+    }
+
+    /// Run a request through the pipeline and report what each rule did
+    ///
+    /// Every rule is listed with its name, whether its conditions matched
+    /// and the changes it made to the method, URI, version and headers.
+    /// Changes to extensions are not reported. Rules from a list of
+    /// [`ConditionalRewriterConfig`]s are named after their position, such
+    /// as `#1`, unless they were given a name. If a rule fails, it is the
+    /// last one listed and the error is reported instead of running the
+    /// rules after it.
+    ///
+    /// The request is rewritten exactly as [`rewrite`](crate::Rewriter::rewrite)
+    /// would, so the report is only useful for inspecting a configuration,
+    /// such as in a tool checking sample requests against it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use http_rewriter::Change;
+    /// use http_rewriter::config::{
+    ///     ConditionConfig, ConditionType, ConditionalRewriterConfig, Rewriter, RewriterConfig,
+    ///     RewriterType,
+    /// };
+    /// use http::{Method, Request};
+    ///
+    /// let rewriter = Rewriter::try_from(vec![
+    ///     ConditionalRewriterConfig::default()
+    ///         .with_name("legacy")
+    ///         .with_rewriter(RewriterConfig::new(RewriterType::Path, &["^/old/", "/new/"])),
+    ///     ConditionalRewriterConfig::default()
+    ///         .with_condition(ConditionConfig::new(ConditionType::Method, &["POST"]))
+    ///         .with_rewriter(RewriterConfig::new(RewriterType::Method, &["PUT"])),
+    /// ])
+    /// .unwrap();
+    ///
+    /// let request = Request::builder().uri("/old/page").body(()).unwrap();
+    /// let explanation = rewriter.explain(request);
+    ///
+    /// assert_eq!(explanation.rules[0].rule, "legacy");
+    /// assert!(matches!(explanation.rules[0].changes[..], [Change::Path { .. }]));
+    /// assert_eq!(explanation.rules[1].rule, "#2");
+    /// assert!(!explanation.rules[1].matched);
+    /// ```
+    pub fn explain<B>(&self, request: Request<B>) -> crate::RewriteExplanation {
+        let mut explanation = crate::RewriteExplanation::default();
+        if let Err(error) = self.explain_rules(request, &mut explanation.rules) {
+            explanation.error = Some(error);
+        }
+        explanation
+    }
+
+    // Walk the sequences and groups a pipeline is built from down to its rules
+    fn explain_rules<B>(
+        &self,
+        request: Request<B>,
+        rules: &mut Vec<crate::RuleExplanation>,
+    ) -> Result<Request<B>, RewriteError> {
+        match &*self.0 {
+            AnyRewriter::Sequence(sequence) => {
+                let (first, second) = sequence.parts();
+                let request = first.explain_rules(request, rules)?;
+                second.explain_rules(request, rules)
+            }
+            AnyRewriter::Composite(composite) => composite
+                .rewriters()
+                .iter()
+                .try_fold(request, |request, rewriter| {
+                    rewriter.explain_rules(request, rules)
+                }),
+            // Every rule checks its own condition, so running them all in
+            // order gives the same result as looking them up by prefix
+            AnyRewriter::RouteTable(table) => {
+                table.rules().iter().try_fold(request, |request, rewriter| {
+                    rewriter.explain_rules(request, rules)
+                })
+            }
+            _ => self.explain_rule(request, rules),
+        }
+    }
+
+    fn explain_rule<B>(
+        &self,
+        request: Request<B>,
+        rules: &mut Vec<crate::RuleExplanation>,
+    ) -> Result<Request<B>, RewriteError> {
+        let (rule, inner) = match &*self.0 {
+            AnyRewriter::Named(named) => (named.name().to_string(), named.rewriter()),
+            _ => (format!("#{}", rules.len() + 1), self),
+        };
+        let matched = match &*inner.0 {
+            AnyRewriter::Conditional(conditional) => conditional.condition().matches(&request),
+            _ => true,
+        };
+
+        let before = crate::rewriter::RequestSnapshot::new(&request);
+        let result = self.rewrite(request);
+        let changes = match &result {
+            Ok(request) => before.changes(request),
+            Err(_) => Vec::new(),
+        };
+        rules.push(crate::RuleExplanation {
+            rule,
+            matched,
+            changes,
+        });
+        result
+    }
+
+    /// Turn a pipeline back into the rule configurations it was built from
+    ///
+    /// Every rule built from a [`ConditionalRewriterConfig`] remembers the
+    /// config it was built from, so the returned list equals the one the
+    /// pipeline was created with. Rule names are returned as given, so rules
+    /// which were named after their position are unnamed again. Pipelines
+    /// combined with [`Rewriter::concat`] return the rules of both.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if part of the pipeline was not built from a
+    /// [`ConditionalRewriterConfig`], such as a rewriter converted from a
+    /// built-in type or from a single [`RewriterConfig`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use http_rewriter::config::{ConditionalRewriterConfig, Rewriter, RewriterConfig, RewriterType};
+    ///
+    /// let configs = vec![
+    ///     ConditionalRewriterConfig::default()
+    ///         .with_rewriter(RewriterConfig::new(RewriterType::Path, &["^/old/", "/new/"])),
+    /// ];
+    /// let rewriter = Rewriter::try_from(configs.clone()).unwrap();
+    ///
+    /// assert_eq!(rewriter.to_configs().unwrap(), configs);
+    /// ```
+    pub fn to_configs(&self) -> Result<Vec<ConditionalRewriterConfig>, ConfigError> {
+        let mut configs = Vec::new();
+        self.collect_configs(&mut configs)?;
+        Ok(configs)
+    }
+
+    fn collect_configs(
+        &self,
+        configs: &mut Vec<ConditionalRewriterConfig>,
+    ) -> Result<(), ConfigError> {
+        if let Some(source) = &self.1 {
+            configs.push(ConditionalRewriterConfig::clone(source));
+            return Ok(());
+        }
+        match &*self.0 {
+            AnyRewriter::Sequence(sequence) => {
+                let (first, second) = sequence.parts();
+                first.collect_configs(configs)?;
+                second.collect_configs(configs)
+            }
+            AnyRewriter::Composite(composite) => composite
+                .rewriters()
+                .iter()
+                .try_for_each(|rewriter| rewriter.collect_configs(configs)),
+            AnyRewriter::RouteTable(table) => table
+                .rules()
+                .iter()
+                .try_for_each(|rewriter| rewriter.collect_configs(configs)),
+            _ => Err(ConfigError::new(
+                "Rewriter was not built from a ConditionalRewriterConfig",
+            )),
+        }
+    }
+}
+
+impl crate::Rewriter for Rewriter {
+    fn rewrite<B>(&self, request: Request<B>) -> Result<Request<B>, RewriteError> {
+        match &*self.0 {
+            AnyRewriter::Path(path) => path.rewrite(request),
+            AnyRewriter::Header(header) => header.rewrite(request),
+            AnyRewriter::Method(method) => method.rewrite(request),
+            AnyRewriter::Href(href) => href.rewrite(request),
+            AnyRewriter::StripQuery(strip) => strip.rewrite(request),
+            AnyRewriter::AllowQueryParams(allow) => allow.rewrite(request),
+            AnyRewriter::PathAndQuery(path_and_query) => path_and_query.rewrite(request),
+            AnyRewriter::DefaultHeader(default_header) => default_header.rewrite(request),
+            AnyRewriter::Named(named) => named.rewrite(request),
+            AnyRewriter::Version(version) => version.rewrite(request),
+            AnyRewriter::Port(port) => port.rewrite(request),
+            AnyRewriter::SortQuery(sort) => sort.rewrite(request),
+            AnyRewriter::StripHopByHop(strip) => strip.rewrite(request),
+            AnyRewriter::RemoveQueryParams(remove) => remove.rewrite(request),
+            AnyRewriter::UserInfo(userinfo) => userinfo.rewrite(request),
+            AnyRewriter::MethodOverride(rewriter) => rewriter.rewrite(request),
+            AnyRewriter::NormalizeMethod(normalize) => normalize.rewrite(request),
+            AnyRewriter::SetHeaders(set) => set.rewrite(request),
+            AnyRewriter::Sequence(sequence) => sequence.rewrite(request),
+            AnyRewriter::Composite(composite) => composite.rewrite(request),
+            AnyRewriter::Metadata(metadata) => metadata.rewrite(request),
+            AnyRewriter::RouteTable(table) => table.rewrite(request),
+            AnyRewriter::Conditional(conditional) => conditional.rewrite(request),
+        }
+    }
+
+    fn rewrite_with_context_and_log<B>(
+        &self,
+        request: Request<B>,
+        context: &mut crate::RewriteContext,
+        log: &mut Vec<crate::Change>,
+    ) -> Result<Request<B>, RewriteError> {
+        match &*self.0 {
+            AnyRewriter::Path(path) => path.rewrite_with_context_and_log(request, context, log),
+            AnyRewriter::Header(header) => {
+                header.rewrite_with_context_and_log(request, context, log)
+            }
+            AnyRewriter::Method(method) => {
+                method.rewrite_with_context_and_log(request, context, log)
+            }
+            AnyRewriter::Href(href) => href.rewrite_with_context_and_log(request, context, log),
+            AnyRewriter::StripQuery(strip) => {
+                strip.rewrite_with_context_and_log(request, context, log)
+            }
+            AnyRewriter::AllowQueryParams(allow) => {
+                allow.rewrite_with_context_and_log(request, context, log)
+            }
+            AnyRewriter::PathAndQuery(path_and_query) => {
+                path_and_query.rewrite_with_context_and_log(request, context, log)
+            }
+            AnyRewriter::DefaultHeader(default_header) => {
+                default_header.rewrite_with_context_and_log(request, context, log)
+            }
+            AnyRewriter::Named(named) => named.rewrite_with_context_and_log(request, context, log),
+            AnyRewriter::Version(version) => {
+                version.rewrite_with_context_and_log(request, context, log)
+            }
+            AnyRewriter::Port(port) => port.rewrite_with_context_and_log(request, context, log),
+            AnyRewriter::SortQuery(sort) => {
+                sort.rewrite_with_context_and_log(request, context, log)
+            }
+            AnyRewriter::StripHopByHop(strip) => {
+                strip.rewrite_with_context_and_log(request, context, log)
+            }
+            AnyRewriter::RemoveQueryParams(remove) => {
+                remove.rewrite_with_context_and_log(request, context, log)
+            }
+            AnyRewriter::UserInfo(userinfo) => {
+                userinfo.rewrite_with_context_and_log(request, context, log)
+            }
+            AnyRewriter::MethodOverride(rewriter) => {
+                rewriter.rewrite_with_context_and_log(request, context, log)
+            }
+            AnyRewriter::NormalizeMethod(normalize) => {
+                normalize.rewrite_with_context_and_log(request, context, log)
+            }
+            AnyRewriter::SetHeaders(set) => set.rewrite_with_context_and_log(request, context, log),
+            AnyRewriter::Sequence(sequence) => {
+                sequence.rewrite_with_context_and_log(request, context, log)
+            }
+            AnyRewriter::Composite(composite) => {
+                composite.rewrite_with_context_and_log(request, context, log)
+            }
+            AnyRewriter::Metadata(metadata) => {
+                metadata.rewrite_with_context_and_log(request, context, log)
+            }
+            AnyRewriter::RouteTable(table) => {
+                table.rewrite_with_context_and_log(request, context, log)
+            }
+            AnyRewriter::Conditional(conditional) => {
+                conditional.rewrite_with_context_and_log(request, context, log)
+            }
+        }
+    }
+
+    fn patterns(&self) -> Vec<&str> {
+        match &*self.0 {
+            AnyRewriter::Path(path) => path.patterns(),
+            AnyRewriter::Header(header) => header.patterns(),
+            AnyRewriter::Method(method) => method.patterns(),
+            AnyRewriter::Href(href) => href.patterns(),
+            AnyRewriter::StripQuery(strip) => strip.patterns(),
+            AnyRewriter::AllowQueryParams(allow) => allow.patterns(),
+            AnyRewriter::PathAndQuery(path_and_query) => path_and_query.patterns(),
+            AnyRewriter::DefaultHeader(default_header) => default_header.patterns(),
+            AnyRewriter::Named(named) => named.patterns(),
+            AnyRewriter::Version(version) => version.patterns(),
+            AnyRewriter::Port(port) => port.patterns(),
+            AnyRewriter::SortQuery(sort) => sort.patterns(),
+            AnyRewriter::StripHopByHop(strip) => strip.patterns(),
+            AnyRewriter::RemoveQueryParams(remove) => remove.patterns(),
+            AnyRewriter::UserInfo(userinfo) => userinfo.patterns(),
+            AnyRewriter::MethodOverride(rewriter) => rewriter.patterns(),
+            AnyRewriter::NormalizeMethod(normalize) => normalize.patterns(),
+            AnyRewriter::SetHeaders(set) => set.patterns(),
+            AnyRewriter::Sequence(sequence) => sequence.patterns(),
+            AnyRewriter::Composite(composite) => composite.patterns(),
+            AnyRewriter::Metadata(metadata) => metadata.patterns(),
+            AnyRewriter::RouteTable(table) => table.patterns(),
+            AnyRewriter::Conditional(conditional) => conditional.patterns(),
+        }
+    }
+}
+
+macro_rules! impl_from_rewriter {
+    ($type:ty, $variant:ident) => {
+        impl From<$type> for Rewriter {
+            fn from(rewriter: $type) -> Self {
+                Self(Arc::new(AnyRewriter::$variant(rewriter)), None)
+            }
+        }
+    };
+}
+
+impl_from_rewriter!(crate::PathRewriter, Path);
+impl_from_rewriter!(crate::HeaderRewriter, Header);
+impl_from_rewriter!(crate::MethodRewriter, Method);
+impl_from_rewriter!(crate::HrefRewriter, Href);
+impl_from_rewriter!(crate::StripQueryRewriter, StripQuery);
+impl_from_rewriter!(crate::AllowQueryParamsRewriter, AllowQueryParams);
+impl_from_rewriter!(crate::PathAndQueryRewriter, PathAndQuery);
+impl_from_rewriter!(crate::DefaultHeaderRewriter, DefaultHeader);
+impl_from_rewriter!(crate::NamedRewriter<Rewriter>, Named);
+impl_from_rewriter!(crate::VersionRewriter, Version);
+impl_from_rewriter!(crate::PortRewriter, Port);
+impl_from_rewriter!(crate::SortQueryRewriter, SortQuery);
+impl_from_rewriter!(crate::StripHopByHopRewriter, StripHopByHop);
+impl_from_rewriter!(crate::RemoveQueryParamsRewriter, RemoveQueryParams);
+impl_from_rewriter!(crate::UserInfoRewriter, UserInfo);
+impl_from_rewriter!(crate::MethodOverrideRewriter, MethodOverride);
+impl_from_rewriter!(crate::NormalizeMethodRewriter, NormalizeMethod);
+impl_from_rewriter!(
+    crate::CompositeRewriter<crate::HeaderSetRewriter>,
+    SetHeaders
+);
+impl_from_rewriter!(crate::SequenceRewriter<Rewriter, Rewriter>, Sequence);
+impl_from_rewriter!(crate::CompositeRewriter<Rewriter>, Composite);
+impl_from_rewriter!(crate::MarkRewriter<RuleMetadata>, Metadata);
+impl_from_rewriter!(crate::RouteTableRewriter<Rewriter>, RouteTable);
+impl_from_rewriter!(crate::ConditionalRewriter<Rewriter, Condition>, Conditional);
+
+impl TryFrom<ConditionalRewriterConfig> for Rewriter {
+    type Error = ConfigError;
+
+    fn try_from(config: ConditionalRewriterConfig) -> Result<Self, ConfigError> {
+        let name = config.name.clone();
+        Rewriter::from_rule(config, name)
+    }
+}
+
+impl Rewriter {
+    // Build a rule, keeping its config so the pipeline can be exported again
+    fn from_rule(
+        config: ConditionalRewriterConfig,
+        name: Option<String>,
+    ) -> Result<Self, ConfigError> {
+        let source = Arc::new(config.clone());
+
+        // Extract fields before consuming config
+        let ConditionalRewriterConfig {
+            operation,
+            conditions,
+            rewriters,
+            metadata,
+            ..
+        } = config;
+
+        // Validate that we have at least one rewriter
+        if rewriters.is_empty() {
+            return Err(ConfigError::new("At least one rewriter is required"));
+        }
+
+        let mut rewriters = rewriters
+            .into_iter()
+            .map(Rewriter::try_from)
+            .collect::<Result<Vec<_>, _>>()?;
+
+        // Record the metadata last, so it is only set when the rewriters succeed
+        if let Some(metadata) = metadata.filter(|metadata| !metadata.is_empty()) {
+            rewriters.push(crate::MarkRewriter::new(RuleMetadata(metadata)).into());
+        }
+        let rewriter: Rewriter = rewriters.try_into()?;
+
+        let conditions = conditions.unwrap_or_default();
+        let rewriter: Rewriter = if conditions.is_empty() {
+            rewriter
+        } else {
+            let conditions: Vec<Condition> = conditions
+                .into_iter()
+                .map(Condition::try_from)
+                .collect::<Result<Vec<_>, _>>()?;
+
+            let operation = operation.unwrap_or_default();
+
+            let condition: Condition = (operation, conditions).try_into()?;
+
+            rewriter.when(condition).into()
+        };
+
+        let rewriter: Rewriter = match name {
+            Some(name) => rewriter.named(name).into(),
+            None => rewriter,
+        };
+        Ok(Rewriter(rewriter.0, Some(source)))
+    }
+}
+
+impl TryFrom<RewriterConfig> for Rewriter {
+    type Error = ConfigError;
+
+    fn try_from(config: RewriterConfig) -> Result<Self, ConfigError> {
+        Ok(Rewriter(
+            Arc::new(match config.rewriter_type {
+                RewriterType::Path => AnyRewriter::Path(config.try_into()?),
+                RewriterType::Header => AnyRewriter::Header(config.try_into()?),
+                RewriterType::Method => AnyRewriter::Method(config.try_into()?),
+                RewriterType::Href => AnyRewriter::Href(config.try_into()?),
+                RewriterType::StripQuery => AnyRewriter::StripQuery(config.try_into()?),
+                RewriterType::AllowQueryParams => AnyRewriter::AllowQueryParams(config.try_into()?),
+                RewriterType::PathAndQuery => AnyRewriter::PathAndQuery(config.try_into()?),
+                RewriterType::DefaultHeader => AnyRewriter::DefaultHeader(config.try_into()?),
+                RewriterType::Version => AnyRewriter::Version(config.try_into()?),
+                RewriterType::Port => AnyRewriter::Port(config.try_into()?),
+                RewriterType::SortQuery => AnyRewriter::SortQuery(config.try_into()?),
+                RewriterType::StripHopByHop => AnyRewriter::StripHopByHop(config.try_into()?),
+                RewriterType::RemoveQueryParams => {
+                    AnyRewriter::RemoveQueryParams(config.try_into()?)
+                }
+                RewriterType::UserInfo => AnyRewriter::UserInfo(config.try_into()?),
+                RewriterType::MethodOverride => AnyRewriter::MethodOverride(config.try_into()?),
+                RewriterType::NormalizeMethod => AnyRewriter::NormalizeMethod(config.try_into()?),
+                RewriterType::SetHeaders => AnyRewriter::SetHeaders(config.try_into()?),
+            }),
+            None,
+        ))
+    }
+}
+
+impl TryFrom<Vec<RewriterConfig>> for Rewriter {
+    type Error = ConfigError;
+
+    fn try_from(configs: Vec<RewriterConfig>) -> Result<Self, ConfigError> {
+        if configs.is_empty() {
+            return Err(ConfigError::new(
+                "At least one rewriter configuration is required",
+            ));
+        }
+
+        // Convert each config to a rewriter
+        configs
+            .into_iter()
+            .map(Rewriter::try_from)
+            .collect::<Result<Vec<_>, _>>()?
+            .try_into()
+    }
+}
+
+impl TryFrom<Vec<Rewriter>> for Rewriter {
+    type Error = ConfigError;
+
+    fn try_from(rewriters: Vec<Rewriter>) -> Result<Self, ConfigError> {
+        // Ensure we have at least one rewriter
+        if rewriters.is_empty() {
+            return Err(ConfigError::new("At least one rewriter is required"));
+        }
+
+        // A single rewriter needs no grouping
+        if rewriters.len() == 1 {
+            return Ok(rewriters.into_iter().next().unwrap());
+        }
+
+        // Group the rewriters so a rule's condition is checked once for all of them
+        Ok(crate::CompositeRewriter::new(rewriters).into())
+    }
+}
+
+impl TryFrom<Vec<ConditionalRewriterConfig>> for Rewriter {
+    type Error = ConfigError;
+
+    fn try_from(configs: Vec<ConditionalRewriterConfig>) -> Result<Self, ConfigError> {
+        if configs.is_empty() {
+            return Err(ConfigError::new("At least one configuration is required"));
+        }
+
+        // Rules which only check a plain path prefix can be looked up by
+        // prefix rather than checked one by one
+        let prefixes: Option<Vec<String>> = configs.iter().map(rule_prefix).collect();
+
+        // Convert each config to a conditional rewriter, naming unnamed rules
+        // by their position so errors identify the failing rule
+        let rules = configs
+            .into_iter()
+            .enumerate()
+            .map(|(index, config)| {
+                let name = config
+                    .name
+                    .clone()
+                    .unwrap_or_else(|| format!("#{}", index + 1));
+                Rewriter::from_rule(config, Some(name))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        match prefixes {
+            Some(prefixes) if rules.len() > 1 => {
+                Ok(crate::RouteTableRewriter::new(prefixes.into_iter().zip(rules)).into())
+            }
+            _ => rules.try_into(),
+        }
+    }
+}
+
+// Get the path prefix a rule is limited to, if that is all it checks
+fn rule_prefix(config: &ConditionalRewriterConfig) -> Option<String> {
+    match config.conditions.as_deref().unwrap_or_default() {
+        [] => Some(String::new()),
+        [condition] if condition.condition == ConditionType::Path => {
+            match condition.args.as_deref()? {
+                [pattern] => crate::route_table::literal_prefix(pattern),
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
@@ -0,0 +1,182 @@
+//! State shared by the stages of a rewrite pipeline
+//!
+//! This module provides the [`RewriteContext`] type, a typed map which a
+//! pipeline passes to each of its stages alongside the request. Stages can
+//! record what they did, such as which checks passed, for later stages to
+//! read, without leaving anything behind on the request itself.
+//!
+//! Start a pipeline with a context using
+//! [`Rewriter::rewrite_with_context`]. Plain [`Rewriter::rewrite`] gives the
+//! pipeline an empty context, so existing callers need no changes. Stages
+//! which use the context are usually written with [`fn_context_rewriter`].
+//!
+//! # Examples
+//!
+//! ```
+//! use http_rewriter::{RewriteContext, Rewriter, RewriterExt, fn_context_rewriter};
+//! use http::Request;
+//!
+//! #[derive(Clone)]
+//! struct Tenant(String);
+//!
+//! let pipeline = fn_context_rewriter(|parts, context| {
+//!     if let Some(host) = parts.uri.host() {
+//!         context.insert(Tenant(host.split('.').next().unwrap_or_default().to_string()));
+//!     }
+//!     Ok(())
+//! })
+//! .then(fn_context_rewriter(|parts, context| {
+//!     if let Some(Tenant(tenant)) = context.get::<Tenant>() {
+//!         parts.headers.insert("x-tenant", tenant.parse().unwrap());
+//!     }
+//!     Ok(())
+//! }));
+//!
+//! let request = Request::builder()
+//!     .uri("https://acme.example.com/")
+//!     .body(())
+//!     .unwrap();
+//!
+//! let mut context = RewriteContext::new();
+//! let result = pipeline.rewrite_with_context(request, &mut context).unwrap();
+//! assert_eq!(result.headers()["x-tenant"], "acme");
+//! assert!(context.get::<Tenant>().is_some());
+//! ```
+
+use std::fmt::{Debug, Formatter, Result as FmtResult};
+
+use http::request::Parts;
+use http::{Extensions, Request};
+
+use crate::{RewriteError, Rewriter};
+
+/// Typed map shared by the stages of a rewrite pipeline
+///
+/// Values are stored by type, so each type holds at most one value, just
+/// like request extensions. Define a type for each piece of state rather than
+/// storing plain strings or numbers, so that unrelated stages do not
+/// overwrite each other.
+///
+/// # Examples
+///
+/// ```
+/// use http_rewriter::RewriteContext;
+///
+/// #[derive(Clone, Default)]
+/// struct ChecksPassed(Vec<&'static str>);
+///
+/// let mut context = RewriteContext::new();
+/// context.get_or_insert_default::<ChecksPassed>().0.push("auth");
+/// context.get_or_insert_default::<ChecksPassed>().0.push("rate-limit");
+///
+/// assert_eq!(context.get::<ChecksPassed>().unwrap().0, ["auth", "rate-limit"]);
+/// ```
+#[derive(Clone, Default)]
+pub struct RewriteContext(Extensions);
+
+impl RewriteContext {
+    /// Create an empty context
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Insert a value, returning the previous value of the same type
+    pub fn insert<T: Clone + Send + Sync + 'static>(&mut self, value: T) -> Option<T> {
+        self.0.insert(value)
+    }
+
+    /// Get a reference to the value of a type
+    pub fn get<T: Send + Sync + 'static>(&self) -> Option<&T> {
+        self.0.get()
+    }
+
+    /// Get a mutable reference to the value of a type
+    pub fn get_mut<T: Send + Sync + 'static>(&mut self) -> Option<&mut T> {
+        self.0.get_mut()
+    }
+
+    /// Get a mutable reference to the value of a type, inserting its default if missing
+    pub fn get_or_insert_default<T: Default + Clone + Send + Sync + 'static>(&mut self) -> &mut T {
+        self.0.get_or_insert_default()
+    }
+
+    /// Remove the value of a type, returning it
+    pub fn remove<T: Send + Sync + 'static>(&mut self) -> Option<T> {
+        self.0.remove()
+    }
+
+    /// Check whether the context holds no values
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+impl Debug for RewriteContext {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        f.debug_struct("RewriteContext")
+            .field("len", &self.0.len())
+            .finish()
+    }
+}
+
+/// Rewriter that changes the request [`Parts`] with a closure which also sees the context
+///
+/// Usually created with [`fn_context_rewriter`]. The closure changes the
+/// request metadata in place, so the body is never touched. When used with
+/// plain [`Rewriter::rewrite`], the closure sees an empty context.
+#[derive(Clone, Copy)]
+pub struct ContextRewriter<F>(F);
+
+impl<F> Debug for ContextRewriter<F> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        f.debug_tuple("ContextRewriter").finish_non_exhaustive()
+    }
+}
+
+impl<F> Rewriter for ContextRewriter<F>
+where
+    F: Fn(&mut Parts, &mut RewriteContext) -> Result<(), RewriteError> + Send + Sync,
+{
+    fn rewrite<B>(&self, request: Request<B>) -> Result<Request<B>, RewriteError> {
+        self.rewrite_with_context(request, &mut RewriteContext::new())
+    }
+
+    fn rewrite_with_context<B>(
+        &self,
+        request: Request<B>,
+        context: &mut RewriteContext,
+    ) -> Result<Request<B>, RewriteError> {
+        let (mut parts, body) = request.into_parts();
+        (self.0)(&mut parts, context)?;
+        Ok(Request::from_parts(parts, body))
+    }
+}
+
+/// Create a rewriter from a closure over the request [`Parts`] and the pipeline context
+///
+/// # Examples
+///
+/// ```
+/// use http_rewriter::{RewriteContext, Rewriter, fn_context_rewriter};
+/// use http::Request;
+///
+/// #[derive(Clone)]
+/// struct OriginalPath(String);
+///
+/// // Remember the path before later stages change it
+/// let remember = fn_context_rewriter(|parts, context| {
+///     context.insert(OriginalPath(parts.uri.path().to_string()));
+///     Ok(())
+/// });
+///
+/// let request = Request::builder().uri("/old/page").body(()).unwrap();
+/// let mut context = RewriteContext::new();
+/// remember.rewrite_with_context(request, &mut context).unwrap();
+/// assert_eq!(context.get::<OriginalPath>().unwrap().0, "/old/page");
+/// ```
+pub fn fn_context_rewriter<F>(f: F) -> ContextRewriter<F>
+where
+    F: Fn(&mut Parts, &mut RewriteContext) -> Result<(), RewriteError> + Send + Sync,
+{
+    ContextRewriter(f)
+}
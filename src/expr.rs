@@ -0,0 +1,382 @@
+//! Conditions written as boolean expressions
+//!
+//! This module provides [`parse_condition`], which compiles an expression
+//! such as `path("^/api") and not method("GET")` into a
+//! [`config::Condition`](crate::config::Condition). This is more compact than
+//! nesting condition configs by hand, and suits conditions read from
+//! configuration files or command line flags.
+//!
+//! This module is only available with the `expr` feature enabled.
+//!
+//! # Syntax
+//!
+//! An expression combines condition calls with `and`, `or` and `not`, in
+//! order of increasing precedence, grouped with parentheses where needed.
+//! Each built-in condition is called by the snake case name of its
+//! [`ConditionType`], with the same arguments its config takes:
+//!
+//! ```text
+//! path("^/admin") and (header("X-Role", "^admin$") or host(r"^internal\."))
+//! not exists() and forwarded_for_count(gt, 1)
+//! ```
+//!
+//! Arguments are either double-quoted strings, where `\"` and `\\` are
+//! escapes, raw strings written `r"..."` without escapes, or bare words made
+//! of letters, digits, `_`, `-` and `.`, such as `8080` or `gte`.
+//!
+//! # Examples
+//!
+//! ```
+//! use http_rewriter::{Condition, parse_condition};
+//! use http::{Method, Request};
+//!
+//! let condition = parse_condition(r#"path("^/api") and not method("GET")"#).unwrap();
+//!
+//! let request = Request::builder()
+//!     .method(Method::POST)
+//!     .uri("/api/users")
+//!     .body(())
+//!     .unwrap();
+//! assert!(condition.matches(&request));
+//! ```
+
+use std::fmt::{Display, Formatter, Result as FmtResult};
+
+use crate::GroupCondition;
+use crate::config::{Condition, ConditionConfig, ConditionType};
+
+/// Error returned when a condition expression can not be compiled
+///
+/// The position is the byte offset into the expression where the problem was
+/// found, such as an unexpected token or the name of a condition whose
+/// arguments were rejected.
+///
+/// # Examples
+///
+/// ```
+/// use http_rewriter::parse_condition;
+///
+/// let error = parse_condition(r#"path("^/api") and"#).unwrap_err();
+/// assert_eq!(error.position(), 17);
+/// assert_eq!(error.message(), "Expected a condition, found end of input");
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExprError {
+    position: usize,
+    message: String,
+}
+
+impl ExprError {
+    fn new(position: usize, message: impl Into<String>) -> Self {
+        Self {
+            position,
+            message: message.into(),
+        }
+    }
+
+    /// Get the byte offset in the expression where the error was found
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    /// Get the error message
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+}
+
+impl Display for ExprError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write!(
+            f,
+            "Expression error at position {}: {}",
+            self.position, self.message
+        )
+    }
+}
+
+impl std::error::Error for ExprError {}
+
+/// Compile a boolean expression into a condition
+///
+/// See the [module documentation](self) for the syntax.
+///
+/// # Arguments
+///
+/// * `input` - The expression to compile
+///
+/// # Errors
+///
+/// Returns an error if the expression is malformed, names an unknown
+/// condition, or passes arguments the condition rejects
+///
+/// # Examples
+///
+/// ```
+/// use http_rewriter::{Condition, parse_condition};
+/// use http::Request;
+///
+/// let condition = parse_condition(r#"not (path("^/static/") or path_glob("/*.ico"))"#).unwrap();
+///
+/// let request = Request::builder().uri("/index.html").body(()).unwrap();
+/// assert!(condition.matches(&request));
+///
+/// let request = Request::builder().uri("/favicon.ico").body(()).unwrap();
+/// assert!(!condition.matches(&request));
+/// ```
+pub fn parse_condition(input: &str) -> Result<Condition, ExprError> {
+    let mut parser = Parser {
+        tokens: tokenize(input)?,
+        index: 0,
+        end: input.len(),
+    };
+    let condition = parser.or()?;
+    match parser.peek() {
+        None => Ok(condition),
+        Some((position, token)) => Err(ExprError::new(
+            position,
+            format!("Expected end of input, found {}", token.describe()),
+        )),
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    LParen,
+    RParen,
+    Comma,
+    Word(String),
+    Str(String),
+}
+
+impl Token {
+    fn describe(&self) -> String {
+        match self {
+            Token::LParen => "'('".to_string(),
+            Token::RParen => "')'".to_string(),
+            Token::Comma => "','".to_string(),
+            Token::Word(word) => format!("'{word}'"),
+            Token::Str(_) => "a string".to_string(),
+        }
+    }
+}
+
+fn is_word_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.')
+}
+
+/// Split an expression into tokens, each with its byte offset
+fn tokenize(input: &str) -> Result<Vec<(usize, Token)>, ExprError> {
+    let mut tokens = Vec::new();
+    let mut chars = input.char_indices().peekable();
+
+    while let Some((start, c)) = chars.next() {
+        let token = match c {
+            c if c.is_whitespace() => continue,
+            '(' => Token::LParen,
+            ')' => Token::RParen,
+            ',' => Token::Comma,
+            'r' if matches!(chars.peek(), Some((_, '"'))) => {
+                chars.next();
+                let mut value = String::new();
+                loop {
+                    match chars.next() {
+                        Some((_, '"')) => break,
+                        Some((_, c)) => value.push(c),
+                        None => return Err(ExprError::new(start, "Unterminated string")),
+                    }
+                }
+                Token::Str(value)
+            }
+            '"' => {
+                let mut value = String::new();
+                loop {
+                    match chars.next() {
+                        Some((_, '"')) => break,
+                        Some((position, '\\')) => match chars.next() {
+                            Some((_, c @ ('"' | '\\'))) => value.push(c),
+                            _ => return Err(ExprError::new(position, "Invalid escape in string")),
+                        },
+                        Some((_, c)) => value.push(c),
+                        None => return Err(ExprError::new(start, "Unterminated string")),
+                    }
+                }
+                Token::Str(value)
+            }
+            c if is_word_char(c) => {
+                let mut word = c.to_string();
+                while let Some(&(_, c)) = chars.peek()
+                    && is_word_char(c)
+                {
+                    word.push(c);
+                    chars.next();
+                }
+                Token::Word(word)
+            }
+            c => return Err(ExprError::new(start, format!("Unexpected character '{c}'"))),
+        };
+        tokens.push((start, token));
+    }
+    Ok(tokens)
+}
+
+/// Recursive descent parser over the tokens of an expression
+struct Parser {
+    tokens: Vec<(usize, Token)>,
+    index: usize,
+    end: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<(usize, &Token)> {
+        self.tokens
+            .get(self.index)
+            .map(|(position, token)| (*position, token))
+    }
+
+    fn next(&mut self) -> Option<(usize, Token)> {
+        let token = self.tokens.get(self.index).cloned();
+        self.index += 1;
+        token
+    }
+
+    fn next_is_keyword(&self, keyword: &str) -> bool {
+        matches!(self.peek(), Some((_, Token::Word(word))) if word == keyword)
+    }
+
+    fn expect(&mut self, expected: Token, what: &str) -> Result<(), ExprError> {
+        match self.next() {
+            Some((_, token)) if token == expected => Ok(()),
+            Some((position, token)) => Err(ExprError::new(
+                position,
+                format!("Expected {what}, found {}", token.describe()),
+            )),
+            None => Err(ExprError::new(
+                self.end,
+                format!("Expected {what}, found end of input"),
+            )),
+        }
+    }
+
+    fn or(&mut self) -> Result<Condition, ExprError> {
+        let mut condition = self.and()?;
+        while self.next_is_keyword("or") {
+            self.index += 1;
+            let other = self.and()?;
+            condition = (*GroupCondition::or(Box::new(condition), Box::new(other))).into();
+        }
+        Ok(condition)
+    }
+
+    fn and(&mut self) -> Result<Condition, ExprError> {
+        let mut condition = self.not()?;
+        while self.next_is_keyword("and") {
+            self.index += 1;
+            let other = self.not()?;
+            condition = (*GroupCondition::and(Box::new(condition), Box::new(other))).into();
+        }
+        Ok(condition)
+    }
+
+    fn not(&mut self) -> Result<Condition, ExprError> {
+        if self.next_is_keyword("not") {
+            self.index += 1;
+            return Ok(crate::NotCondition::new(self.not()?).into());
+        }
+        self.primary()
+    }
+
+    fn primary(&mut self) -> Result<Condition, ExprError> {
+        match self.next() {
+            Some((_, Token::LParen)) => {
+                let condition = self.or()?;
+                self.expect(Token::RParen, "')'")?;
+                Ok(condition)
+            }
+            Some((position, Token::Word(name))) if !is_keyword(&name) => self.call(position, &name),
+            Some((position, token)) => Err(ExprError::new(
+                position,
+                format!("Expected a condition, found {}", token.describe()),
+            )),
+            None => Err(ExprError::new(
+                self.end,
+                "Expected a condition, found end of input",
+            )),
+        }
+    }
+
+    fn call(&mut self, position: usize, name: &str) -> Result<Condition, ExprError> {
+        let condition_type = condition_type(name)
+            .ok_or_else(|| ExprError::new(position, format!("Unknown condition '{name}'")))?;
+        self.expect(Token::LParen, &format!("'(' after '{name}'"))?;
+
+        let mut args = Vec::new();
+        if !matches!(self.peek(), Some((_, Token::RParen))) {
+            loop {
+                match self.next() {
+                    Some((_, Token::Str(arg) | Token::Word(arg))) => args.push(arg),
+                    Some((position, token)) => {
+                        return Err(ExprError::new(
+                            position,
+                            format!("Expected an argument, found {}", token.describe()),
+                        ));
+                    }
+                    None => {
+                        return Err(ExprError::new(
+                            self.end,
+                            "Expected an argument, found end of input",
+                        ));
+                    }
+                }
+                if !matches!(self.peek(), Some((_, Token::Comma))) {
+                    break;
+                }
+                self.index += 1;
+            }
+        }
+        self.expect(Token::RParen, "')' or ','")?;
+
+        let args: Vec<&str> = args.iter().map(String::as_str).collect();
+        Condition::try_from(ConditionConfig::new(condition_type, &args))
+            .map_err(|e| ExprError::new(position, e.message()))
+    }
+}
+
+fn is_keyword(word: &str) -> bool {
+    matches!(word, "and" | "or" | "not")
+}
+
+/// Look up a built-in condition by its name in expressions
+fn condition_type(name: &str) -> Option<ConditionType> {
+    Some(match name {
+        "path" => ConditionType::Path,
+        "header" => ConditionType::Header,
+        "method" => ConditionType::Method,
+        "exists" => ConditionType::Exists,
+        "not_exists" => ConditionType::NotExists,
+        "header_absent" => ConditionType::HeaderAbsent,
+        "auth_scheme" => ConditionType::AuthScheme,
+        "query" => ConditionType::Query,
+        "query_param" => ConditionType::QueryParam,
+        "host" => ConditionType::Host,
+        "language" => ConditionType::Language,
+        "forwarded_for_count" => ConditionType::ForwardedForCount,
+        "path_glob" => ConditionType::PathGlob,
+        "absolute_uri" => ConditionType::AbsoluteUri,
+        "forwarded" => ConditionType::Forwarded,
+        "port" => ConditionType::Port,
+        "header_name_exists" => ConditionType::HeaderNameExists,
+        "required_headers" => ConditionType::RequiredHeaders,
+        "header_compare" => ConditionType::HeaderCompare,
+        "trailing_slash" => ConditionType::TrailingSlash,
+        "target_length" => ConditionType::TargetLength,
+        "query_flag" => ConditionType::QueryFlag,
+        "cors_preflight" => ConditionType::CorsPreflight,
+        "host_allowlist" => ConditionType::HostAllowlist,
+        "document_root" => ConditionType::DocumentRoot,
+        "duplicate_header" => ConditionType::DuplicateHeader,
+        "traversal" => ConditionType::Traversal,
+        "accept_encoding" => ConditionType::AcceptEncoding,
+        _ => return None,
+    })
+}
@@ -0,0 +1,307 @@
+//! Rewrite rules reloaded from a watched file
+//!
+//! This module provides the [`HotConfig`] type, which builds a
+//! [`config::Rewriter`](crate::config::Rewriter) from a file and rebuilds it
+//! whenever the file changes, so long-running servers can pick up new rules
+//! without restarting. Requests are rewritten with whichever pipeline was
+//! current when they arrived; a reload never affects a rewrite in progress.
+//!
+//! This module is only available with the `hot-reload` feature enabled.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use std::time::Duration;
+//! use http_rewriter::HotConfig;
+//! use http_rewriter::config::{ConfigError, Rewriter, RewriterConfig, RewriterType};
+//!
+//! // One `pattern replacement` path rewrite per line
+//! fn parse_rules(contents: &str) -> Result<Rewriter, ConfigError> {
+//!     contents
+//!         .lines()
+//!         .map(|line| {
+//!             let args: Vec<&str> = line.split_whitespace().collect();
+//!             Rewriter::try_from(RewriterConfig::new(RewriterType::Path, &args))
+//!         })
+//!         .collect::<Result<Vec<_>, _>>()?
+//!         .try_into()
+//! }
+//!
+//! let rules = HotConfig::builder("/etc/proxy/rules.conf", parse_rules)
+//!     .poll_interval(Duration::from_secs(5))
+//!     .on_error(|error| eprintln!("keeping previous rules: {error}"))
+//!     .build()
+//!     .unwrap();
+//! ```
+
+use std::fmt::{Debug, Formatter, Result as FmtResult};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Weak};
+use std::thread;
+use std::time::{Duration, SystemTime};
+
+use arc_swap::ArcSwap;
+use http::Request;
+
+use crate::config::{ConfigError, Rewriter as ConfigRewriter};
+use crate::{RewriteError, Rewriter};
+
+type Loader = dyn Fn(&str) -> Result<ConfigRewriter, ConfigError> + Send + Sync;
+type ErrorHandler = dyn Fn(&ConfigError) + Send + Sync;
+
+/// Rewriter whose rules are loaded from a file and reloaded when it changes
+///
+/// The file is read as UTF-8 and passed to a loader function, which turns
+/// its contents into a [`config::Rewriter`](crate::config::Rewriter). This
+/// keeps the file format up to the application. A background thread checks
+/// the file's modification time and size at a fixed interval and reloads it
+/// when either changes. The thread stops once every clone of the
+/// `HotConfig` has been dropped.
+///
+/// If a reload fails, because the file can not be read or the loader
+/// rejects it, the last good pipeline is kept and the error is passed to the
+/// handler set with [`HotConfigBuilder::on_error`].
+///
+/// [`Rewriter::patterns`] is empty, as the patterns of a pipeline which may
+/// be swapped out at any moment can not be borrowed. Use
+/// [`HotConfig::current`] to inspect a snapshot instead.
+///
+/// # Examples
+///
+/// ```
+/// use http_rewriter::{HotConfig, Rewriter};
+/// use http_rewriter::config::{ConfigError, Rewriter as ConfigRewriter, RewriterConfig, RewriterType};
+/// use http::Request;
+///
+/// // One `pattern replacement` path rewrite per line
+/// fn parse_rules(contents: &str) -> Result<ConfigRewriter, ConfigError> {
+///     contents
+///         .lines()
+///         .map(|line| {
+///             let args: Vec<&str> = line.split_whitespace().collect();
+///             ConfigRewriter::try_from(RewriterConfig::new(RewriterType::Path, &args))
+///         })
+///         .collect::<Result<Vec<_>, _>>()?
+///         .try_into()
+/// }
+///
+/// let path = std::env::temp_dir().join("http-rewriter-hot-config-example.conf");
+/// std::fs::write(&path, "^/old/ /new/\n").unwrap();
+///
+/// let rules = HotConfig::new(&path, parse_rules).unwrap();
+///
+/// let request = Request::builder().uri("/old/page").body(()).unwrap();
+/// assert_eq!(rules.rewrite(request).unwrap().uri().path(), "/new/page");
+/// # std::fs::remove_file(&path).unwrap();
+/// ```
+#[derive(Clone)]
+pub struct HotConfig(Arc<State>);
+
+struct State {
+    path: PathBuf,
+    load: Box<Loader>,
+    on_error: Option<Box<ErrorHandler>>,
+    current: ArcSwap<ConfigRewriter>,
+}
+
+impl HotConfig {
+    /// Load rules from a file and reload them when it changes
+    ///
+    /// The file is checked for changes every second. Use
+    /// [`HotConfig::builder`] to change the interval or handle reload errors.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The file to load the rules from
+    /// * `load` - Function turning the file contents into a pipeline
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file can not be read or the initial load fails
+    pub fn new<F>(path: impl Into<PathBuf>, load: F) -> Result<Self, ConfigError>
+    where
+        F: Fn(&str) -> Result<ConfigRewriter, ConfigError> + Send + Sync + 'static,
+    {
+        Self::builder(path, load).build()
+    }
+
+    /// Create a builder for a hot-reloaded configuration
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The file to load the rules from
+    /// * `load` - Function turning the file contents into a pipeline
+    pub fn builder<F>(path: impl Into<PathBuf>, load: F) -> HotConfigBuilder
+    where
+        F: Fn(&str) -> Result<ConfigRewriter, ConfigError> + Send + Sync + 'static,
+    {
+        HotConfigBuilder {
+            path: path.into(),
+            load: Box::new(load),
+            on_error: None,
+            poll_interval: Some(Duration::from_secs(1)),
+        }
+    }
+
+    /// Reload the rules from the file now
+    ///
+    /// This is done automatically when the file changes, but can be used to
+    /// reload on a signal instead, or when polling is disabled.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file can not be read or the loader rejects
+    /// it. The previous rules are kept, and the error is also passed to the
+    /// error handler.
+    pub fn reload(&self) -> Result<(), ConfigError> {
+        self.0.reload()
+    }
+
+    /// Get the pipeline currently in use
+    ///
+    /// The snapshot stays valid, and unchanged, after later reloads.
+    pub fn current(&self) -> Arc<ConfigRewriter> {
+        self.0.current.load_full()
+    }
+
+    /// Get the file the rules are loaded from
+    pub fn path(&self) -> &Path {
+        &self.0.path
+    }
+}
+
+impl State {
+    fn reload(&self) -> Result<(), ConfigError> {
+        match load_rules(&self.path, &*self.load) {
+            Ok(rewriter) => {
+                self.current.store(Arc::new(rewriter));
+                Ok(())
+            }
+            Err(error) => {
+                if let Some(on_error) = &self.on_error {
+                    on_error(&error);
+                }
+                Err(error)
+            }
+        }
+    }
+}
+
+impl Debug for HotConfig {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        f.debug_struct("HotConfig")
+            .field("path", &self.0.path)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Rewriter for HotConfig {
+    fn rewrite<B>(&self, request: Request<B>) -> Result<Request<B>, RewriteError> {
+        self.0.current.load().rewrite(request)
+    }
+}
+
+/// Builder for a [`HotConfig`]
+///
+/// Created with [`HotConfig::builder`].
+pub struct HotConfigBuilder {
+    path: PathBuf,
+    load: Box<Loader>,
+    on_error: Option<Box<ErrorHandler>>,
+    poll_interval: Option<Duration>,
+}
+
+impl HotConfigBuilder {
+    /// Set how often the file is checked for changes, one second by default
+    ///
+    /// With `None` the file is never checked, and rules are only reloaded
+    /// with [`HotConfig::reload`].
+    pub fn poll_interval(mut self, interval: impl Into<Option<Duration>>) -> Self {
+        self.poll_interval = interval.into();
+        self
+    }
+
+    /// Set a function to call when reloading the rules fails
+    ///
+    /// The previous rules stay in use. The function is called from the
+    /// polling thread, or from [`HotConfig::reload`] when reloading by hand.
+    pub fn on_error<F>(mut self, on_error: F) -> Self
+    where
+        F: Fn(&ConfigError) + Send + Sync + 'static,
+    {
+        self.on_error = Some(Box::new(on_error));
+        self
+    }
+
+    /// Load the rules and start watching the file
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file can not be read or the initial load
+    /// fails. The error handler is not called for this error.
+    pub fn build(self) -> Result<HotConfig, ConfigError> {
+        let fingerprint = fingerprint(&self.path);
+        let rewriter = load_rules(&self.path, &*self.load)?;
+        let state = State {
+            path: self.path,
+            load: self.load,
+            on_error: self.on_error,
+            current: ArcSwap::from_pointee(rewriter),
+        };
+
+        let state = Arc::new(state);
+        if let Some(interval) = self.poll_interval {
+            watch(Arc::downgrade(&state), fingerprint, interval)
+                .map_err(|e| ConfigError::new(format!("Failed to start watching: {e}")))?;
+        }
+        Ok(HotConfig(state))
+    }
+}
+
+impl Debug for HotConfigBuilder {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        f.debug_struct("HotConfigBuilder")
+            .field("path", &self.path)
+            .field("poll_interval", &self.poll_interval)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Read the file and turn its contents into a pipeline
+fn load_rules(path: &Path, load: &Loader) -> Result<ConfigRewriter, ConfigError> {
+    let contents = fs::read_to_string(path)
+        .map_err(|e| ConfigError::new(format!("Failed to read {}: {e}", path.display())))?;
+    load(&contents)
+}
+
+/// The modification time and size of a file, used to notice changes
+fn fingerprint(path: &Path) -> Option<(SystemTime, u64)> {
+    let metadata = fs::metadata(path).ok()?;
+    Some((metadata.modified().ok()?, metadata.len()))
+}
+
+/// Poll the file for changes until the configuration is dropped
+fn watch(
+    state: Weak<State>,
+    mut last: Option<(SystemTime, u64)>,
+    interval: Duration,
+) -> std::io::Result<()> {
+    thread::Builder::new()
+        .name("http-rewriter-hot-config".to_string())
+        .spawn(move || {
+            loop {
+                thread::sleep(interval);
+                let Some(state) = state.upgrade() else {
+                    break;
+                };
+                let current = fingerprint(&state.path);
+                if current != last {
+                    last = current;
+                    // Errors are reported to the handler, and the old rules kept
+                    let _ = state.reload();
+                }
+            }
+        })?;
+    Ok(())
+}
@@ -152,26 +152,71 @@
 //! - [`condition`]: Types for matching requests (PathCondition, MethodCondition, etc.)
 //! - [`rewriter`]: Types for transforming requests (PathRewriter, HeaderRewriter, etc.)
 //! - [`conditional_rewriter`]: Combines conditions and rewriters
+//! - [`config`]: Builds rewriters from configuration data
+//! - [`context`]: State shared by the stages of a pipeline
+//! - [`route_table`]: Path prefix lookup for large rule sets
+//! - `url_rewriter`: Structured URL rewriting (requires the `url` feature)
+//! - `hot_config`: Rules reloaded from a watched file (requires the `hot-reload` feature)
 
 #![warn(clippy::dbg_macro, clippy::print_stdout)]
 #![warn(missing_docs)]
 
+#[cfg(feature = "serde_json")]
+pub mod claim_condition;
 pub mod condition;
 pub mod conditional_rewriter;
+pub mod config;
+pub mod context;
+#[cfg(feature = "expr")]
+pub mod expr;
+#[cfg(feature = "hot-reload")]
+pub mod hot_config;
 pub mod rewriter;
+pub mod route_table;
+#[cfg(feature = "url")]
+pub mod url_rewriter;
 
 #[cfg(test)]
 mod integration_tests;
 
+#[cfg(feature = "serde_json")]
+pub use claim_condition::ClaimCondition;
 pub use condition::{
-    Condition, ConditionExt, ExistenceCondition, GroupCondition, HeaderCondition, MethodCondition,
-    NonExistenceCondition, PathCondition,
+    AbsoluteUriCondition, AcceptEncodingCondition, AuthSchemeCondition, BloomPathCondition,
+    BucketCondition, Comparison, Condition, ConditionExt, ConditionRef, ContentLengthCondition,
+    CookieCondition, CorsPreflightCondition, DocumentRootCondition, DuplicateHeaderCondition,
+    ExistenceCondition, FnCondition, ForwardedCondition, ForwardedForCountCondition,
+    ForwardedParam, ForwardedProtoCondition, GroupCondition, HasBodyCondition,
+    HeaderAbsentCondition, HeaderCompareCondition, HeaderCondition, HeaderNameExistsCondition,
+    HostAllowlistCondition, HostCondition, HostListMode, LanguageCondition, MarkerCondition,
+    MethodCondition, NonExistenceCondition, NotCondition, PathCondition, PathGlobCondition,
+    PortCondition, QueryCondition, QueryFlagCondition, QueryParamCondition, RequestView,
+    RequiredHeadersCondition, TargetLengthCondition, ThresholdCondition, TlsCondition, TlsInfo,
+    TlsVersion, TrailingSlashCondition, TraversalCondition, fn_condition,
+    missing_content_type_on_write,
 };
 pub use conditional_rewriter::ConditionalRewriter;
+pub use context::{ContextRewriter, RewriteContext, fn_context_rewriter};
+#[cfg(feature = "expr")]
+pub use expr::{ExprError, parse_condition};
+#[cfg(feature = "hot-reload")]
+pub use hot_config::{HotConfig, HotConfigBuilder};
 pub use rewriter::{
-    HeaderRewriter, HrefRewriter, MethodRewriter, PathRewriter, RewriteError, Rewriter,
-    RewriterExt, SequenceRewriter,
+    AllowQueryParamsRewriter, AppendForwardedForRewriter, AppendQueryParamRewriter, BranchRewriter,
+    CanonicalizeHeaderOptions, CanonicalizeHeaderRewriter, Change, ClientAddr, CompositeRewriter,
+    DefaultHeaderRewriter, ForwardedProtoRewriter, HeaderRewriter, HeaderRewriterBuilder,
+    HeaderSetRewriter, HostRouter, HrefRewriter, InfallibleRewriter, Invertible, MapRewriter,
+    MarkRewriter, MethodOverrideRewriter, MethodRewriter, NamedRewriter, NormalizeMethodRewriter,
+    PathAndQueryRewriter, PathMethodRewriter, PathRewriter, PathWrapRewriter, PortRewriter,
+    PrefixToQueryRewriter, RemoveQueryParamsRewriter, RepeatRewriter, RequestIdRewriter,
+    RewriteError, RewriteExplanation, RewriteTimings, Rewriter, RewriterExt, RewriterRef,
+    RuleExplanation, SegmentRewriter, SequenceRewriter, SortQueryRewriter, StripHopByHopRewriter,
+    StripQueryRewriter, SyncHostRewriter, TimedRewriter, ToggleHandle, ToggleRewriter,
+    UserInfoRewriter, VersionRewriter, strip_auth_on,
 };
+pub use route_table::{RouteTable, RouteTableRewriter};
+#[cfg(feature = "url")]
+pub use url_rewriter::{UrlRewriter, UrlRewriterBuilder};
 
 /// Provides N-API bindings to expose the `http_rewriter` crate types to Node.js.
 #[cfg(feature = "napi-support")]
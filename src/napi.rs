@@ -1,4 +1,6 @@
-use ::napi::bindgen_prelude::Either6;
+use std::collections::BTreeMap;
+
+use ::napi::bindgen_prelude::{Either9, Either24};
 use ::napi::{Error, Result, Status};
 use napi_derive::napi;
 
@@ -135,7 +137,7 @@ impl MethodCondition {
 
 /// A N-API wrapper for the `ExistenceCondition` type.
 #[napi]
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Debug)]
 pub struct ExistenceCondition(crate::ExistenceCondition);
 
 #[napi]
@@ -167,7 +169,7 @@ impl ExistenceCondition {
 
 /// A N-API wrapper for the `NonExistenceCondition` type.
 #[napi]
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Debug)]
 pub struct NonExistenceCondition(crate::NonExistenceCondition);
 
 #[napi]
@@ -197,312 +199,138 @@ impl NonExistenceCondition {
     }
 }
 
-//
-// Complex Conditions
-//
-
-// Since Condition traits have generic methods, we need to create a type-erased
-// wrapper that can be used with GroupCondition
-#[allow(non_camel_case_types)]
+/// A N-API wrapper for the `HeaderAbsentCondition` type.
+#[napi]
 #[derive(Clone, Debug)]
-enum GroupConditionType {
-    Path_Path(crate::GroupCondition<crate::PathCondition, crate::PathCondition>),
-    Path_Header(crate::GroupCondition<crate::PathCondition, crate::HeaderCondition>),
-    Path_Method(crate::GroupCondition<crate::PathCondition, crate::MethodCondition>),
-    Path_Existence(crate::GroupCondition<crate::PathCondition, crate::ExistenceCondition>),
-    Path_NonExistence(crate::GroupCondition<crate::PathCondition, crate::NonExistenceCondition>),
-    Path_Group(crate::GroupCondition<crate::PathCondition, GroupConditionType>),
-    Header_Path(crate::GroupCondition<crate::HeaderCondition, crate::PathCondition>),
-    Header_Header(crate::GroupCondition<crate::HeaderCondition, crate::HeaderCondition>),
-    Header_Method(crate::GroupCondition<crate::HeaderCondition, crate::MethodCondition>),
-    Header_Existence(crate::GroupCondition<crate::HeaderCondition, crate::ExistenceCondition>),
-    Header_NonExistence(
-        crate::GroupCondition<crate::HeaderCondition, crate::NonExistenceCondition>,
-    ),
-    Header_Group(crate::GroupCondition<crate::HeaderCondition, GroupConditionType>),
-    Method_Path(crate::GroupCondition<crate::MethodCondition, crate::PathCondition>),
-    Method_Header(crate::GroupCondition<crate::MethodCondition, crate::HeaderCondition>),
-    Method_Method(crate::GroupCondition<crate::MethodCondition, crate::MethodCondition>),
-    Method_Existence(crate::GroupCondition<crate::MethodCondition, crate::ExistenceCondition>),
-    Method_NonExistence(
-        crate::GroupCondition<crate::MethodCondition, crate::NonExistenceCondition>,
-    ),
-    Method_Group(crate::GroupCondition<crate::MethodCondition, GroupConditionType>),
-    Existence_Path(crate::GroupCondition<crate::ExistenceCondition, crate::PathCondition>),
-    Existence_Header(crate::GroupCondition<crate::ExistenceCondition, crate::HeaderCondition>),
-    Existence_Method(crate::GroupCondition<crate::ExistenceCondition, crate::MethodCondition>),
-    Existence_Existence(
-        crate::GroupCondition<crate::ExistenceCondition, crate::ExistenceCondition>,
-    ),
-    Existence_NonExistence(
-        crate::GroupCondition<crate::ExistenceCondition, crate::NonExistenceCondition>,
-    ),
-    Existence_Group(crate::GroupCondition<crate::ExistenceCondition, GroupConditionType>),
-    NonExistence_Path(crate::GroupCondition<crate::NonExistenceCondition, crate::PathCondition>),
-    NonExistence_Header(
-        crate::GroupCondition<crate::NonExistenceCondition, crate::HeaderCondition>,
-    ),
-    NonExistence_Method(
-        crate::GroupCondition<crate::NonExistenceCondition, crate::MethodCondition>,
-    ),
-    NonExistence_Existence(
-        crate::GroupCondition<crate::NonExistenceCondition, crate::ExistenceCondition>,
-    ),
-    NonExistence_NonExistence(
-        crate::GroupCondition<crate::NonExistenceCondition, crate::NonExistenceCondition>,
-    ),
-    NonExistence_Group(crate::GroupCondition<crate::NonExistenceCondition, GroupConditionType>),
-    Group_Path(crate::GroupCondition<GroupConditionType, crate::PathCondition>),
-    Group_Header(crate::GroupCondition<GroupConditionType, crate::HeaderCondition>),
-    Group_Method(crate::GroupCondition<GroupConditionType, crate::MethodCondition>),
-    Group_Existence(crate::GroupCondition<GroupConditionType, crate::ExistenceCondition>),
-    Group_NonExistence(crate::GroupCondition<GroupConditionType, crate::NonExistenceCondition>),
-    Group_Group(crate::GroupCondition<GroupConditionType, GroupConditionType>),
+pub struct HeaderAbsentCondition(crate::HeaderAbsentCondition);
+
+#[napi]
+impl HeaderAbsentCondition {
+    /// Create a new header absence condition.
+    ///
+    /// # Examples
+    ///
+    /// ```js
+    /// const condition = new HeaderAbsentCondition('X-Request-Id');
+    /// ```
+    #[napi(constructor)]
+    pub fn new(header: String) -> Result<Self> {
+        Ok(Self(crate::HeaderAbsentCondition::new(header)))
+    }
+
+    /// Check if the given request matches the condition.
+    ///
+    /// # Examples
+    ///
+    /// ```js
+    /// const matches = condition.matches(request);
+    /// ```
+    #[napi]
+    pub fn matches(&self, request: Request) -> Result<bool> {
+        Ok(self.0.matches(&request))
+    }
 }
 
-impl crate::Condition for GroupConditionType {
-    fn matches<B>(&self, request: &http::Request<B>) -> bool {
-        match self {
-            GroupConditionType::Path_Path(c) => c.matches(request),
-            GroupConditionType::Path_Header(c) => c.matches(request),
-            GroupConditionType::Path_Method(c) => c.matches(request),
-            GroupConditionType::Path_Existence(c) => c.matches(request),
-            GroupConditionType::Path_NonExistence(c) => c.matches(request),
-            GroupConditionType::Path_Group(c) => c.matches(request),
-            GroupConditionType::Header_Path(c) => c.matches(request),
-            GroupConditionType::Header_Header(c) => c.matches(request),
-            GroupConditionType::Header_Method(c) => c.matches(request),
-            GroupConditionType::Header_Existence(c) => c.matches(request),
-            GroupConditionType::Header_NonExistence(c) => c.matches(request),
-            GroupConditionType::Header_Group(c) => c.matches(request),
-            GroupConditionType::Method_Path(c) => c.matches(request),
-            GroupConditionType::Method_Header(c) => c.matches(request),
-            GroupConditionType::Method_Method(c) => c.matches(request),
-            GroupConditionType::Method_Existence(c) => c.matches(request),
-            GroupConditionType::Method_NonExistence(c) => c.matches(request),
-            GroupConditionType::Method_Group(c) => c.matches(request),
-            GroupConditionType::Existence_Path(c) => c.matches(request),
-            GroupConditionType::Existence_Header(c) => c.matches(request),
-            GroupConditionType::Existence_Method(c) => c.matches(request),
-            GroupConditionType::Existence_Existence(c) => c.matches(request),
-            GroupConditionType::Existence_NonExistence(c) => c.matches(request),
-            GroupConditionType::Existence_Group(c) => c.matches(request),
-            GroupConditionType::NonExistence_Path(c) => c.matches(request),
-            GroupConditionType::NonExistence_Header(c) => c.matches(request),
-            GroupConditionType::NonExistence_Method(c) => c.matches(request),
-            GroupConditionType::NonExistence_Existence(c) => c.matches(request),
-            GroupConditionType::NonExistence_NonExistence(c) => c.matches(request),
-            GroupConditionType::NonExistence_Group(c) => c.matches(request),
-            GroupConditionType::Group_Path(c) => c.matches(request),
-            GroupConditionType::Group_Header(c) => c.matches(request),
-            GroupConditionType::Group_Method(c) => c.matches(request),
-            GroupConditionType::Group_Existence(c) => c.matches(request),
-            GroupConditionType::Group_NonExistence(c) => c.matches(request),
-            GroupConditionType::Group_Group(c) => c.matches(request),
-        }
+/// A N-API wrapper for the `AuthSchemeCondition` type.
+#[napi]
+#[derive(Clone, Debug)]
+pub struct AuthSchemeCondition(crate::AuthSchemeCondition);
+
+#[napi]
+impl AuthSchemeCondition {
+    /// Create a new authorization scheme condition.
+    ///
+    /// # Examples
+    ///
+    /// ```js
+    /// const condition = new AuthSchemeCondition('Bearer');
+    /// ```
+    #[napi(constructor)]
+    pub fn new(scheme: String) -> Result<Self> {
+        Ok(Self(crate::AuthSchemeCondition::new(scheme)))
+    }
+
+    /// Check if the given request matches the condition.
+    ///
+    /// # Examples
+    ///
+    /// ```js
+    /// const matches = condition.matches(request);
+    /// ```
+    #[napi]
+    pub fn matches(&self, request: Request) -> Result<bool> {
+        Ok(self.0.matches(&request))
     }
 }
 
-// Implement `From` for each combination of GroupCondition
-macro_rules! impl_from_group_condition {
-    ($a:ty, $b:ty, $name:ident) => {
-        impl From<crate::GroupCondition<$a, $b>> for GroupConditionType {
-            fn from(condition: crate::GroupCondition<$a, $b>) -> Self {
-                GroupConditionType::$name(condition)
-            }
-        }
+/// A N-API wrapper for the `LanguageCondition` type.
+#[napi]
+#[derive(Clone, Debug)]
+pub struct LanguageCondition(crate::LanguageCondition);
 
-        impl TryFrom<GroupConditionType> for crate::GroupCondition<$a, $b> {
-            type Error = Error;
-
-            fn try_from(value: GroupConditionType) -> Result<Self> {
-                match value {
-                    GroupConditionType::$name(c) => Ok(c),
-                    _ => Err(Error::new(
-                        Status::InvalidArg,
-                        format!(
-                            "Expected GroupConditionType::{}, found {:?}",
-                            stringify!($name),
-                            value
-                        ),
-                    )),
-                }
-            }
+#[napi]
+impl LanguageCondition {
+    /// Create a new language condition.
+    ///
+    /// Matches the most preferred language, or any acceptable language when
+    /// `any` is true.
+    ///
+    /// # Examples
+    ///
+    /// ```js
+    /// const condition = new LanguageCondition('^fr', true);
+    /// ```
+    #[napi(constructor)]
+    pub fn new(pattern: String, any: Option<bool>) -> Result<Self> {
+        let condition = if any.unwrap_or_default() {
+            crate::LanguageCondition::new_any(pattern)
+        } else {
+            crate::LanguageCondition::new(pattern)
         }
+        .map_err(|e| Error::new(Status::GenericFailure, e.to_string()))?;
 
-        impl From<crate::GroupCondition<$a, $b>> for Condition {
-            fn from(condition: crate::GroupCondition<$a, $b>) -> Self {
-                Condition(Either6::F(condition.into()))
-            }
-        }
+        Ok(Self(condition))
+    }
 
-        impl TryFrom<Condition> for crate::GroupCondition<$a, $b> {
-            type Error = Error;
-
-            fn try_from(value: Condition) -> Result<Self> {
-                match value.0 {
-                    Either6::F(c) => c.try_into(),
-                    _ => Err(Error::new(
-                        Status::InvalidArg,
-                        format!(
-                            "Expected crate::GroupCondition<{}, {}>, found {:?}",
-                            stringify!($a),
-                            stringify!($b),
-                            value
-                        ),
-                    )),
-                }
-            }
-        }
-    };
+    /// Check if the given request matches the condition.
+    ///
+    /// # Examples
+    ///
+    /// ```js
+    /// const matches = condition.matches(request);
+    /// ```
+    #[napi]
+    pub fn matches(&self, request: Request) -> Result<bool> {
+        Ok(self.0.matches(&request))
+    }
 }
 
-impl_from_group_condition!(crate::PathCondition, crate::PathCondition, Path_Path);
-impl_from_group_condition!(crate::HeaderCondition, crate::PathCondition, Header_Path);
-impl_from_group_condition!(crate::MethodCondition, crate::PathCondition, Method_Path);
-impl_from_group_condition!(
-    crate::ExistenceCondition,
-    crate::PathCondition,
-    Existence_Path
-);
-impl_from_group_condition!(
-    crate::NonExistenceCondition,
-    crate::PathCondition,
-    NonExistence_Path
-);
-impl_from_group_condition!(GroupConditionType, crate::PathCondition, Group_Path);
-
-impl_from_group_condition!(crate::PathCondition, crate::HeaderCondition, Path_Header);
-impl_from_group_condition!(
-    crate::HeaderCondition,
-    crate::HeaderCondition,
-    Header_Header
-);
-impl_from_group_condition!(
-    crate::MethodCondition,
-    crate::HeaderCondition,
-    Method_Header
-);
-impl_from_group_condition!(
-    crate::ExistenceCondition,
-    crate::HeaderCondition,
-    Existence_Header
-);
-impl_from_group_condition!(
-    crate::NonExistenceCondition,
-    crate::HeaderCondition,
-    NonExistence_Header
-);
-impl_from_group_condition!(GroupConditionType, crate::HeaderCondition, Group_Header);
-
-impl_from_group_condition!(crate::PathCondition, crate::MethodCondition, Path_Method);
-impl_from_group_condition!(
-    crate::HeaderCondition,
-    crate::MethodCondition,
-    Header_Method
-);
-impl_from_group_condition!(
-    crate::MethodCondition,
-    crate::MethodCondition,
-    Method_Method
-);
-impl_from_group_condition!(
-    crate::ExistenceCondition,
-    crate::MethodCondition,
-    Existence_Method
-);
-impl_from_group_condition!(
-    crate::NonExistenceCondition,
-    crate::MethodCondition,
-    NonExistence_Method
-);
-impl_from_group_condition!(GroupConditionType, crate::MethodCondition, Group_Method);
-
-impl_from_group_condition!(
-    crate::PathCondition,
-    crate::ExistenceCondition,
-    Path_Existence
-);
-impl_from_group_condition!(
-    crate::HeaderCondition,
-    crate::ExistenceCondition,
-    Header_Existence
-);
-impl_from_group_condition!(
-    crate::MethodCondition,
-    crate::ExistenceCondition,
-    Method_Existence
-);
-impl_from_group_condition!(
-    crate::ExistenceCondition,
-    crate::ExistenceCondition,
-    Existence_Existence
-);
-impl_from_group_condition!(
-    crate::NonExistenceCondition,
-    crate::ExistenceCondition,
-    NonExistence_Existence
-);
-impl_from_group_condition!(
-    GroupConditionType,
-    crate::ExistenceCondition,
-    Group_Existence
-);
-
-impl_from_group_condition!(
-    crate::PathCondition,
-    crate::NonExistenceCondition,
-    Path_NonExistence
-);
-impl_from_group_condition!(
-    crate::HeaderCondition,
-    crate::NonExistenceCondition,
-    Header_NonExistence
-);
-impl_from_group_condition!(
-    crate::MethodCondition,
-    crate::NonExistenceCondition,
-    Method_NonExistence
-);
-impl_from_group_condition!(
-    crate::ExistenceCondition,
-    crate::NonExistenceCondition,
-    Existence_NonExistence
-);
-impl_from_group_condition!(
-    crate::NonExistenceCondition,
-    crate::NonExistenceCondition,
-    NonExistence_NonExistence
-);
-impl_from_group_condition!(
-    GroupConditionType,
-    crate::NonExistenceCondition,
-    Group_NonExistence
-);
-
-impl_from_group_condition!(crate::PathCondition, GroupConditionType, Path_Group);
-impl_from_group_condition!(crate::HeaderCondition, GroupConditionType, Header_Group);
-impl_from_group_condition!(crate::MethodCondition, GroupConditionType, Method_Group);
-impl_from_group_condition!(
-    crate::ExistenceCondition,
-    GroupConditionType,
-    Existence_Group
-);
-impl_from_group_condition!(
-    crate::NonExistenceCondition,
-    GroupConditionType,
-    NonExistence_Group
-);
-impl_from_group_condition!(GroupConditionType, GroupConditionType, Group_Group);
-
-/// A N-API wrapper for the `GroupCondition` type.
+/// A N-API wrapper for the `ForwardedForCountCondition` type.
 #[napi]
 #[derive(Clone, Debug)]
-pub struct GroupCondition(GroupConditionType);
+pub struct ForwardedForCountCondition(crate::ForwardedForCountCondition);
 
 #[napi]
-impl GroupCondition {
-    /// Check if the given request matches the group condition.
+impl ForwardedForCountCondition {
+    /// Create a new forwarded for count condition.
+    ///
+    /// The comparison is one of `eq`, `ne`, `lt`, `le`, `gt` or `ge`.
+    ///
+    /// # Examples
+    ///
+    /// ```js
+    /// const condition = new ForwardedForCountCondition('gt', 1);
+    /// ```
+    #[napi(constructor)]
+    pub fn new(comparison: String, count: u32) -> Result<Self> {
+        let comparison = config::parse_comparison(&comparison)?;
+        Ok(Self(crate::ForwardedForCountCondition::new(
+            comparison,
+            count as usize,
+        )))
+    }
+
+    /// Check if the given request matches the condition.
     ///
     /// # Examples
     ///
@@ -515,265 +343,655 @@ impl GroupCondition {
     }
 }
 
-// Type alias for any condition which can be passed to `and`/`or` methods in JS
-type AnyCondition<'a> = Either6<
-    &'a PathCondition,
-    &'a HeaderCondition,
-    &'a MethodCondition,
-    &'a ExistenceCondition,
-    &'a NonExistenceCondition,
-    &'a GroupCondition,
->;
+/// A N-API wrapper for the `PathGlobCondition` type.
+#[napi]
+#[derive(Clone, Debug)]
+pub struct PathGlobCondition(crate::PathGlobCondition);
 
-// Type alias for any condition which can be passed to `and`/`or` methods in Rust
-type AnyConditionOwned = Either6<
-    crate::PathCondition,
-    crate::HeaderCondition,
-    crate::MethodCondition,
-    crate::ExistenceCondition,
-    crate::NonExistenceCondition,
-    GroupConditionType,
->;
+#[napi]
+impl PathGlobCondition {
+    /// Create a new path glob condition.
+    ///
+    /// `*` and `?` match within a single path segment, while `**` also
+    /// matches across `/`.
+    ///
+    /// # Examples
+    ///
+    /// ```js
+    /// const condition = new PathGlobCondition('/assets/**/*.css');
+    /// ```
+    #[napi(constructor)]
+    pub fn new(glob: String) -> Result<Self> {
+        let condition = crate::PathGlobCondition::new(glob)
+            .map_err(|e| Error::new(Status::GenericFailure, e.to_string()))?;
 
-macro_rules! impl_from_condition {
-    ($type:ty, $name:ident) => {
-        impl From<$type> for Condition {
-            fn from(condition: $type) -> Self {
-                Condition(Either6::$name(condition))
-            }
-        }
+        Ok(Self(condition))
+    }
 
-        impl TryFrom<Condition> for $type {
-            type Error = Error;
-
-            fn try_from(value: Condition) -> Result<Self> {
-                match value.0 {
-                    Either6::$name(c) => Ok(c),
-                    _ => Err(Error::new(
-                        Status::InvalidArg,
-                        format!("Expected Either6::{}, found {:?}", stringify!($name), value),
-                    )),
-                }
-            }
-        }
-    };
+    /// Check if the given request matches the condition.
+    ///
+    /// # Examples
+    ///
+    /// ```js
+    /// const matches = condition.matches(request);
+    /// ```
+    #[napi]
+    pub fn matches(&self, request: Request) -> Result<bool> {
+        Ok(self.0.matches(&request))
+    }
 }
 
-impl_from_condition!(crate::PathCondition, A);
-impl_from_condition!(crate::HeaderCondition, B);
-impl_from_condition!(crate::MethodCondition, C);
-impl_from_condition!(crate::ExistenceCondition, D);
-impl_from_condition!(crate::NonExistenceCondition, E);
-impl_from_condition!(GroupConditionType, F);
+/// A N-API wrapper for the `ForwardedCondition` type.
+#[napi]
+#[derive(Clone, Debug)]
+pub struct ForwardedCondition(crate::ForwardedCondition);
 
-// Implement combinators for all condition types
-//
-// Provides:
-// - `and` method to combine conditions with logical AND
-// - `or` method to combine conditions with logical OR
-macro_rules! impl_condition_combinators {
-    ($type:ty) => {
-        #[napi]
-        impl $type {
-            /// Create a new condition that matches when both conditions match
-            ///
-            /// # Examples
-            ///
-            /// ```js
-            /// const combined = condition1.and(condition2);
-            /// ```
-            #[napi]
-            pub fn and(&self, other: AnyCondition) -> Result<GroupCondition> {
-                let this = self.0.clone();
-                Ok(GroupCondition(match other {
-                    Either6::A(path) => this.and(path.0.clone()).into(),
-                    Either6::B(header) => this.and(header.0.clone()).into(),
-                    Either6::C(method) => this.and(method.0.clone()).into(),
-                    Either6::D(existence) => this.and(existence.0).into(),
-                    Either6::E(nonexistence) => this.and(nonexistence.0).into(),
-                    Either6::F(group) => this.and(group.0.clone()).into(),
-                }))
-            }
+#[napi]
+impl ForwardedCondition {
+    /// Create a new forwarded condition.
+    ///
+    /// The parameter is one of `proto`, `host` or `for`.
+    ///
+    /// # Examples
+    ///
+    /// ```js
+    /// const condition = new ForwardedCondition('proto', '^https$');
+    /// ```
+    #[napi(constructor)]
+    pub fn new(param: String, pattern: String) -> Result<Self> {
+        let param = config::parse_forwarded_param(&param)?;
+        let condition = crate::ForwardedCondition::new(param, pattern)
+            .map_err(|e| Error::new(Status::GenericFailure, e.to_string()))?;
 
-            /// Create a new condition that matches when either condition matches
-            ///
-            /// # Examples
-            ///
-            /// ```js
-            /// const combined = condition1.or(condition2);
-            /// ```
-            #[napi]
-            pub fn or(&self, other: AnyCondition) -> Result<GroupCondition> {
-                let this = self.0.clone();
-                Ok(GroupCondition(match other {
-                    Either6::A(path) => this.or(path.0.clone()).into(),
-                    Either6::B(header) => this.or(header.0.clone()).into(),
-                    Either6::C(method) => this.or(method.0.clone()).into(),
-                    Either6::D(existence) => this.or(existence.0).into(),
-                    Either6::E(nonexistence) => this.or(nonexistence.0).into(),
-                    Either6::F(group) => this.or(group.0.clone()).into(),
-                }))
-            }
-        }
-    };
-}
+        Ok(Self(condition))
+    }
 
-impl_condition_combinators!(PathCondition);
-impl_condition_combinators!(HeaderCondition);
-impl_condition_combinators!(MethodCondition);
-impl_condition_combinators!(ExistenceCondition);
-impl_condition_combinators!(NonExistenceCondition);
-impl_condition_combinators!(GroupCondition);
+    /// Check if the given request matches the condition.
+    ///
+    /// # Examples
+    ///
+    /// ```js
+    /// const matches = condition.matches(request);
+    /// ```
+    #[napi]
+    pub fn matches(&self, request: Request) -> Result<bool> {
+        Ok(self.0.matches(&request))
+    }
+}
 
-/// Allows constructing rewriter and condition configurations from JSON.
+/// A N-API wrapper for the `HeaderNameExistsCondition` type.
+#[napi]
 #[derive(Clone, Debug)]
-pub struct Condition(AnyConditionOwned);
-
-impl crate::Condition for Condition {
-    fn matches<B>(&self, request: &http::Request<B>) -> bool {
-        match &self.0 {
-            Either6::A(c) => c.matches(request),
-            Either6::B(c) => c.matches(request),
-            Either6::C(c) => c.matches(request),
-            Either6::D(c) => c.matches(request),
-            Either6::E(c) => c.matches(request),
-            Either6::F(c) => c.matches(request),
-        }
+pub struct HeaderNameExistsCondition(crate::HeaderNameExistsCondition);
+
+#[napi]
+impl HeaderNameExistsCondition {
+    /// Create a new header name condition.
+    ///
+    /// Header names are lowercase, so the pattern should be too.
+    ///
+    /// # Examples
+    ///
+    /// ```js
+    /// const condition = new HeaderNameExistsCondition('^x-debug-');
+    /// ```
+    #[napi(constructor)]
+    pub fn new(pattern: String) -> Result<Self> {
+        let condition = crate::HeaderNameExistsCondition::new(pattern)
+            .map_err(|e| Error::new(Status::GenericFailure, e.to_string()))?;
+
+        Ok(Self(condition))
+    }
+
+    /// Check if the given request matches the condition.
+    ///
+    /// # Examples
+    ///
+    /// ```js
+    /// const matches = condition.matches(request);
+    /// ```
+    #[napi]
+    pub fn matches(&self, request: Request) -> Result<bool> {
+        Ok(self.0.matches(&request))
     }
 }
 
-impl TryFrom<ConditionConfig> for Condition {
-    type Error = Error;
+/// A N-API wrapper for the `RequiredHeadersCondition` type.
+#[napi]
+#[derive(Clone, Debug)]
+pub struct RequiredHeadersCondition(crate::RequiredHeadersCondition);
 
-    fn try_from(config: ConditionConfig) -> Result<Self> {
-        match config.condition {
-            ConditionType::Path => {
-                let path_condition = crate::PathCondition::try_from(config)
-                    .map_err(|e| Error::new(Status::GenericFailure, e.to_string()))?;
-                Ok(path_condition.into())
-            }
-            ConditionType::Header => {
-                let header_condition = crate::HeaderCondition::try_from(config)
-                    .map_err(|e| Error::new(Status::GenericFailure, e.to_string()))?;
-                Ok(header_condition.into())
-            }
-            ConditionType::Method => {
-                let method_condition = crate::MethodCondition::try_from(config)
-                    .map_err(|e| Error::new(Status::GenericFailure, e.to_string()))?;
-                Ok(method_condition.into())
-            }
-            ConditionType::Exists => {
-                let existence_condition = crate::ExistenceCondition::new();
-                Ok(existence_condition.into())
-            }
-            ConditionType::NotExists => {
-                let nonexistence_condition = crate::NonExistenceCondition::new();
-                Ok(nonexistence_condition.into())
-            }
-        }
+#[napi]
+impl RequiredHeadersCondition {
+    /// Create a new condition matching when any of the headers is missing.
+    ///
+    /// # Examples
+    ///
+    /// ```js
+    /// const condition = new RequiredHeadersCondition(['Authorization', 'X-Tenant-Id']);
+    /// ```
+    #[napi(constructor)]
+    pub fn new(headers: Vec<String>) -> Result<Self> {
+        Ok(Self(crate::RequiredHeadersCondition::new(&headers)))
     }
-}
 
-fn and<A, B>(a: A, b: B) -> Condition
-where
-    A: Into<Condition>,
-    B: Into<Condition>,
-{
-    match (a.into().0, b.into().0) {
-        (Either6::A(a), Either6::A(b)) => a.and(b).into(),
-        (Either6::A(a), Either6::B(b)) => a.and(b).into(),
-        (Either6::A(a), Either6::C(b)) => a.and(b).into(),
-        (Either6::A(a), Either6::D(b)) => a.and(b).into(),
-        (Either6::A(a), Either6::E(b)) => a.and(b).into(),
-        (Either6::A(a), Either6::F(b)) => a.and(b).into(),
-
-        (Either6::B(a), Either6::A(b)) => a.and(b).into(),
-        (Either6::B(a), Either6::B(b)) => a.and(b).into(),
-        (Either6::B(a), Either6::C(b)) => a.and(b).into(),
-        (Either6::B(a), Either6::D(b)) => a.and(b).into(),
-        (Either6::B(a), Either6::E(b)) => a.and(b).into(),
-        (Either6::B(a), Either6::F(b)) => a.and(b).into(),
-
-        (Either6::C(a), Either6::A(b)) => a.and(b).into(),
-        (Either6::C(a), Either6::B(b)) => a.and(b).into(),
-        (Either6::C(a), Either6::C(b)) => a.and(b).into(),
-        (Either6::C(a), Either6::D(b)) => a.and(b).into(),
-        (Either6::C(a), Either6::E(b)) => a.and(b).into(),
-        (Either6::C(a), Either6::F(b)) => a.and(b).into(),
-
-        (Either6::D(a), Either6::A(b)) => a.and(b).into(),
-        (Either6::D(a), Either6::B(b)) => a.and(b).into(),
-        (Either6::D(a), Either6::C(b)) => a.and(b).into(),
-        (Either6::D(a), Either6::D(b)) => a.and(b).into(),
-        (Either6::D(a), Either6::E(b)) => a.and(b).into(),
-        (Either6::D(a), Either6::F(b)) => a.and(b).into(),
-
-        (Either6::E(a), Either6::A(b)) => a.and(b).into(),
-        (Either6::E(a), Either6::B(b)) => a.and(b).into(),
-        (Either6::E(a), Either6::C(b)) => a.and(b).into(),
-        (Either6::E(a), Either6::D(b)) => a.and(b).into(),
-        (Either6::E(a), Either6::E(b)) => a.and(b).into(),
-        (Either6::E(a), Either6::F(b)) => a.and(b).into(),
-
-        (Either6::F(a), Either6::A(b)) => a.and(b).into(),
-        (Either6::F(a), Either6::B(b)) => a.and(b).into(),
-        (Either6::F(a), Either6::C(b)) => a.and(b).into(),
-        (Either6::F(a), Either6::D(b)) => a.and(b).into(),
-        (Either6::F(a), Either6::E(b)) => a.and(b).into(),
-        (Either6::F(a), Either6::F(b)) => a.and(b).into(),
+    /// Check if the given request matches the condition.
+    ///
+    /// # Examples
+    ///
+    /// ```js
+    /// const matches = condition.matches(request);
+    /// ```
+    #[napi]
+    pub fn matches(&self, request: Request) -> Result<bool> {
+        Ok(self.0.matches(&request))
     }
 }
 
-fn or<A, B>(a: A, b: B) -> Condition
-where
-    A: Into<Condition>,
-    B: Into<Condition>,
-{
-    match (a.into().0, b.into().0) {
-        (Either6::A(a), Either6::A(b)) => a.or(b).into(),
-        (Either6::A(a), Either6::B(b)) => a.or(b).into(),
-        (Either6::A(a), Either6::C(b)) => a.or(b).into(),
-        (Either6::A(a), Either6::D(b)) => a.or(b).into(),
-        (Either6::A(a), Either6::E(b)) => a.or(b).into(),
-        (Either6::A(a), Either6::F(b)) => a.or(b).into(),
-
-        (Either6::B(a), Either6::A(b)) => a.or(b).into(),
-        (Either6::B(a), Either6::B(b)) => a.or(b).into(),
-        (Either6::B(a), Either6::C(b)) => a.or(b).into(),
-        (Either6::B(a), Either6::D(b)) => a.or(b).into(),
-        (Either6::B(a), Either6::E(b)) => a.or(b).into(),
-        (Either6::B(a), Either6::F(b)) => a.or(b).into(),
-
-        (Either6::C(a), Either6::A(b)) => a.or(b).into(),
-        (Either6::C(a), Either6::B(b)) => a.or(b).into(),
-        (Either6::C(a), Either6::C(b)) => a.or(b).into(),
-        (Either6::C(a), Either6::D(b)) => a.or(b).into(),
-        (Either6::C(a), Either6::E(b)) => a.or(b).into(),
-        (Either6::C(a), Either6::F(b)) => a.or(b).into(),
-
-        (Either6::D(a), Either6::A(b)) => a.or(b).into(),
-        (Either6::D(a), Either6::B(b)) => a.or(b).into(),
-        (Either6::D(a), Either6::C(b)) => a.or(b).into(),
-        (Either6::D(a), Either6::D(b)) => a.or(b).into(),
-        (Either6::D(a), Either6::E(b)) => a.or(b).into(),
-        (Either6::D(a), Either6::F(b)) => a.or(b).into(),
-
-        (Either6::E(a), Either6::A(b)) => a.or(b).into(),
-        (Either6::E(a), Either6::B(b)) => a.or(b).into(),
-        (Either6::E(a), Either6::C(b)) => a.or(b).into(),
-        (Either6::E(a), Either6::D(b)) => a.or(b).into(),
-        (Either6::E(a), Either6::E(b)) => a.or(b).into(),
-        (Either6::E(a), Either6::F(b)) => a.or(b).into(),
-
-        (Either6::F(a), Either6::A(b)) => a.or(b).into(),
-        (Either6::F(a), Either6::B(b)) => a.or(b).into(),
-        (Either6::F(a), Either6::C(b)) => a.or(b).into(),
-        (Either6::F(a), Either6::D(b)) => a.or(b).into(),
-        (Either6::F(a), Either6::E(b)) => a.or(b).into(),
-        (Either6::F(a), Either6::F(b)) => a.or(b).into(),
-    }
+/// A N-API wrapper for the `HeaderCompareCondition` type.
+#[napi]
+#[derive(Clone, Debug)]
+pub struct HeaderCompareCondition(crate::HeaderCompareCondition);
+
+#[napi]
+impl HeaderCompareCondition {
+    /// Create a new condition matching when two headers have the same value.
+    ///
+    /// Values are compared ignoring ASCII case when `caseInsensitive` is true.
+    ///
+    /// # Examples
+    ///
+    /// ```js
+    /// const condition = new HeaderCompareCondition('Host', 'X-Forwarded-Host', true);
+    /// ```
+    #[napi(constructor)]
+    pub fn new(first: String, second: String, case_insensitive: Option<bool>) -> Result<Self> {
+        Ok(Self(
+            crate::HeaderCompareCondition::new(first, second)
+                .with_case_insensitive(case_insensitive.unwrap_or_default()),
+        ))
+    }
+
+    /// Check if the given request matches the condition.
+    ///
+    /// # Examples
+    ///
+    /// ```js
+    /// const matches = condition.matches(request);
+    /// ```
+    #[napi]
+    pub fn matches(&self, request: Request) -> Result<bool> {
+        Ok(self.0.matches(&request))
+    }
+}
+
+/// A N-API wrapper for the `TrailingSlashCondition` type.
+#[napi]
+#[derive(Clone, Debug)]
+pub struct TrailingSlashCondition(crate::TrailingSlashCondition);
+
+#[napi]
+impl TrailingSlashCondition {
+    /// Create a new condition matching on whether the path ends with a slash.
+    ///
+    /// The root path `/` counts as having a trailing slash.
+    ///
+    /// # Examples
+    ///
+    /// ```js
+    /// const condition = new TrailingSlashCondition(false);
+    /// ```
+    #[napi(constructor)]
+    pub fn new(present: bool) -> Result<Self> {
+        Ok(Self(crate::TrailingSlashCondition::new(present)))
+    }
+
+    /// Check if the given request matches the condition.
+    ///
+    /// # Examples
+    ///
+    /// ```js
+    /// const matches = condition.matches(request);
+    /// ```
+    #[napi]
+    pub fn matches(&self, request: Request) -> Result<bool> {
+        Ok(self.0.matches(&request))
+    }
+}
+
+/// A N-API wrapper for the `TargetLengthCondition` type.
+#[napi]
+#[derive(Clone, Debug)]
+pub struct TargetLengthCondition(crate::TargetLengthCondition);
+
+#[napi]
+impl TargetLengthCondition {
+    /// Create a new condition on the byte length of the path and query.
+    ///
+    /// The comparison is one of `eq`, `ne`, `lt`, `le`, `gt` or `ge`.
+    ///
+    /// # Examples
+    ///
+    /// ```js
+    /// const condition = new TargetLengthCondition('gt', 8192);
+    /// ```
+    #[napi(constructor)]
+    pub fn new(comparison: String, length: u32) -> Result<Self> {
+        let comparison = config::parse_comparison(&comparison)?;
+        Ok(Self(crate::TargetLengthCondition::new(
+            comparison,
+            length as usize,
+        )))
+    }
+
+    /// Check if the given request matches the condition.
+    ///
+    /// # Examples
+    ///
+    /// ```js
+    /// const matches = condition.matches(request);
+    /// ```
+    #[napi]
+    pub fn matches(&self, request: Request) -> Result<bool> {
+        Ok(self.0.matches(&request))
+    }
+}
+
+/// A N-API wrapper for the `QueryFlagCondition` type.
+#[napi]
+#[derive(Clone, Debug)]
+pub struct QueryFlagCondition(crate::QueryFlagCondition);
+
+#[napi]
+impl QueryFlagCondition {
+    /// Create a new condition matching when a query parameter is set as a flag.
+    ///
+    /// The parameter must be present with an empty value, or with one of `1`,
+    /// `true`, `yes` or `on`, ignoring case.
+    ///
+    /// # Examples
+    ///
+    /// ```js
+    /// const condition = new QueryFlagCondition('beta');
+    /// ```
+    #[napi(constructor)]
+    pub fn new(name: String) -> Result<Self> {
+        Ok(Self(crate::QueryFlagCondition::new(name)))
+    }
+
+    /// Check if the given request matches the condition.
+    ///
+    /// # Examples
+    ///
+    /// ```js
+    /// const matches = condition.matches(request);
+    /// ```
+    #[napi]
+    pub fn matches(&self, request: Request) -> Result<bool> {
+        Ok(self.0.matches(&request))
+    }
+}
+
+/// A N-API wrapper for the `CorsPreflightCondition` type.
+#[napi]
+#[derive(Clone, Debug)]
+pub struct CorsPreflightCondition(crate::CorsPreflightCondition);
+
+#[napi]
+impl CorsPreflightCondition {
+    /// Create a new condition matching CORS preflight requests.
+    ///
+    /// A preflight is an `OPTIONS` request carrying the
+    /// `Access-Control-Request-Method` header.
+    ///
+    /// # Examples
+    ///
+    /// ```js
+    /// const condition = new CorsPreflightCondition();
+    /// ```
+    #[napi(constructor)]
+    pub fn new() -> Result<Self> {
+        Ok(Self(crate::CorsPreflightCondition::new()))
+    }
+
+    /// Check if the given request matches the condition.
+    ///
+    /// # Examples
+    ///
+    /// ```js
+    /// const matches = condition.matches(request);
+    /// ```
+    #[napi]
+    pub fn matches(&self, request: Request) -> Result<bool> {
+        Ok(self.0.matches(&request))
+    }
+}
+
+/// A N-API wrapper for the `HostAllowlistCondition` type.
+#[napi]
+#[derive(Clone, Debug)]
+pub struct HostAllowlistCondition(crate::HostAllowlistCondition);
+
+#[napi]
+impl HostAllowlistCondition {
+    /// Create a new condition matching requests for hosts which should be rejected.
+    ///
+    /// With mode `allow` it matches every host outside the list, and with
+    /// mode `deny` the hosts in it. Entries such as `*.example.com` match
+    /// any subdomain.
+    ///
+    /// # Examples
+    ///
+    /// ```js
+    /// const condition = new HostAllowlistCondition(['example.com', '*.example.com'], 'allow');
+    /// ```
+    #[napi(constructor)]
+    pub fn new(hosts: Vec<String>, mode: String) -> Result<Self> {
+        let mode = crate::config::parse_host_list_mode(&mode)?;
+        let condition = crate::HostAllowlistCondition::new(&hosts, mode)
+            .map_err(|e| Error::new(Status::GenericFailure, e.to_string()))?;
+
+        Ok(Self(condition))
+    }
+
+    /// Check if the given request matches the condition.
+    ///
+    /// # Examples
+    ///
+    /// ```js
+    /// const matches = condition.matches(request);
+    /// ```
+    #[napi]
+    pub fn matches(&self, request: Request) -> Result<bool> {
+        Ok(self.0.matches(&request))
+    }
+}
+
+/// A N-API wrapper for the `DocumentRootCondition` type.
+#[napi]
+#[derive(Clone, Debug)]
+pub struct DocumentRootCondition(crate::DocumentRootCondition);
+
+#[napi]
+impl DocumentRootCondition {
+    /// Create a new document root condition with the given pattern.
+    ///
+    /// # Examples
+    ///
+    /// ```js
+    /// const condition = new DocumentRootCondition('^/srv/tenants/acme/');
+    /// ```
+    #[napi(constructor)]
+    pub fn new(pattern: String) -> Result<Self> {
+        let condition = crate::DocumentRootCondition::new(pattern)
+            .map_err(|e| Error::new(Status::GenericFailure, e.to_string()))?;
+
+        Ok(Self(condition))
+    }
+
+    /// Check if the given request matches the condition.
+    ///
+    /// # Examples
+    ///
+    /// ```js
+    /// const matches = condition.matches(request);
+    /// ```
+    #[napi]
+    pub fn matches(&self, request: Request) -> Result<bool> {
+        Ok(self.0.matches(&request))
+    }
+}
+
+/// A N-API wrapper for the `DuplicateHeaderCondition` type.
+#[napi]
+#[derive(Clone, Debug)]
+pub struct DuplicateHeaderCondition(crate::DuplicateHeaderCondition);
+
+#[napi]
+impl DuplicateHeaderCondition {
+    /// Create a new condition matching a header which appears more than once.
+    ///
+    /// # Examples
+    ///
+    /// ```js
+    /// const condition = new DuplicateHeaderCondition('Content-Length');
+    /// ```
+    #[napi(constructor)]
+    pub fn new(header: String) -> Result<Self> {
+        Ok(Self(crate::DuplicateHeaderCondition::new(header)))
+    }
+
+    /// Check if the given request matches the condition.
+    ///
+    /// # Examples
+    ///
+    /// ```js
+    /// const matches = condition.matches(request);
+    /// ```
+    #[napi]
+    pub fn matches(&self, request: Request) -> Result<bool> {
+        Ok(self.0.matches(&request))
+    }
+}
+
+/// A N-API wrapper for the `TraversalCondition` type.
+#[napi]
+#[derive(Clone, Debug)]
+pub struct TraversalCondition(crate::TraversalCondition);
+
+#[napi]
+impl TraversalCondition {
+    /// Create a new condition matching request targets with traversal sequences.
+    ///
+    /// Both literal `..` segments and their percent-encoded forms, such as
+    /// `%2e%2e%2f`, are detected.
+    ///
+    /// # Examples
+    ///
+    /// ```js
+    /// const condition = new TraversalCondition();
+    /// ```
+    #[napi(constructor)]
+    pub fn new() -> Result<Self> {
+        Ok(Self(crate::TraversalCondition::new()))
+    }
+
+    /// Check if the given request matches the condition.
+    ///
+    /// # Examples
+    ///
+    /// ```js
+    /// const matches = condition.matches(request);
+    /// ```
+    #[napi]
+    pub fn matches(&self, request: Request) -> Result<bool> {
+        Ok(self.0.matches(&request))
+    }
+}
+
+/// A N-API wrapper for the `AcceptEncodingCondition` type.
+#[napi]
+#[derive(Clone, Debug)]
+pub struct AcceptEncodingCondition(crate::AcceptEncodingCondition);
+
+#[napi]
+impl AcceptEncodingCondition {
+    /// Create a new condition matching requests which accept a content coding.
+    ///
+    /// The codec is acceptable when `Accept-Encoding` lists it, or `*`, with
+    /// a nonzero quality. An explicit `;q=0` excludes it.
+    ///
+    /// # Examples
+    ///
+    /// ```js
+    /// const condition = new AcceptEncodingCondition('br');
+    /// ```
+    #[napi(constructor)]
+    pub fn new(codec: String) -> Result<Self> {
+        Ok(Self(crate::AcceptEncodingCondition::new(codec)))
+    }
+
+    /// Check if the given request matches the condition.
+    ///
+    /// # Examples
+    ///
+    /// ```js
+    /// const matches = condition.matches(request);
+    /// ```
+    #[napi]
+    pub fn matches(&self, request: Request) -> Result<bool> {
+        Ok(self.0.matches(&request))
+    }
+}
+
+//
+// Complex Conditions
+//
+
+use crate::config::{self, Condition};
+
+/// A N-API wrapper for the `GroupCondition` type.
+#[napi]
+#[derive(Clone, Debug)]
+pub struct GroupCondition(crate::GroupCondition<Condition, Condition>);
+
+#[napi]
+impl GroupCondition {
+    /// Check if the given request matches the group condition.
+    ///
+    /// # Examples
+    ///
+    /// ```js
+    /// const matches = condition.matches(request);
+    /// ```
+    #[napi]
+    pub fn matches(&self, request: Request) -> Result<bool> {
+        Ok(self.0.matches(&request))
+    }
+}
+
+// Type alias for any condition which can be passed to `and`/`or` methods in JS
+type AnyCondition<'a> = Either24<
+    &'a PathCondition,
+    &'a HeaderCondition,
+    &'a MethodCondition,
+    &'a ExistenceCondition,
+    &'a NonExistenceCondition,
+    &'a HeaderAbsentCondition,
+    &'a AuthSchemeCondition,
+    &'a LanguageCondition,
+    &'a ForwardedForCountCondition,
+    &'a PathGlobCondition,
+    &'a ForwardedCondition,
+    &'a HeaderNameExistsCondition,
+    &'a RequiredHeadersCondition,
+    &'a HeaderCompareCondition,
+    &'a TrailingSlashCondition,
+    &'a TargetLengthCondition,
+    &'a QueryFlagCondition,
+    &'a CorsPreflightCondition,
+    &'a HostAllowlistCondition,
+    &'a DocumentRootCondition,
+    &'a DuplicateHeaderCondition,
+    &'a TraversalCondition,
+    &'a AcceptEncodingCondition,
+    &'a GroupCondition,
+>;
+
+impl From<AnyCondition<'_>> for Condition {
+    fn from(condition: AnyCondition<'_>) -> Self {
+        match condition {
+            Either24::A(path) => path.0.clone().into(),
+            Either24::B(header) => header.0.clone().into(),
+            Either24::C(method) => method.0.clone().into(),
+            Either24::D(existence) => existence.0.clone().into(),
+            Either24::E(nonexistence) => nonexistence.0.clone().into(),
+            Either24::F(header_absent) => header_absent.0.clone().into(),
+            Either24::G(auth_scheme) => auth_scheme.0.clone().into(),
+            Either24::H(language) => language.0.clone().into(),
+            Either24::I(forwarded_for_count) => forwarded_for_count.0.clone().into(),
+            Either24::J(path_glob) => path_glob.0.clone().into(),
+            Either24::K(forwarded) => forwarded.0.clone().into(),
+            Either24::L(header_name_exists) => header_name_exists.0.clone().into(),
+            Either24::M(required_headers) => required_headers.0.clone().into(),
+            Either24::N(header_compare) => header_compare.0.clone().into(),
+            Either24::O(trailing_slash) => trailing_slash.0.clone().into(),
+            Either24::P(target_length) => target_length.0.clone().into(),
+            Either24::Q(query_flag) => query_flag.0.clone().into(),
+            Either24::R(cors_preflight) => cors_preflight.0.into(),
+            Either24::S(host_allowlist) => host_allowlist.0.clone().into(),
+            Either24::T(document_root) => document_root.0.clone().into(),
+            Either24::U(duplicate_header) => duplicate_header.0.clone().into(),
+            Either24::V(traversal) => traversal.0.into(),
+            Either24::W(accept_encoding) => accept_encoding.0.clone().into(),
+            Either24::X(group) => group.0.clone().into(),
+        }
+    }
+}
+
+// Implement combinators for all condition types
+//
+// Provides:
+// - `and` method to combine conditions with logical AND
+// - `or` method to combine conditions with logical OR
+macro_rules! impl_condition_combinators {
+    ($type:ty) => {
+        #[napi]
+        impl $type {
+            /// Create a new condition that matches when both conditions match
+            ///
+            /// # Examples
+            ///
+            /// ```js
+            /// const combined = condition1.and(condition2);
+            /// ```
+            #[napi]
+            pub fn and(&self, other: AnyCondition) -> Result<GroupCondition> {
+                let this = Condition::from(self.0.clone());
+                Ok(GroupCondition(this.and(Condition::from(other))))
+            }
+
+            /// Create a new condition that matches when either condition matches
+            ///
+            /// # Examples
+            ///
+            /// ```js
+            /// const combined = condition1.or(condition2);
+            /// ```
+            #[napi]
+            pub fn or(&self, other: AnyCondition) -> Result<GroupCondition> {
+                let this = Condition::from(self.0.clone());
+                Ok(GroupCondition(this.or(Condition::from(other))))
+            }
+        }
+    };
 }
 
+impl_condition_combinators!(PathCondition);
+impl_condition_combinators!(HeaderCondition);
+impl_condition_combinators!(MethodCondition);
+impl_condition_combinators!(ExistenceCondition);
+impl_condition_combinators!(NonExistenceCondition);
+impl_condition_combinators!(HeaderAbsentCondition);
+impl_condition_combinators!(AuthSchemeCondition);
+impl_condition_combinators!(LanguageCondition);
+impl_condition_combinators!(ForwardedForCountCondition);
+impl_condition_combinators!(PathGlobCondition);
+impl_condition_combinators!(ForwardedCondition);
+impl_condition_combinators!(HeaderNameExistsCondition);
+impl_condition_combinators!(RequiredHeadersCondition);
+impl_condition_combinators!(HeaderCompareCondition);
+impl_condition_combinators!(TrailingSlashCondition);
+impl_condition_combinators!(TargetLengthCondition);
+impl_condition_combinators!(QueryFlagCondition);
+impl_condition_combinators!(CorsPreflightCondition);
+impl_condition_combinators!(HostAllowlistCondition);
+impl_condition_combinators!(DocumentRootCondition);
+impl_condition_combinators!(DuplicateHeaderCondition);
+impl_condition_combinators!(TraversalCondition);
+impl_condition_combinators!(AcceptEncodingCondition);
+impl_condition_combinators!(GroupCondition);
+
 //
 // Rewriters
 //
@@ -900,6 +1118,83 @@ impl MethodRewriter {
     }
 }
 
+/// A N-API wrapper for the `VersionRewriter` type.
+#[napi]
+#[derive(Clone, Debug)]
+pub struct VersionRewriter(crate::VersionRewriter);
+
+#[napi]
+impl VersionRewriter {
+    /// Create a new version rewriter.
+    ///
+    /// # Examples
+    ///
+    /// ```js
+    /// const rewriter = new VersionRewriter('HTTP/2');
+    /// ```
+    #[napi(constructor)]
+    pub fn new(version: String) -> Result<Self> {
+        let rewriter = crate::VersionRewriter::new(version)
+            .map_err(|e| Error::new(Status::GenericFailure, e.to_string()))?;
+
+        Ok(Self(rewriter))
+    }
+
+    /// Rewrite the given request version.
+    ///
+    /// # Examples
+    ///
+    /// ```js
+    /// const rewritten = rewriter.rewrite(request);
+    /// ```
+    #[napi]
+    pub fn rewrite(&self, request: Request, docroot: Option<String>) -> Result<Request> {
+        let rewritten = self
+            .0
+            .rewrite(prepare_request_with_docroot(request, docroot)?)
+            .map_err(|e| Error::new(Status::GenericFailure, e.to_string()))?;
+
+        Ok(rewritten.into())
+    }
+}
+
+/// A N-API wrapper for the `PortRewriter` type.
+#[napi]
+#[derive(Clone, Debug)]
+pub struct PortRewriter(crate::PortRewriter);
+
+#[napi]
+impl PortRewriter {
+    /// Create a new port rewriter.
+    ///
+    /// # Examples
+    ///
+    /// ```js
+    /// const rewriter = new PortRewriter(8080);
+    /// ```
+    #[napi(constructor)]
+    pub fn new(port: u16) -> Self {
+        Self(crate::PortRewriter::new(port))
+    }
+
+    /// Rewrite the given request port.
+    ///
+    /// # Examples
+    ///
+    /// ```js
+    /// const rewritten = rewriter.rewrite(request);
+    /// ```
+    #[napi]
+    pub fn rewrite(&self, request: Request, docroot: Option<String>) -> Result<Request> {
+        let rewritten = self
+            .0
+            .rewrite(prepare_request_with_docroot(request, docroot)?)
+            .map_err(|e| Error::new(Status::GenericFailure, e.to_string()))?;
+
+        Ok(rewritten.into())
+    }
+}
+
 /// A N-API wrapper for the `HrefRewriter` type.
 #[napi]
 #[derive(Clone, Debug)]
@@ -940,224 +1235,50 @@ impl HrefRewriter {
     }
 }
 
-// Since Rewriter traits have generic methods, we need to create a type-erased
-// wrapper that can be used with SequenceRewriter
-#[allow(non_camel_case_types)]
+/// A N-API wrapper for the `PathAndQueryRewriter` type.
+#[napi]
 #[derive(Clone, Debug)]
-enum SequenceRewriterType {
-    Path_Path(crate::SequenceRewriter<crate::PathRewriter, crate::PathRewriter>),
-    Path_Header(crate::SequenceRewriter<crate::PathRewriter, crate::HeaderRewriter>),
-    Path_Method(crate::SequenceRewriter<crate::PathRewriter, crate::MethodRewriter>),
-    Path_Href(crate::SequenceRewriter<crate::PathRewriter, crate::HrefRewriter>),
-    Path_Sequence(crate::SequenceRewriter<crate::PathRewriter, SequenceRewriterType>),
-    Path_Conditional(crate::SequenceRewriter<crate::PathRewriter, ConditionalRewriterType>),
-
-    Header_Path(crate::SequenceRewriter<crate::HeaderRewriter, crate::PathRewriter>),
-    Header_Header(crate::SequenceRewriter<crate::HeaderRewriter, crate::HeaderRewriter>),
-    Header_Method(crate::SequenceRewriter<crate::HeaderRewriter, crate::MethodRewriter>),
-    Header_Href(crate::SequenceRewriter<crate::HeaderRewriter, crate::HrefRewriter>),
-    Header_Sequence(crate::SequenceRewriter<crate::HeaderRewriter, SequenceRewriterType>),
-    Header_Conditional(crate::SequenceRewriter<crate::HeaderRewriter, ConditionalRewriterType>),
-
-    Method_Path(crate::SequenceRewriter<crate::MethodRewriter, crate::PathRewriter>),
-    Method_Header(crate::SequenceRewriter<crate::MethodRewriter, crate::HeaderRewriter>),
-    Method_Method(crate::SequenceRewriter<crate::MethodRewriter, crate::MethodRewriter>),
-    Method_Href(crate::SequenceRewriter<crate::MethodRewriter, crate::HrefRewriter>),
-    Method_Sequence(crate::SequenceRewriter<crate::MethodRewriter, SequenceRewriterType>),
-    Method_Conditional(crate::SequenceRewriter<crate::MethodRewriter, ConditionalRewriterType>),
-
-    // Sequences with href
-    Href_Path(crate::SequenceRewriter<crate::HrefRewriter, crate::PathRewriter>),
-    Href_Header(crate::SequenceRewriter<crate::HrefRewriter, crate::HeaderRewriter>),
-    Href_Method(crate::SequenceRewriter<crate::HrefRewriter, crate::MethodRewriter>),
-    Href_Href(crate::SequenceRewriter<crate::HrefRewriter, crate::HrefRewriter>),
-    Href_Sequence(crate::SequenceRewriter<crate::HrefRewriter, SequenceRewriterType>),
-    Href_Conditional(crate::SequenceRewriter<crate::HrefRewriter, ConditionalRewriterType>),
-
-    // Sequences with sequences (for nested sequences)
-    Sequence_Path(crate::SequenceRewriter<SequenceRewriterType, crate::PathRewriter>),
-    Sequence_Header(crate::SequenceRewriter<SequenceRewriterType, crate::HeaderRewriter>),
-    Sequence_Method(crate::SequenceRewriter<SequenceRewriterType, crate::MethodRewriter>),
-    Sequence_Href(crate::SequenceRewriter<SequenceRewriterType, crate::HrefRewriter>),
-    Sequence_Sequence(crate::SequenceRewriter<SequenceRewriterType, SequenceRewriterType>),
-    Sequence_Conditional(crate::SequenceRewriter<SequenceRewriterType, ConditionalRewriterType>),
-
-    // Conditional rewriters
-    Conditional_Path(crate::SequenceRewriter<ConditionalRewriterType, crate::PathRewriter>),
-    Conditional_Header(crate::SequenceRewriter<ConditionalRewriterType, crate::HeaderRewriter>),
-    Conditional_Method(crate::SequenceRewriter<ConditionalRewriterType, crate::MethodRewriter>),
-    Conditional_Href(crate::SequenceRewriter<ConditionalRewriterType, crate::HrefRewriter>),
-    Conditional_Sequence(crate::SequenceRewriter<ConditionalRewriterType, SequenceRewriterType>),
-    Conditional_Conditional(
-        crate::SequenceRewriter<ConditionalRewriterType, ConditionalRewriterType>,
-    ),
-}
+pub struct PathAndQueryRewriter(crate::PathAndQueryRewriter);
 
-impl crate::Rewriter for SequenceRewriterType {
-    fn rewrite<B>(
-        &self,
-        request: http::Request<B>,
-    ) -> std::result::Result<http::Request<B>, crate::RewriteError> {
-        match self {
-            SequenceRewriterType::Path_Path(r) => r.rewrite(request),
-            SequenceRewriterType::Path_Header(r) => r.rewrite(request),
-            SequenceRewriterType::Path_Method(r) => r.rewrite(request),
-            SequenceRewriterType::Path_Href(r) => r.rewrite(request),
-            SequenceRewriterType::Path_Sequence(r) => r.rewrite(request),
-            SequenceRewriterType::Path_Conditional(r) => r.rewrite(request),
-
-            SequenceRewriterType::Header_Path(r) => r.rewrite(request),
-            SequenceRewriterType::Header_Header(r) => r.rewrite(request),
-            SequenceRewriterType::Header_Method(r) => r.rewrite(request),
-            SequenceRewriterType::Header_Href(r) => r.rewrite(request),
-            SequenceRewriterType::Header_Sequence(r) => r.rewrite(request),
-            SequenceRewriterType::Header_Conditional(r) => r.rewrite(request),
-
-            SequenceRewriterType::Method_Path(r) => r.rewrite(request),
-            SequenceRewriterType::Method_Header(r) => r.rewrite(request),
-            SequenceRewriterType::Method_Method(r) => r.rewrite(request),
-            SequenceRewriterType::Method_Href(r) => r.rewrite(request),
-            SequenceRewriterType::Method_Sequence(r) => r.rewrite(request),
-            SequenceRewriterType::Method_Conditional(r) => r.rewrite(request),
-
-            SequenceRewriterType::Href_Path(r) => r.rewrite(request),
-            SequenceRewriterType::Href_Header(r) => r.rewrite(request),
-            SequenceRewriterType::Href_Method(r) => r.rewrite(request),
-            SequenceRewriterType::Href_Href(r) => r.rewrite(request),
-            SequenceRewriterType::Href_Sequence(r) => r.rewrite(request),
-            SequenceRewriterType::Href_Conditional(r) => r.rewrite(request),
-
-            SequenceRewriterType::Sequence_Path(r) => r.rewrite(request),
-            SequenceRewriterType::Sequence_Header(r) => r.rewrite(request),
-            SequenceRewriterType::Sequence_Method(r) => r.rewrite(request),
-            SequenceRewriterType::Sequence_Href(r) => r.rewrite(request),
-            SequenceRewriterType::Sequence_Sequence(r) => r.rewrite(request),
-            SequenceRewriterType::Sequence_Conditional(r) => r.rewrite(request),
-
-            SequenceRewriterType::Conditional_Path(r) => r.rewrite(request),
-            SequenceRewriterType::Conditional_Header(r) => r.rewrite(request),
-            SequenceRewriterType::Conditional_Method(r) => r.rewrite(request),
-            SequenceRewriterType::Conditional_Href(r) => r.rewrite(request),
-            SequenceRewriterType::Conditional_Sequence(r) => r.rewrite(request),
-            SequenceRewriterType::Conditional_Conditional(r) => {
-                println!("yep: {:#?}", r);
-                r.rewrite(request)
-            }
-        }
+#[napi]
+impl PathAndQueryRewriter {
+    /// Create a new path and query rewriter.
+    ///
+    /// # Examples
+    ///
+    /// ```js
+    /// const rewriter = new PathAndQueryRewriter('^/old\\?x=1$', '/new?x=2');
+    /// ```
+    #[napi(constructor)]
+    pub fn new(pattern: String, replacement: String) -> Result<Self> {
+        let rewriter = crate::PathAndQueryRewriter::new(pattern, replacement)
+            .map_err(|e| Error::new(Status::GenericFailure, e.to_string()))?;
+
+        Ok(Self(rewriter))
     }
-}
 
-// Implement `From` for each combination of SequenceRewriter
-macro_rules! impl_from_sequence_rewriter {
-    ($a:ty, $b:ty, $name:ident) => {
-        impl From<crate::SequenceRewriter<$a, $b>> for SequenceRewriterType {
-            fn from(rewriter: crate::SequenceRewriter<$a, $b>) -> Self {
-                SequenceRewriterType::$name(rewriter)
-            }
-        }
+    /// Rewrite the given request path and query.
+    ///
+    /// # Examples
+    ///
+    /// ```js
+    /// const rewritten = rewriter.rewrite(request);
+    /// ```
+    #[napi]
+    pub fn rewrite(&self, request: Request, docroot: Option<String>) -> Result<Request> {
+        let rewritten = self
+            .0
+            .rewrite(prepare_request_with_docroot(request, docroot)?)
+            .map_err(|e| Error::new(Status::GenericFailure, e.to_string()))?;
 
-        impl From<crate::SequenceRewriter<$a, $b>> for Rewriter {
-            fn from(rewriter: crate::SequenceRewriter<$a, $b>) -> Self {
-                Rewriter(Either6::E(rewriter.into()))
-            }
-        }
-    };
+        Ok(rewritten.into())
+    }
 }
 
-impl_from_sequence_rewriter!(crate::PathRewriter, crate::PathRewriter, Path_Path);
-impl_from_sequence_rewriter!(crate::PathRewriter, crate::HeaderRewriter, Path_Header);
-impl_from_sequence_rewriter!(crate::PathRewriter, crate::MethodRewriter, Path_Method);
-impl_from_sequence_rewriter!(crate::PathRewriter, crate::HrefRewriter, Path_Href);
-impl_from_sequence_rewriter!(crate::PathRewriter, SequenceRewriterType, Path_Sequence);
-impl_from_sequence_rewriter!(
-    crate::PathRewriter,
-    ConditionalRewriterType,
-    Path_Conditional
-);
-
-impl_from_sequence_rewriter!(crate::HeaderRewriter, crate::PathRewriter, Header_Path);
-impl_from_sequence_rewriter!(crate::HeaderRewriter, crate::HeaderRewriter, Header_Header);
-impl_from_sequence_rewriter!(crate::HeaderRewriter, crate::MethodRewriter, Header_Method);
-impl_from_sequence_rewriter!(crate::HeaderRewriter, crate::HrefRewriter, Header_Href);
-impl_from_sequence_rewriter!(crate::HeaderRewriter, SequenceRewriterType, Header_Sequence);
-impl_from_sequence_rewriter!(
-    crate::HeaderRewriter,
-    ConditionalRewriterType,
-    Header_Conditional
-);
-
-impl_from_sequence_rewriter!(crate::MethodRewriter, crate::PathRewriter, Method_Path);
-impl_from_sequence_rewriter!(crate::MethodRewriter, crate::HeaderRewriter, Method_Header);
-impl_from_sequence_rewriter!(crate::MethodRewriter, crate::MethodRewriter, Method_Method);
-impl_from_sequence_rewriter!(crate::MethodRewriter, crate::HrefRewriter, Method_Href);
-impl_from_sequence_rewriter!(crate::MethodRewriter, SequenceRewriterType, Method_Sequence);
-impl_from_sequence_rewriter!(
-    crate::MethodRewriter,
-    ConditionalRewriterType,
-    Method_Conditional
-);
-
-impl_from_sequence_rewriter!(crate::HrefRewriter, crate::PathRewriter, Href_Path);
-impl_from_sequence_rewriter!(crate::HrefRewriter, crate::HeaderRewriter, Href_Header);
-impl_from_sequence_rewriter!(crate::HrefRewriter, crate::MethodRewriter, Href_Method);
-impl_from_sequence_rewriter!(crate::HrefRewriter, crate::HrefRewriter, Href_Href);
-impl_from_sequence_rewriter!(crate::HrefRewriter, SequenceRewriterType, Href_Sequence);
-impl_from_sequence_rewriter!(
-    crate::HrefRewriter,
-    ConditionalRewriterType,
-    Href_Conditional
-);
-
-impl_from_sequence_rewriter!(SequenceRewriterType, crate::PathRewriter, Sequence_Path);
-impl_from_sequence_rewriter!(SequenceRewriterType, crate::HeaderRewriter, Sequence_Header);
-impl_from_sequence_rewriter!(SequenceRewriterType, crate::MethodRewriter, Sequence_Method);
-impl_from_sequence_rewriter!(SequenceRewriterType, crate::HrefRewriter, Sequence_Href);
-impl_from_sequence_rewriter!(
-    SequenceRewriterType,
-    SequenceRewriterType,
-    Sequence_Sequence
-);
-impl_from_sequence_rewriter!(
-    SequenceRewriterType,
-    ConditionalRewriterType,
-    Sequence_Conditional
-);
-
-impl_from_sequence_rewriter!(
-    ConditionalRewriterType,
-    crate::PathRewriter,
-    Conditional_Path
-);
-impl_from_sequence_rewriter!(
-    ConditionalRewriterType,
-    crate::HeaderRewriter,
-    Conditional_Header
-);
-impl_from_sequence_rewriter!(
-    ConditionalRewriterType,
-    crate::MethodRewriter,
-    Conditional_Method
-);
-impl_from_sequence_rewriter!(
-    ConditionalRewriterType,
-    crate::HrefRewriter,
-    Conditional_Href
-);
-impl_from_sequence_rewriter!(
-    ConditionalRewriterType,
-    SequenceRewriterType,
-    Conditional_Sequence
-);
-impl_from_sequence_rewriter!(
-    ConditionalRewriterType,
-    ConditionalRewriterType,
-    Conditional_Conditional
-);
-
 /// A N-API wrapper for the `SequenceRewriter` type.
 #[napi]
 #[derive(Clone, Debug)]
-pub struct SequenceRewriter(SequenceRewriterType);
+pub struct SequenceRewriter(crate::SequenceRewriter<config::Rewriter, config::Rewriter>);
 
 #[napi]
 impl SequenceRewriter {
@@ -1179,244 +1300,10 @@ impl SequenceRewriter {
     }
 }
 
-// Since Rewriter and Condition traits have generic methods, we need to create
-// a type-erased wrapper that can be used with ConditionalRewriter
-#[allow(non_camel_case_types)]
-#[derive(Clone, Debug)]
-enum ConditionalRewriterType {
-    Path_Path(crate::ConditionalRewriter<crate::PathRewriter, crate::PathCondition>),
-    Path_Header(crate::ConditionalRewriter<crate::PathRewriter, crate::HeaderCondition>),
-    Path_Method(crate::ConditionalRewriter<crate::PathRewriter, crate::MethodCondition>),
-    Path_Existence(crate::ConditionalRewriter<crate::PathRewriter, crate::ExistenceCondition>),
-    Path_NonExistence(
-        crate::ConditionalRewriter<crate::PathRewriter, crate::NonExistenceCondition>,
-    ),
-    Path_Group(crate::ConditionalRewriter<crate::PathRewriter, GroupConditionType>),
-    Header_Path(crate::ConditionalRewriter<crate::HeaderRewriter, crate::PathCondition>),
-    Header_Header(crate::ConditionalRewriter<crate::HeaderRewriter, crate::HeaderCondition>),
-    Header_Method(crate::ConditionalRewriter<crate::HeaderRewriter, crate::MethodCondition>),
-    Header_Existence(crate::ConditionalRewriter<crate::HeaderRewriter, crate::ExistenceCondition>),
-    Header_NonExistence(
-        crate::ConditionalRewriter<crate::HeaderRewriter, crate::NonExistenceCondition>,
-    ),
-    Header_Group(crate::ConditionalRewriter<crate::HeaderRewriter, GroupConditionType>),
-    Method_Path(crate::ConditionalRewriter<crate::MethodRewriter, crate::PathCondition>),
-    Method_Header(crate::ConditionalRewriter<crate::MethodRewriter, crate::HeaderCondition>),
-    Method_Method(crate::ConditionalRewriter<crate::MethodRewriter, crate::MethodCondition>),
-    Method_Existence(crate::ConditionalRewriter<crate::MethodRewriter, crate::ExistenceCondition>),
-    Method_NonExistence(
-        crate::ConditionalRewriter<crate::MethodRewriter, crate::NonExistenceCondition>,
-    ),
-    Method_Group(crate::ConditionalRewriter<crate::MethodRewriter, GroupConditionType>),
-    Href_Path(crate::ConditionalRewriter<crate::HrefRewriter, crate::PathCondition>),
-    Href_Header(crate::ConditionalRewriter<crate::HrefRewriter, crate::HeaderCondition>),
-    Href_Method(crate::ConditionalRewriter<crate::HrefRewriter, crate::MethodCondition>),
-    Href_Existence(crate::ConditionalRewriter<crate::HrefRewriter, crate::ExistenceCondition>),
-    Href_NonExistence(
-        crate::ConditionalRewriter<crate::HrefRewriter, crate::NonExistenceCondition>,
-    ),
-    Href_Group(crate::ConditionalRewriter<crate::HrefRewriter, GroupConditionType>),
-    Sequence_Path(crate::ConditionalRewriter<SequenceRewriterType, crate::PathCondition>),
-    Sequence_Header(crate::ConditionalRewriter<SequenceRewriterType, crate::HeaderCondition>),
-    Sequence_Method(crate::ConditionalRewriter<SequenceRewriterType, crate::MethodCondition>),
-    Sequence_Existence(crate::ConditionalRewriter<SequenceRewriterType, crate::ExistenceCondition>),
-    Sequence_NonExistence(
-        crate::ConditionalRewriter<SequenceRewriterType, crate::NonExistenceCondition>,
-    ),
-    Sequence_Group(crate::ConditionalRewriter<SequenceRewriterType, GroupConditionType>),
-    Conditional_Path(crate::ConditionalRewriter<ConditionalRewriterType, crate::PathCondition>),
-    Conditional_Header(crate::ConditionalRewriter<ConditionalRewriterType, crate::HeaderCondition>),
-    Conditional_Method(crate::ConditionalRewriter<ConditionalRewriterType, crate::MethodCondition>),
-    Conditional_Existence(
-        crate::ConditionalRewriter<ConditionalRewriterType, crate::ExistenceCondition>,
-    ),
-    Conditional_NonExistence(
-        crate::ConditionalRewriter<ConditionalRewriterType, crate::NonExistenceCondition>,
-    ),
-    Conditional_Group(crate::ConditionalRewriter<ConditionalRewriterType, GroupConditionType>),
-}
-
-impl crate::Rewriter for ConditionalRewriterType {
-    fn rewrite<B>(
-        &self,
-        request: http::Request<B>,
-    ) -> std::result::Result<http::Request<B>, crate::RewriteError> {
-        match self {
-            ConditionalRewriterType::Path_Path(r) => r.rewrite(request),
-            ConditionalRewriterType::Path_Header(r) => r.rewrite(request),
-            ConditionalRewriterType::Path_Method(r) => r.rewrite(request),
-            ConditionalRewriterType::Path_Existence(r) => r.rewrite(request),
-            ConditionalRewriterType::Path_NonExistence(r) => r.rewrite(request),
-            ConditionalRewriterType::Path_Group(r) => r.rewrite(request),
-            ConditionalRewriterType::Header_Path(r) => r.rewrite(request),
-            ConditionalRewriterType::Header_Header(r) => r.rewrite(request),
-            ConditionalRewriterType::Header_Method(r) => r.rewrite(request),
-            ConditionalRewriterType::Header_Existence(r) => r.rewrite(request),
-            ConditionalRewriterType::Header_NonExistence(r) => r.rewrite(request),
-            ConditionalRewriterType::Header_Group(r) => r.rewrite(request),
-            ConditionalRewriterType::Method_Path(r) => r.rewrite(request),
-            ConditionalRewriterType::Method_Header(r) => r.rewrite(request),
-            ConditionalRewriterType::Method_Method(r) => r.rewrite(request),
-            ConditionalRewriterType::Method_Existence(r) => r.rewrite(request),
-            ConditionalRewriterType::Method_NonExistence(r) => r.rewrite(request),
-            ConditionalRewriterType::Method_Group(r) => r.rewrite(request),
-            ConditionalRewriterType::Href_Path(r) => r.rewrite(request),
-            ConditionalRewriterType::Href_Header(r) => r.rewrite(request),
-            ConditionalRewriterType::Href_Method(r) => r.rewrite(request),
-            ConditionalRewriterType::Href_Existence(r) => r.rewrite(request),
-            ConditionalRewriterType::Href_NonExistence(r) => r.rewrite(request),
-            ConditionalRewriterType::Href_Group(r) => r.rewrite(request),
-            ConditionalRewriterType::Sequence_Path(r) => r.rewrite(request),
-            ConditionalRewriterType::Sequence_Header(r) => r.rewrite(request),
-            ConditionalRewriterType::Sequence_Method(r) => r.rewrite(request),
-            ConditionalRewriterType::Sequence_Existence(r) => r.rewrite(request),
-            ConditionalRewriterType::Sequence_NonExistence(r) => r.rewrite(request),
-            ConditionalRewriterType::Sequence_Group(r) => r.rewrite(request),
-            ConditionalRewriterType::Conditional_Path(r) => r.rewrite(request),
-            ConditionalRewriterType::Conditional_Header(r) => r.rewrite(request),
-            ConditionalRewriterType::Conditional_Method(r) => r.rewrite(request),
-            ConditionalRewriterType::Conditional_Existence(r) => r.rewrite(request),
-            ConditionalRewriterType::Conditional_NonExistence(r) => r.rewrite(request),
-            ConditionalRewriterType::Conditional_Group(r) => r.rewrite(request),
-        }
-    }
-}
-
-// Implement `From` for each combination of ConditionalRewriter
-macro_rules! impl_from_conditional_rewriter {
-    ($a:ty, $b:ty, $name:ident) => {
-        impl From<crate::ConditionalRewriter<$a, $b>> for ConditionalRewriterType {
-            fn from(rewriter: crate::ConditionalRewriter<$a, $b>) -> Self {
-                ConditionalRewriterType::$name(rewriter)
-            }
-        }
-
-        impl From<crate::ConditionalRewriter<$a, $b>> for Rewriter {
-            fn from(rewriter: crate::ConditionalRewriter<$a, $b>) -> Self {
-                Rewriter(Either6::F(rewriter.into()))
-            }
-        }
-    };
-}
-
-impl_from_conditional_rewriter!(crate::PathRewriter, crate::PathCondition, Path_Path);
-impl_from_conditional_rewriter!(crate::PathRewriter, crate::HeaderCondition, Path_Header);
-impl_from_conditional_rewriter!(crate::PathRewriter, crate::MethodCondition, Path_Method);
-impl_from_conditional_rewriter!(
-    crate::PathRewriter,
-    crate::ExistenceCondition,
-    Path_Existence
-);
-impl_from_conditional_rewriter!(
-    crate::PathRewriter,
-    crate::NonExistenceCondition,
-    Path_NonExistence
-);
-impl_from_conditional_rewriter!(crate::PathRewriter, GroupConditionType, Path_Group);
-
-impl_from_conditional_rewriter!(crate::HeaderRewriter, crate::PathCondition, Header_Path);
-impl_from_conditional_rewriter!(crate::HeaderRewriter, crate::HeaderCondition, Header_Header);
-impl_from_conditional_rewriter!(crate::HeaderRewriter, crate::MethodCondition, Header_Method);
-impl_from_conditional_rewriter!(
-    crate::HeaderRewriter,
-    crate::ExistenceCondition,
-    Header_Existence
-);
-impl_from_conditional_rewriter!(
-    crate::HeaderRewriter,
-    crate::NonExistenceCondition,
-    Header_NonExistence
-);
-impl_from_conditional_rewriter!(crate::HeaderRewriter, GroupConditionType, Header_Group);
-
-impl_from_conditional_rewriter!(crate::MethodRewriter, crate::PathCondition, Method_Path);
-impl_from_conditional_rewriter!(crate::MethodRewriter, crate::HeaderCondition, Method_Header);
-impl_from_conditional_rewriter!(crate::MethodRewriter, crate::MethodCondition, Method_Method);
-impl_from_conditional_rewriter!(
-    crate::MethodRewriter,
-    crate::ExistenceCondition,
-    Method_Existence
-);
-impl_from_conditional_rewriter!(
-    crate::MethodRewriter,
-    crate::NonExistenceCondition,
-    Method_NonExistence
-);
-impl_from_conditional_rewriter!(crate::MethodRewriter, GroupConditionType, Method_Group);
-
-impl_from_conditional_rewriter!(crate::HrefRewriter, crate::PathCondition, Href_Path);
-impl_from_conditional_rewriter!(crate::HrefRewriter, crate::HeaderCondition, Href_Header);
-impl_from_conditional_rewriter!(crate::HrefRewriter, crate::MethodCondition, Href_Method);
-impl_from_conditional_rewriter!(
-    crate::HrefRewriter,
-    crate::ExistenceCondition,
-    Href_Existence
-);
-impl_from_conditional_rewriter!(
-    crate::HrefRewriter,
-    crate::NonExistenceCondition,
-    Href_NonExistence
-);
-impl_from_conditional_rewriter!(crate::HrefRewriter, GroupConditionType, Href_Group);
-
-impl_from_conditional_rewriter!(SequenceRewriterType, crate::PathCondition, Sequence_Path);
-impl_from_conditional_rewriter!(
-    SequenceRewriterType,
-    crate::HeaderCondition,
-    Sequence_Header
-);
-impl_from_conditional_rewriter!(
-    SequenceRewriterType,
-    crate::MethodCondition,
-    Sequence_Method
-);
-impl_from_conditional_rewriter!(
-    SequenceRewriterType,
-    crate::ExistenceCondition,
-    Sequence_Existence
-);
-impl_from_conditional_rewriter!(
-    SequenceRewriterType,
-    crate::NonExistenceCondition,
-    Sequence_NonExistence
-);
-impl_from_conditional_rewriter!(SequenceRewriterType, GroupConditionType, Sequence_Group);
-
-impl_from_conditional_rewriter!(
-    ConditionalRewriterType,
-    crate::PathCondition,
-    Conditional_Path
-);
-impl_from_conditional_rewriter!(
-    ConditionalRewriterType,
-    crate::HeaderCondition,
-    Conditional_Header
-);
-impl_from_conditional_rewriter!(
-    ConditionalRewriterType,
-    crate::MethodCondition,
-    Conditional_Method
-);
-impl_from_conditional_rewriter!(
-    ConditionalRewriterType,
-    crate::ExistenceCondition,
-    Conditional_Existence
-);
-impl_from_conditional_rewriter!(
-    ConditionalRewriterType,
-    crate::NonExistenceCondition,
-    Conditional_NonExistence
-);
-impl_from_conditional_rewriter!(
-    ConditionalRewriterType,
-    GroupConditionType,
-    Conditional_Group
-);
-
 /// A N-API wrapper for the `ConditionalRewriter` type.
 #[napi]
 #[derive(Clone, Debug)]
-pub struct ConditionalRewriter(ConditionalRewriterType);
+pub struct ConditionalRewriter(crate::ConditionalRewriter<config::Rewriter, Condition>);
 
 #[napi]
 impl ConditionalRewriter {
@@ -1439,42 +1326,34 @@ impl ConditionalRewriter {
 }
 
 /// Type alias for any rewriter which can be passed to `then`/`when` methods in JS
-type AnyRewriter<'a> = Either6<
+type AnyRewriter<'a> = Either9<
     &'a PathRewriter,
     &'a HeaderRewriter,
     &'a MethodRewriter,
+    &'a VersionRewriter,
+    &'a PortRewriter,
     &'a HrefRewriter,
+    &'a PathAndQueryRewriter,
     &'a SequenceRewriter,
     &'a ConditionalRewriter,
 >;
 
-// Type alias for any rewriter which can be passed to `then`/`when` methods in Rust
-type AnyRewriterOwned = Either6<
-    crate::PathRewriter,
-    crate::HeaderRewriter,
-    crate::MethodRewriter,
-    crate::HrefRewriter,
-    SequenceRewriterType,
-    ConditionalRewriterType,
->;
-
-macro_rules! impl_from_rewriter {
-    ($type:ty, $variant:ident) => {
-        impl From<$type> for Rewriter {
-            fn from(rewriter: $type) -> Self {
-                Self(Either6::$variant(rewriter))
-            }
+impl From<AnyRewriter<'_>> for config::Rewriter {
+    fn from(rewriter: AnyRewriter<'_>) -> Self {
+        match rewriter {
+            Either9::A(path) => path.0.clone().into(),
+            Either9::B(header) => header.0.clone().into(),
+            Either9::C(method) => method.0.clone().into(),
+            Either9::D(version) => version.0.clone().into(),
+            Either9::E(port) => port.0.into(),
+            Either9::F(href) => href.0.clone().into(),
+            Either9::G(path_and_query) => path_and_query.0.clone().into(),
+            Either9::H(sequence) => sequence.0.clone().into(),
+            Either9::I(conditional) => conditional.0.clone().into(),
         }
-    };
+    }
 }
 
-impl_from_rewriter!(crate::PathRewriter, A);
-impl_from_rewriter!(crate::HeaderRewriter, B);
-impl_from_rewriter!(crate::MethodRewriter, C);
-impl_from_rewriter!(crate::HrefRewriter, D);
-impl_from_rewriter!(SequenceRewriterType, E);
-impl_from_rewriter!(ConditionalRewriterType, F);
-
 // Implement combinator functions for rewriter types
 //
 // This provides:
@@ -1493,15 +1372,8 @@ macro_rules! impl_rewriter_combinators {
             /// ```
             #[napi]
             pub fn then(&self, other: AnyRewriter) -> Result<SequenceRewriter> {
-                let this = self.0.clone();
-                Ok(SequenceRewriter(match other {
-                    Either6::A(path) => this.then(path.0.clone()).into(),
-                    Either6::B(header) => this.then(header.0.clone()).into(),
-                    Either6::C(method) => this.then(method.0.clone()).into(),
-                    Either6::D(href) => this.then(href.0.clone()).into(),
-                    Either6::E(sequence) => this.then(sequence.0.clone()).into(),
-                    Either6::F(conditional) => this.then(conditional.0.clone()).into(),
-                }))
+                let this = config::Rewriter::from(self.0.clone());
+                Ok(SequenceRewriter(this.then(config::Rewriter::from(other))))
             }
 
             /// Apply this rewriter conditionally based on a condition
@@ -1513,15 +1385,8 @@ macro_rules! impl_rewriter_combinators {
             /// ```
             #[napi]
             pub fn when(&self, condition: AnyCondition) -> Result<ConditionalRewriter> {
-                let this = self.0.clone();
-                Ok(ConditionalRewriter(match condition {
-                    Either6::A(path) => this.clone().when(path.0.clone()).into(),
-                    Either6::B(header) => this.clone().when(header.0.clone()).into(),
-                    Either6::C(method) => this.clone().when(method.0.clone()).into(),
-                    Either6::D(existence) => this.clone().when(existence.0).into(),
-                    Either6::E(nonexistence) => this.clone().when(nonexistence.0).into(),
-                    Either6::F(group) => this.when(group.0.clone()).into(),
-                }))
+                let this = config::Rewriter::from(self.0.clone());
+                Ok(ConditionalRewriter(this.when(Condition::from(condition))))
             }
         }
     };
@@ -1530,7 +1395,10 @@ macro_rules! impl_rewriter_combinators {
 impl_rewriter_combinators!(PathRewriter);
 impl_rewriter_combinators!(HeaderRewriter);
 impl_rewriter_combinators!(MethodRewriter);
+impl_rewriter_combinators!(VersionRewriter);
+impl_rewriter_combinators!(PortRewriter);
 impl_rewriter_combinators!(HrefRewriter);
+impl_rewriter_combinators!(PathAndQueryRewriter);
 impl_rewriter_combinators!(SequenceRewriter);
 impl_rewriter_combinators!(ConditionalRewriter);
 
@@ -1538,31 +1406,38 @@ impl_rewriter_combinators!(ConditionalRewriter);
 // Config-based Rewriter
 //
 
-/// Describe if a conmdition set is combined with AND or OR logic
-#[napi(string_enum = "lowercase")]
-#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Hash)]
-pub enum ConditionOperation {
-    /// All conditions must match for the rewriters to be applied
-    #[default]
-    And,
-    /// At least one condition must match for the rewriters to be applied
-    Or,
+use crate::config::{ConditionOperation, ConditionType, ConfigError, RewriterType};
+
+impl From<ConfigError> for Error {
+    fn from(error: ConfigError) -> Self {
+        Error::new(Status::InvalidArg, error.message().to_string())
+    }
+}
+
+/// Limits applied when compiling the patterns of a configuration.
+///
+/// Missing limits use the defaults.
+#[napi(object)]
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+pub struct RegexLimits {
+    /// Approximate maximum size in bytes of a compiled pattern
+    pub size_limit: Option<u32>,
+    /// Approximate maximum size in bytes of the lazy DFA cache of a pattern
+    pub dfa_size_limit: Option<u32>,
 }
 
-/// The types of conditions which may be used in a `ConditionConfig`.
-#[napi(string_enum = "snake_case")]
-#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
-pub enum ConditionType {
-    /// Matches based on the request path
-    Path,
-    /// Matches based on the request header
-    Header,
-    /// Matches based on the request method
-    Method,
-    /// Matches if a file exists at the given path
-    Exists,
-    /// Matches if a file does not exist at the given path
-    NotExists,
+impl From<RegexLimits> for config::RegexLimits {
+    fn from(limits: RegexLimits) -> Self {
+        let defaults = Self::default();
+        Self {
+            size_limit: limits
+                .size_limit
+                .map_or(defaults.size_limit, |limit| limit as usize),
+            dfa_size_limit: limits
+                .dfa_size_limit
+                .map_or(defaults.dfa_size_limit, |limit| limit as usize),
+        }
+    }
 }
 
 /// Configuration for a condition that can be used in a `ConditionalRewriterConfig`.
@@ -1574,156 +1449,23 @@ pub struct ConditionConfig {
     pub condition: ConditionType,
     /// The arguments for the condition, such as the path or header name
     pub args: Option<Vec<String>>,
+    /// Limits for compiling the patterns in the arguments
+    pub regex_limits: Option<RegexLimits>,
+    /// Compare the value literally rather than as a pattern, for `header` conditions
+    pub exact: Option<bool>,
 }
 
-impl TryFrom<ConditionConfig> for crate::PathCondition {
-    type Error = Error;
-
-    fn try_from(config: ConditionConfig) -> Result<Self> {
-        if config.condition != ConditionType::Path {
-            return Err(Error::new(
-                Status::InvalidArg,
-                "Expected Path condition type".to_string(),
-            ));
-        }
-        let args = config.args.unwrap_or_default();
-        if args.len() != 1 {
-            return Err(Error::new(
-                Status::InvalidArg,
-                "Path condition requires exactly one argument".to_string(),
-            ));
-        }
-        let pattern = args[0].clone();
-        let condition = crate::PathCondition::new(pattern)
-            .map_err(|e| Error::new(Status::InvalidArg, e.to_string()))?;
-        Ok(condition)
-    }
-}
-
-impl TryFrom<ConditionConfig> for crate::HeaderCondition {
-    type Error = Error;
-
-    fn try_from(config: ConditionConfig) -> Result<Self> {
-        if config.condition != ConditionType::Header {
-            return Err(Error::new(
-                Status::InvalidArg,
-                "Expected Header condition type".to_string(),
-            ));
-        }
-        let args = config.args.unwrap_or_default();
-        if args.len() != 2 {
-            return Err(Error::new(
-                Status::InvalidArg,
-                "Header condition requires exactly two arguments".to_string(),
-            ));
-        }
-        let header = args[0].clone();
-        let value = args[1].clone();
-        let condition = crate::HeaderCondition::new(header, value)
-            .map_err(|e| Error::new(Status::InvalidArg, e.to_string()))?;
-        Ok(condition)
-    }
-}
-
-impl TryFrom<ConditionConfig> for crate::MethodCondition {
-    type Error = Error;
-
-    fn try_from(config: ConditionConfig) -> Result<Self> {
-        if config.condition != ConditionType::Method {
-            return Err(Error::new(
-                Status::InvalidArg,
-                "Expected Method condition type".to_string(),
-            ));
-        }
-        let args = config.args.unwrap_or_default();
-        if args.len() != 1 {
-            return Err(Error::new(
-                Status::InvalidArg,
-                "Method condition requires exactly one argument".to_string(),
-            ));
-        }
-        let method = args[0].clone();
-        let condition = crate::MethodCondition::new(method)
-            .map_err(|e| Error::new(Status::InvalidArg, e.to_string()))?;
-        Ok(condition)
-    }
-}
-
-impl TryFrom<ConditionConfig> for crate::ExistenceCondition {
-    type Error = Error;
-
-    fn try_from(config: ConditionConfig) -> Result<Self> {
-        if config.condition != ConditionType::Exists {
-            return Err(Error::new(
-                Status::InvalidArg,
-                "Expected Exists condition type".to_string(),
-            ));
-        }
-        if !config.args.unwrap_or_default().is_empty() {
-            return Err(Error::new(
-                Status::InvalidArg,
-                "Exists condition requires no arguments".to_string(),
-            ));
-        }
-        Ok(crate::ExistenceCondition::new())
-    }
-}
-
-impl TryFrom<ConditionConfig> for crate::NonExistenceCondition {
-    type Error = Error;
-
-    fn try_from(config: ConditionConfig) -> Result<Self> {
-        if config.condition != ConditionType::NotExists {
-            return Err(Error::new(
-                Status::InvalidArg,
-                "Expected NotExists condition type".to_string(),
-            ));
-        }
-        if !config.args.unwrap_or_default().is_empty() {
-            return Err(Error::new(
-                Status::InvalidArg,
-                "NotExists condition requires no arguments".to_string(),
-            ));
-        }
-        Ok(crate::NonExistenceCondition::new())
-    }
-}
-
-impl TryFrom<(ConditionOperation, Vec<Condition>)> for Condition {
-    type Error = Error;
-
-    fn try_from((operation, conditions): (ConditionOperation, Vec<Condition>)) -> Result<Self> {
-        if conditions.is_empty() {
-            return Err(Error::new(
-                Status::InvalidArg,
-                "At least one condition is required".to_string(),
-            ));
+impl From<ConditionConfig> for config::ConditionConfig {
+    fn from(config: ConditionConfig) -> Self {
+        Self {
+            condition: config.condition,
+            args: config.args,
+            regex_limits: config.regex_limits.map(Into::into),
+            exact: config.exact,
         }
-
-        Ok(conditions
-            .into_iter()
-            .reduce(|a, b| match operation {
-                ConditionOperation::And => and(a, b),
-                ConditionOperation::Or => or(a, b),
-            })
-            .unwrap())
     }
 }
 
-/// The types of rewriters which may be used in a `RewriterConfig`.
-#[napi(string_enum = "lowercase")]
-#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
-pub enum RewriterType {
-    /// Rewrites the request path
-    Path,
-    /// Rewrites a request header
-    Header,
-    /// Rewrites the request method
-    Method,
-    /// Rewrites the request href
-    Href,
-}
-
 /// Configuration for a rewriter that can be used in a `ConditionalRewriterConfig`.
 #[napi(object)]
 #[derive(Clone, Debug, Eq, PartialEq, Hash)]
@@ -1733,109 +1475,20 @@ pub struct RewriterConfig {
     pub rewriter_type: RewriterType,
     /// The arguments for the rewriter, such as the pattern and replacement
     pub args: Option<Vec<String>>,
+    /// Replace every match of the pattern rather than only the first
+    pub replace_all: Option<bool>,
+    /// Limits for compiling the patterns in the arguments
+    pub regex_limits: Option<RegexLimits>,
 }
 
-//
-// Convert `RewriterConfig` into specific rewriter types.
-//
-
-impl TryFrom<RewriterConfig> for crate::PathRewriter {
-    type Error = Error;
-
-    fn try_from(config: RewriterConfig) -> Result<Self> {
-        if config.rewriter_type != RewriterType::Path {
-            return Err(Error::new(
-                Status::InvalidArg,
-                "Expected Path rewriter type".to_string(),
-            ));
-        }
-        let args = config.args.unwrap_or_default();
-        if args.len() != 2 {
-            return Err(Error::new(
-                Status::InvalidArg,
-                "Path rewriter requires exactly two arguments".to_string(),
-            ));
-        }
-        let pattern = args[0].clone();
-        let replacement = args[1].clone();
-        let rewriter = crate::PathRewriter::new(pattern, replacement)
-            .map_err(|e| Error::new(Status::InvalidArg, e.to_string()))?;
-        Ok(rewriter)
-    }
-}
-
-impl TryFrom<RewriterConfig> for crate::HeaderRewriter {
-    type Error = Error;
-
-    fn try_from(config: RewriterConfig) -> Result<Self> {
-        if config.rewriter_type != RewriterType::Header {
-            return Err(Error::new(
-                Status::InvalidArg,
-                "Expected Header rewriter type".to_string(),
-            ));
-        }
-        let args = config.args.unwrap_or_default();
-        if args.len() != 3 {
-            return Err(Error::new(
-                Status::InvalidArg,
-                "Header rewriter requires exactly three arguments".to_string(),
-            ));
-        }
-        let header = args[0].clone();
-        let pattern = args[1].clone();
-        let replacement = args[2].clone();
-        let rewriter = crate::HeaderRewriter::new(header, pattern, replacement)
-            .map_err(|e| Error::new(Status::InvalidArg, e.to_string()))?;
-        Ok(rewriter)
-    }
-}
-
-impl TryFrom<RewriterConfig> for crate::MethodRewriter {
-    type Error = Error;
-
-    fn try_from(config: RewriterConfig) -> Result<Self> {
-        if config.rewriter_type != RewriterType::Method {
-            return Err(Error::new(
-                Status::InvalidArg,
-                "Expected Method rewriter type".to_string(),
-            ));
-        }
-        let args = config.args.unwrap_or_default();
-        if args.len() != 1 {
-            return Err(Error::new(
-                Status::InvalidArg,
-                "Method rewriter requires exactly one argument".to_string(),
-            ));
-        }
-        let method = args[0].clone();
-        let rewriter = crate::MethodRewriter::new(method.as_str())
-            .map_err(|e| Error::new(Status::InvalidArg, e.to_string()))?;
-        Ok(rewriter)
-    }
-}
-
-impl TryFrom<RewriterConfig> for crate::HrefRewriter {
-    type Error = Error;
-
-    fn try_from(config: RewriterConfig) -> Result<Self> {
-        if config.rewriter_type != RewriterType::Href {
-            return Err(Error::new(
-                Status::InvalidArg,
-                "Expected Href rewriter type".to_string(),
-            ));
+impl From<RewriterConfig> for config::RewriterConfig {
+    fn from(config: RewriterConfig) -> Self {
+        Self {
+            rewriter_type: config.rewriter_type,
+            args: config.args,
+            replace_all: config.replace_all,
+            regex_limits: config.regex_limits.map(Into::into),
         }
-        let args = config.args.unwrap_or_default();
-        if args.len() != 2 {
-            return Err(Error::new(
-                Status::InvalidArg,
-                "Href rewriter requires exactly two arguments".to_string(),
-            ));
-        }
-        let pattern = args[0].clone();
-        let replacement = args[1].clone();
-        let rewriter = crate::HrefRewriter::new(pattern, replacement)
-            .map_err(|e| Error::new(Status::InvalidArg, e.to_string()))?;
-        Ok(rewriter)
     }
 }
 
@@ -1843,18 +1496,36 @@ impl TryFrom<RewriterConfig> for crate::HrefRewriter {
 #[napi(object)]
 #[derive(Clone, Debug, Eq, PartialEq, Hash)]
 pub struct ConditionalRewriterConfig {
+    /// Optional rule name, included in the message of errors raised by this rule
+    pub name: Option<String>,
     /// The logical operation to use when applying the condition set
     pub operation: Option<ConditionOperation>,
     /// The conditions that must be met for the rewriters to be applied
     pub conditions: Option<Vec<ConditionConfig>>,
     /// The rewriters to apply if the conditions are met
     pub rewriters: Vec<RewriterConfig>,
+    /// Metadata recorded on the requests this rule rewrites
+    pub metadata: Option<BTreeMap<String, String>>,
+}
+
+impl From<ConditionalRewriterConfig> for config::ConditionalRewriterConfig {
+    fn from(config: ConditionalRewriterConfig) -> Self {
+        Self {
+            name: config.name,
+            operation: config.operation,
+            conditions: config
+                .conditions
+                .map(|conditions| conditions.into_iter().map(Into::into).collect()),
+            rewriters: config.rewriters.into_iter().map(Into::into).collect(),
+            metadata: config.metadata,
+        }
+    }
 }
 
 /// Allows constructing rewriter and condition configurations from JSON.
 #[napi]
 #[derive(Clone, Debug)]
-pub struct Rewriter(AnyRewriterOwned);
+pub struct Rewriter(config::Rewriter);
 
 #[napi]
 impl Rewriter {
@@ -1886,10 +1557,10 @@ impl Rewriter {
     /// ```
     #[napi(constructor)]
     pub fn new(configs: Vec<ConditionalRewriterConfig>) -> Result<Self> {
-        let rewriter = Rewriter::try_from(configs)
-            .map_err(|e| Error::new(Status::GenericFailure, e.to_string()))?;
+        let configs: Vec<config::ConditionalRewriterConfig> =
+            configs.into_iter().map(Into::into).collect();
 
-        Ok(rewriter)
+        Ok(Self(config::Rewriter::try_from(configs)?))
     }
 
     /// Rewrite the given request using the configured rewriter.
@@ -1904,26 +1575,25 @@ impl Rewriter {
     #[napi(js_name = "rewrite")]
     pub fn js_rewrite(&self, request: Request, docroot: Option<String>) -> Result<Request> {
         let rewritten = self
+            .0
             .rewrite(prepare_request_with_docroot(request, docroot)?)
             .map_err(|e| Error::new(Status::GenericFailure, e.to_string()))?;
 
         Ok(rewritten.into())
     }
-}
 
-impl crate::Rewriter for Rewriter {
-    fn rewrite<B>(
-        &self,
-        request: http::Request<B>,
-    ) -> std::result::Result<http::Request<B>, crate::RewriteError> {
-        match &self.0 {
-            Either6::A(path) => path.rewrite(request),
-            Either6::B(header) => header.rewrite(request),
-            Either6::C(method) => method.rewrite(request),
-            Either6::D(href) => href.rewrite(request),
-            Either6::E(sequence) => sequence.rewrite(request),
-            Either6::F(conditional) => conditional.rewrite(request),
-        }
+    /// Combine this rewriter with another, applying all rules of this one first.
+    ///
+    /// # Examples
+    ///
+    /// ```js
+    /// const base = new Rewriter(baseConfigs);
+    /// const tenant = new Rewriter(tenantConfigs);
+    /// const rewriter = base.concat(tenant);
+    /// ```
+    #[napi]
+    pub fn concat(&self, other: &Rewriter) -> Rewriter {
+        Rewriter(self.0.clone().concat(other.0.clone()))
     }
 }
 
@@ -1939,14 +1609,7 @@ impl FromNapiValue for Rewriter {
 
         // If that fails, try to convert from AnyRewriter
         if let Ok(rewriter) = unsafe { AnyRewriter::from_napi_value(env, value) } {
-            return Ok(match rewriter {
-                Either6::A(PathRewriter(path)) => path.to_owned().into(),
-                Either6::B(HeaderRewriter(header)) => header.to_owned().into(),
-                Either6::C(MethodRewriter(method)) => method.to_owned().into(),
-                Either6::D(HrefRewriter(href)) => href.to_owned().into(),
-                Either6::E(SequenceRewriter(sequence)) => sequence.to_owned().into(),
-                Either6::F(ConditionalRewriter(conditional)) => conditional.to_owned().into(),
-            });
+            return Ok(Rewriter(rewriter.into()));
         }
 
         // If both conversions fail, return an error
@@ -1956,224 +1619,3 @@ impl FromNapiValue for Rewriter {
         ))
     }
 }
-
-impl TryFrom<ConditionalRewriterConfig> for Rewriter {
-    type Error = Error;
-
-    fn try_from(config: ConditionalRewriterConfig) -> Result<Self> {
-        // Extract fields before consuming config
-        let ConditionalRewriterConfig {
-            operation,
-            conditions,
-            rewriters,
-        } = config;
-
-        // Validate that we have at least one rewriter
-        if rewriters.is_empty() {
-            return Err(Error::new(
-                Status::InvalidArg,
-                "At least one rewriter is required".to_string(),
-            ));
-        }
-
-        let rewriter: Rewriter = rewriters
-            .into_iter()
-            .map(Rewriter::try_from)
-            .collect::<Result<Vec<_>>>()?
-            .try_into()?;
-
-        if conditions.is_none() {
-            return Ok(rewriter);
-        }
-
-        let conditions = conditions.unwrap_or_default();
-        if conditions.is_empty() {
-            return Ok(rewriter);
-        }
-
-        let conditions: Vec<Condition> = conditions
-            .into_iter()
-            .map(Condition::try_from)
-            .collect::<Result<Vec<_>>>()?;
-
-        let operation = operation.unwrap_or_default();
-
-        let condition: Condition = (operation, conditions).try_into()?;
-
-        Ok(when(rewriter, condition))
-    }
-}
-
-impl TryFrom<RewriterConfig> for Rewriter {
-    type Error = Error;
-
-    fn try_from(config: RewriterConfig) -> Result<Self> {
-        Ok(Rewriter(match config.rewriter_type {
-            RewriterType::Path => Either6::A(config.try_into()?),
-            RewriterType::Header => Either6::B(config.try_into()?),
-            RewriterType::Method => Either6::C(config.try_into()?),
-            RewriterType::Href => Either6::D(config.try_into()?),
-        }))
-    }
-}
-
-impl TryFrom<Vec<RewriterConfig>> for Rewriter {
-    type Error = Error;
-
-    fn try_from(configs: Vec<RewriterConfig>) -> Result<Self> {
-        if configs.is_empty() {
-            return Err(Error::new(
-                Status::InvalidArg,
-                "At least one rewriter configuration is required".to_string(),
-            ));
-        }
-
-        // Convert each config to a rewriter
-        configs
-            .into_iter()
-            .map(Rewriter::try_from)
-            .collect::<Result<Vec<_>>>()?
-            .try_into()
-    }
-}
-
-impl TryFrom<Vec<Rewriter>> for Rewriter {
-    type Error = Error;
-
-    fn try_from(rewriters: Vec<Rewriter>) -> Result<Self> {
-        // Ensure we have at least one rewriter
-        if rewriters.is_empty() {
-            return Err(Error::new(
-                Status::InvalidArg,
-                "At least one rewriter is required".to_string(),
-            ));
-        }
-
-        // Reduce the rewriters into a single Rewriter sequence
-        Ok(rewriters.into_iter().reduce(then).unwrap())
-    }
-}
-
-impl TryFrom<Vec<ConditionalRewriterConfig>> for Rewriter {
-    type Error = Error;
-
-    fn try_from(configs: Vec<ConditionalRewriterConfig>) -> Result<Self> {
-        if configs.is_empty() {
-            return Err(Error::new(
-                Status::InvalidArg,
-                "At least one configuration is required".to_string(),
-            ));
-        }
-
-        // Convert each config to a ConditionalRewriterType
-        configs
-            .into_iter()
-            .map(Rewriter::try_from)
-            .collect::<Result<Vec<_>>>()?
-            .try_into()
-    }
-}
-
-//
-// Generic combinators for rewriters
-//
-
-fn then<A, B>(a: A, b: B) -> Rewriter
-where
-    A: Into<Rewriter>,
-    B: Into<Rewriter>,
-{
-    match (a.into().0, b.into().0) {
-        (Either6::A(a), Either6::A(b)) => a.then(b).into(),
-        (Either6::A(a), Either6::B(b)) => a.then(b).into(),
-        (Either6::A(a), Either6::C(b)) => a.then(b).into(),
-        (Either6::A(a), Either6::D(b)) => a.then(b).into(),
-        (Either6::A(a), Either6::E(b)) => a.then(b).into(),
-        (Either6::A(a), Either6::F(b)) => a.then(b).into(),
-
-        (Either6::B(a), Either6::A(b)) => a.then(b).into(),
-        (Either6::B(a), Either6::B(b)) => a.then(b).into(),
-        (Either6::B(a), Either6::C(b)) => a.then(b).into(),
-        (Either6::B(a), Either6::D(b)) => a.then(b).into(),
-        (Either6::B(a), Either6::E(b)) => a.then(b).into(),
-        (Either6::B(a), Either6::F(b)) => a.then(b).into(),
-
-        (Either6::C(a), Either6::A(b)) => a.then(b).into(),
-        (Either6::C(a), Either6::B(b)) => a.then(b).into(),
-        (Either6::C(a), Either6::C(b)) => a.then(b).into(),
-        (Either6::C(a), Either6::D(b)) => a.then(b).into(),
-        (Either6::C(a), Either6::E(b)) => a.then(b).into(),
-        (Either6::C(a), Either6::F(b)) => a.then(b).into(),
-
-        (Either6::D(a), Either6::A(b)) => a.then(b).into(),
-        (Either6::D(a), Either6::B(b)) => a.then(b).into(),
-        (Either6::D(a), Either6::C(b)) => a.then(b).into(),
-        (Either6::D(a), Either6::D(b)) => a.then(b).into(),
-        (Either6::D(a), Either6::E(b)) => a.then(b).into(),
-        (Either6::D(a), Either6::F(b)) => a.then(b).into(),
-
-        (Either6::E(a), Either6::A(b)) => a.then(b).into(),
-        (Either6::E(a), Either6::B(b)) => a.then(b).into(),
-        (Either6::E(a), Either6::C(b)) => a.then(b).into(),
-        (Either6::E(a), Either6::D(b)) => a.then(b).into(),
-        (Either6::E(a), Either6::E(b)) => a.then(b).into(),
-        (Either6::E(a), Either6::F(b)) => a.then(b).into(),
-
-        (Either6::F(a), Either6::A(b)) => a.then(b).into(),
-        (Either6::F(a), Either6::B(b)) => a.then(b).into(),
-        (Either6::F(a), Either6::C(b)) => a.then(b).into(),
-        (Either6::F(a), Either6::D(b)) => a.then(b).into(),
-        (Either6::F(a), Either6::E(b)) => a.then(b).into(),
-        (Either6::F(a), Either6::F(b)) => a.then(b).into(),
-    }
-}
-
-fn when<A, B>(a: A, b: B) -> Rewriter
-where
-    A: Into<Rewriter>,
-    B: Into<Condition>,
-{
-    match (a.into().0, b.into().0) {
-        (Either6::A(path), Either6::A(condition)) => path.when(condition).into(),
-        (Either6::A(path), Either6::B(condition)) => path.when(condition).into(),
-        (Either6::A(path), Either6::C(condition)) => path.when(condition).into(),
-        (Either6::A(path), Either6::D(condition)) => path.when(condition).into(),
-        (Either6::A(path), Either6::E(condition)) => path.when(condition).into(),
-        (Either6::A(path), Either6::F(condition)) => path.when(condition).into(),
-
-        (Either6::B(header), Either6::A(condition)) => header.when(condition).into(),
-        (Either6::B(header), Either6::B(condition)) => header.when(condition).into(),
-        (Either6::B(header), Either6::C(condition)) => header.when(condition).into(),
-        (Either6::B(header), Either6::D(condition)) => header.when(condition).into(),
-        (Either6::B(header), Either6::E(condition)) => header.when(condition).into(),
-        (Either6::B(header), Either6::F(condition)) => header.when(condition).into(),
-
-        (Either6::C(method), Either6::A(condition)) => method.when(condition).into(),
-        (Either6::C(method), Either6::B(condition)) => method.when(condition).into(),
-        (Either6::C(method), Either6::C(condition)) => method.when(condition).into(),
-        (Either6::C(method), Either6::D(condition)) => method.when(condition).into(),
-        (Either6::C(method), Either6::E(condition)) => method.when(condition).into(),
-        (Either6::C(method), Either6::F(condition)) => method.when(condition).into(),
-
-        (Either6::D(href), Either6::A(condition)) => href.when(condition).into(),
-        (Either6::D(href), Either6::B(condition)) => href.when(condition).into(),
-        (Either6::D(href), Either6::C(condition)) => href.when(condition).into(),
-        (Either6::D(href), Either6::D(condition)) => href.when(condition).into(),
-        (Either6::D(href), Either6::E(condition)) => href.when(condition).into(),
-        (Either6::D(href), Either6::F(condition)) => href.when(condition).into(),
-
-        (Either6::E(sequence), Either6::A(condition)) => sequence.when(condition).into(),
-        (Either6::E(sequence), Either6::B(condition)) => sequence.when(condition).into(),
-        (Either6::E(sequence), Either6::C(condition)) => sequence.when(condition).into(),
-        (Either6::E(sequence), Either6::D(condition)) => sequence.when(condition).into(),
-        (Either6::E(sequence), Either6::E(condition)) => sequence.when(condition).into(),
-        (Either6::E(sequence), Either6::F(condition)) => sequence.when(condition).into(),
-
-        (Either6::F(conditional), Either6::A(condition)) => conditional.when(condition).into(),
-        (Either6::F(conditional), Either6::B(condition)) => conditional.when(condition).into(),
-        (Either6::F(conditional), Either6::C(condition)) => conditional.when(condition).into(),
-        (Either6::F(conditional), Either6::D(condition)) => conditional.when(condition).into(),
-        (Either6::F(conditional), Either6::E(condition)) => conditional.when(condition).into(),
-        (Either6::F(conditional), Either6::F(condition)) => conditional.when(condition).into(),
-    }
-}
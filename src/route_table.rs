@@ -0,0 +1,312 @@
+//! Path prefix lookup for large rule sets
+//!
+//! Matching a path against thousands of prefix patterns one regex at a time
+//! gets slow as the rule set grows. This module provides [`RouteTable`], a
+//! radix trie of path prefixes which finds every prefix of a path in a single
+//! walk, in time proportional to the length of the path rather than the
+//! number of prefixes, and [`RouteTableRewriter`], which uses it to pick the
+//! rules of a pipeline to apply.
+//!
+//! Pipelines built from configuration use a route table automatically when
+//! every rule is either unconditional or has a single `Path` condition which
+//! is a plain prefix, such as `^/api/v1/`.
+//!
+//! # Examples
+//!
+//! ```
+//! use http_rewriter::RouteTable;
+//!
+//! let table: RouteTable = ["/", "/api/", "/api/v1/"].into_iter().collect();
+//!
+//! assert_eq!(table.longest_match("/api/v1/users"), Some(2));
+//! assert_eq!(table.longest_match("/api/v2/users"), Some(1));
+//! assert_eq!(table.longest_match("/about"), Some(0));
+//! assert_eq!(table.matching("/api/v1/users").collect::<Vec<_>>(), [0, 1, 2]);
+//! ```
+
+use http::Request;
+
+use crate::{Change, Condition, RewriteContext, RewriteError, Rewriter};
+
+/// Radix trie mapping path prefixes to rule indices
+///
+/// Each prefix is stored with the index of the rule it belongs to. Prefixes
+/// are compared byte for byte against the raw request path, just like a
+/// [`PathCondition`](crate::PathCondition) pattern of the form `^prefix`, so
+/// `/api` is a prefix of `/apiary` as well as of `/api/users`. End prefixes
+/// with a `/` to match whole segments only.
+///
+/// Several rules may share a prefix. Lookups walk the trie once along the
+/// path, so their cost depends on the length of the path, not on the number
+/// of prefixes in the table.
+///
+/// As a [`Condition`], the table matches requests whose path starts with any
+/// of its prefixes.
+///
+/// # Examples
+///
+/// ```
+/// use http_rewriter::{Condition, RouteTable};
+/// use http::Request;
+///
+/// let mut table = RouteTable::new();
+/// table.insert("/static/", 0);
+/// table.insert("/assets/", 1);
+///
+/// let request = Request::builder().uri("/assets/app.css").body(()).unwrap();
+/// assert!(table.matches(&request));
+/// assert_eq!(table.longest_match("/assets/app.css"), Some(1));
+///
+/// let request = Request::builder().uri("/api/users").body(()).unwrap();
+/// assert!(!table.matches(&request));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct RouteTable {
+    root: Node,
+    len: usize,
+}
+
+#[derive(Debug, Clone, Default)]
+struct Node {
+    // Indices of the rules whose prefix ends at this node, in insertion order
+    indices: Vec<usize>,
+    // Edges sorted by the first byte of their label, which is never empty
+    children: Vec<(Box<[u8]>, Node)>,
+}
+
+impl RouteTable {
+    /// Create an empty route table
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a prefix for the rule with the given index
+    ///
+    /// # Arguments
+    ///
+    /// * `prefix` - The path prefix, compared byte for byte
+    /// * `index` - The index of the rule the prefix belongs to
+    pub fn insert(&mut self, prefix: impl AsRef<str>, index: usize) {
+        let mut key = prefix.as_ref().as_bytes();
+        let mut node = &mut self.root;
+        while let Some(&first) = key.first() {
+            match node
+                .children
+                .binary_search_by_key(&first, |(label, _)| label[0])
+            {
+                Err(position) => {
+                    node.children
+                        .insert(position, (key.into(), Node::default()));
+                    node = &mut node.children[position].1;
+                    key = &[];
+                }
+                Ok(position) => {
+                    let label = &node.children[position].0;
+                    let common = label.iter().zip(key).take_while(|(a, b)| a == b).count();
+                    if common < label.len() {
+                        // Split the edge where the new prefix leaves it
+                        let (label, child) = std::mem::take(&mut node.children[position]);
+                        let middle = Node {
+                            indices: Vec::new(),
+                            children: vec![(label[common..].into(), child)],
+                        };
+                        node.children[position] = (label[..common].into(), middle);
+                    }
+                    node = &mut node.children[position].1;
+                    key = &key[common..];
+                }
+            }
+        }
+        node.indices.push(index);
+        self.len += 1;
+    }
+
+    /// Get the rule index of the longest prefix of the path
+    ///
+    /// When several rules share that prefix, the one inserted first is
+    /// returned.
+    pub fn longest_match(&self, path: &str) -> Option<usize> {
+        self.nodes(path)
+            .filter_map(|node| node.indices.first().copied())
+            .last()
+    }
+
+    /// Get the rule indices of every prefix of the path
+    ///
+    /// Indices are returned from the shortest prefix to the longest, and in
+    /// insertion order for rules sharing a prefix.
+    pub fn matching<'a>(&'a self, path: &'a str) -> impl Iterator<Item = usize> + 'a {
+        self.nodes(path)
+            .flat_map(|node| node.indices.iter().copied())
+    }
+
+    /// Get the number of prefixes in the table
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Check whether the table holds no prefixes
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    // Walk the nodes along the path, from the root to the deepest one reached
+    fn nodes<'a>(&'a self, path: &'a str) -> impl Iterator<Item = &'a Node> {
+        std::iter::successors(Some((&self.root, path.as_bytes())), |&(node, rest)| {
+            let &first = rest.first()?;
+            let position = node
+                .children
+                .binary_search_by_key(&first, |(label, _)| label[0])
+                .ok()?;
+            let (label, child) = &node.children[position];
+            Some((child, rest.strip_prefix(&**label)?))
+        })
+        .map(|(node, _)| node)
+    }
+}
+
+impl<P: AsRef<str>> FromIterator<P> for RouteTable {
+    /// Build a table whose rule indices are the positions of the prefixes
+    fn from_iter<I: IntoIterator<Item = P>>(prefixes: I) -> Self {
+        let mut table = Self::new();
+        for (index, prefix) in prefixes.into_iter().enumerate() {
+            table.insert(prefix, index);
+        }
+        table
+    }
+}
+
+impl Condition for RouteTable {
+    fn matches<B>(&self, request: &Request<B>) -> bool {
+        self.nodes(request.uri().path())
+            .any(|node| !node.indices.is_empty())
+    }
+}
+
+/// Rewriter that applies the rules whose path prefix matches the request
+///
+/// Each rule is paired with a path prefix. Rules are applied in order, each
+/// one only when the path, as rewritten by the rules before it, starts with
+/// its prefix. This is exactly what a list of rules each wrapped in
+/// `.when(PathCondition::new("^prefix"))` does, but the rules to apply are
+/// found through a [`RouteTable`], so rules which cannot match cost nothing.
+/// An empty prefix matches every path.
+///
+/// # Examples
+///
+/// ```
+/// use http_rewriter::{PathRewriter, RouteTableRewriter, Rewriter};
+/// use http::Request;
+///
+/// let rewriter = RouteTableRewriter::new([
+///     ("/legacy/", PathRewriter::new("^/legacy/", "/api/").unwrap()),
+///     ("/api/", PathRewriter::new("^/api/", "/backend/api/").unwrap()),
+///     ("/static/", PathRewriter::new("^/static/", "/cdn/").unwrap()),
+/// ]);
+///
+/// // The first rule makes the second one apply as well
+/// let request = Request::builder().uri("/legacy/users").body(()).unwrap();
+/// let result = rewriter.rewrite(request).unwrap();
+/// assert_eq!(result.uri().path(), "/backend/api/users");
+/// ```
+#[derive(Debug, Clone)]
+pub struct RouteTableRewriter<R> {
+    table: RouteTable,
+    rules: Vec<R>,
+}
+
+impl<R: Rewriter> RouteTableRewriter<R> {
+    /// Create a new route table rewriter from prefix and rule pairs
+    ///
+    /// # Arguments
+    ///
+    /// * `rules` - Path prefix and rule pairs, applied in order
+    pub fn new<P: AsRef<str>>(rules: impl IntoIterator<Item = (P, R)>) -> Self {
+        let (prefixes, rules): (Vec<P>, Vec<R>) = rules.into_iter().unzip();
+        Self {
+            table: prefixes.into_iter().collect(),
+            rules,
+        }
+    }
+
+    /// Get the rules, in the order they are applied
+    pub fn rules(&self) -> &[R] {
+        &self.rules
+    }
+
+    // Apply each rule whose prefix matches with the given function
+    fn apply<B>(
+        &self,
+        mut request: Request<B>,
+        mut rewrite: impl FnMut(&R, Request<B>) -> Result<Request<B>, RewriteError>,
+    ) -> Result<Request<B>, RewriteError> {
+        // Look the path up again after each rule, as the rule may change it
+        let mut next = 0;
+        loop {
+            let index = self
+                .table
+                .matching(request.uri().path())
+                .filter(|&index| index >= next)
+                .min();
+            let Some(index) = index else {
+                return Ok(request);
+            };
+            request = rewrite(&self.rules[index], request)?;
+            next = index + 1;
+        }
+    }
+}
+
+impl<R: Rewriter> Rewriter for RouteTableRewriter<R> {
+    fn rewrite<B>(&self, request: Request<B>) -> Result<Request<B>, RewriteError> {
+        self.rewrite_with_context(request, &mut RewriteContext::new())
+    }
+
+    fn rewrite_with_context<B>(
+        &self,
+        request: Request<B>,
+        context: &mut RewriteContext,
+    ) -> Result<Request<B>, RewriteError> {
+        self.apply(request, |rule, request| {
+            rule.rewrite_with_context(request, context)
+        })
+    }
+
+    fn rewrite_with_context_and_log<B>(
+        &self,
+        request: Request<B>,
+        context: &mut RewriteContext,
+        log: &mut Vec<Change>,
+    ) -> Result<Request<B>, RewriteError> {
+        self.apply(request, |rule, request| {
+            rule.rewrite_with_context_and_log(request, context, log)
+        })
+    }
+
+    fn patterns(&self) -> Vec<&str> {
+        self.rules.iter().flat_map(Rewriter::patterns).collect()
+    }
+}
+
+/// Get the literal prefix a path pattern of the form `^prefix` matches
+///
+/// Returns `None` when the pattern is not anchored at the start, or uses
+/// anything other than literal characters and escaped punctuation.
+pub(crate) fn literal_prefix(pattern: &str) -> Option<String> {
+    let mut chars = pattern.strip_prefix('^')?.chars();
+    let mut prefix = String::new();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => match chars.next()? {
+                // `\<` and `\>` are word boundaries, not literals
+                c if c.is_ascii_punctuation() && c != '<' && c != '>' => prefix.push(c),
+                _ => return None,
+            },
+            '.' | '+' | '*' | '?' | '(' | ')' | '|' | '[' | ']' | '{' | '}' | '^' | '$' => {
+                return None;
+            }
+            c => prefix.push(c),
+        }
+    }
+    Some(prefix)
+}
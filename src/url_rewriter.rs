@@ -0,0 +1,269 @@
+//! Structured URL rewriting using the `url` crate
+//!
+//! This module provides the [`UrlRewriter`] type, which parses the effective
+//! URL of a request into a [`url::Url`], lets you edit it, and writes the
+//! result back into the request. This avoids the pitfalls of regex-based
+//! rewriting when changing individual parts such as the host or port.
+//!
+//! This module is only available with the `url` feature enabled.
+//!
+//! # Examples
+//!
+//! ```
+//! use http_rewriter::{Rewriter, UrlRewriter};
+//! use http::Request;
+//!
+//! // Move requests over to the https port
+//! let rewriter = UrlRewriter::builder()
+//!     .scheme("https")
+//!     .port(Some(8443))
+//!     .build();
+//!
+//! let request = Request::builder()
+//!     .uri("http://example.com:8080/api/users?page=2")
+//!     .body(())
+//!     .unwrap();
+//!
+//! let result = rewriter.rewrite(request).unwrap();
+//! assert_eq!(result.uri().to_string(), "https://example.com:8443/api/users?page=2");
+//! ```
+
+use std::fmt::{Debug, Formatter, Result as FmtResult};
+use std::sync::Arc;
+
+use http::{HeaderValue, Request, header::HOST, request::Parts};
+use url::{Position, Url};
+
+use super::{RewriteError, Rewriter};
+
+type UrlEdit = dyn Fn(&mut Url) -> Result<(), RewriteError> + Send + Sync;
+
+/// Rewriter that edits the request URL as a structured [`url::Url`]
+///
+/// The effective URL of the request is built from the request URI when it is
+/// absolute, or from the `Host` header and the request path otherwise. After
+/// the edit the URL is written back in the same form: absolute URIs stay
+/// absolute, and relative URIs stay relative with the `Host` header updated
+/// to the new host and port.
+///
+/// If the effective URL can not be determined, such as for a relative URI
+/// without a `Host` header, rewriting fails with a [`RewriteError`].
+///
+/// # Examples
+///
+/// ```
+/// use http_rewriter::{Rewriter, UrlRewriter, RewriteError};
+/// use http::Request;
+///
+/// // Edit the URL with a closure
+/// let rewriter = UrlRewriter::new(|url| {
+///     url.query_pairs_mut().append_pair("source", "proxy");
+///     Ok(())
+/// });
+///
+/// let request = Request::builder()
+///     .uri("/search?q=rust")
+///     .header("Host", "example.com")
+///     .body(())
+///     .unwrap();
+///
+/// let result = rewriter.rewrite(request).unwrap();
+/// assert_eq!(result.uri().to_string(), "/search?q=rust&source=proxy");
+/// ```
+#[derive(Clone)]
+pub struct UrlRewriter {
+    edit: Arc<UrlEdit>,
+}
+
+impl UrlRewriter {
+    /// Create a new URL rewriter which edits the URL with the given closure
+    ///
+    /// # Arguments
+    ///
+    /// * `edit` - Closure which modifies the parsed URL in place
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use http_rewriter::{UrlRewriter, RewriteError};
+    ///
+    /// let rewriter = UrlRewriter::new(|url| {
+    ///     url.set_host(Some("internal.example.com"))
+    ///         .map_err(|e| RewriteError::new(e.to_string()))
+    /// });
+    /// ```
+    pub fn new<F>(edit: F) -> Self
+    where
+        F: Fn(&mut Url) -> Result<(), RewriteError> + Send + Sync + 'static,
+    {
+        Self {
+            edit: Arc::new(edit),
+        }
+    }
+
+    /// Create a builder for a URL rewriter which sets individual URL parts
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use http_rewriter::UrlRewriter;
+    ///
+    /// let rewriter = UrlRewriter::builder()
+    ///     .host("internal.example.com")
+    ///     .port(None)
+    ///     .build();
+    /// ```
+    pub fn builder() -> UrlRewriterBuilder {
+        UrlRewriterBuilder::default()
+    }
+}
+
+impl Debug for UrlRewriter {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        f.debug_struct("UrlRewriter").finish_non_exhaustive()
+    }
+}
+
+impl Rewriter for UrlRewriter {
+    fn rewrite<B>(&self, request: Request<B>) -> Result<Request<B>, RewriteError> {
+        let (mut parts, body) = request.into_parts();
+
+        let original = effective_url(&parts)?;
+        let mut url = original.clone();
+        (self.edit)(&mut url)?;
+
+        if url != original {
+            let href = if parts.uri.scheme().is_some() {
+                &url[..Position::AfterQuery]
+            } else {
+                &url[Position::BeforePath..Position::AfterQuery]
+            };
+            parts.uri = href
+                .parse()
+                .map_err(|_| RewriteError::new("Invalid URI after url rewrite"))?;
+
+            let authority = &url[Position::BeforeHost..Position::AfterPort];
+            if authority != &original[Position::BeforeHost..Position::AfterPort]
+                && parts.headers.contains_key(HOST)
+            {
+                let host = HeaderValue::from_str(authority)
+                    .map_err(|_| RewriteError::new("Invalid host after url rewrite"))?;
+                parts.headers.insert(HOST, host);
+            }
+        }
+
+        Ok(Request::from_parts(parts, body))
+    }
+}
+
+/// Build the effective URL of a request from its URI and `Host` header
+fn effective_url(parts: &Parts) -> Result<Url, RewriteError> {
+    let href = if parts.uri.scheme().is_some() && parts.uri.authority().is_some() {
+        parts.uri.to_string()
+    } else {
+        let host = parts
+            .headers
+            .get(HOST)
+            .and_then(|host| host.to_str().ok())
+            .ok_or_else(|| RewriteError::new("Request has no host to build a URL from"))?;
+        let path_and_query = parts
+            .uri
+            .path_and_query()
+            .map(|pq| pq.as_str())
+            .unwrap_or("/");
+        format!("http://{host}{path_and_query}")
+    };
+
+    Url::parse(&href).map_err(|e| RewriteError::new(format!("Invalid request URL: {e}")))
+}
+
+/// Builder for a [`UrlRewriter`] which sets individual URL parts
+///
+/// Created with [`UrlRewriter::builder`]. Parts which are not set are left
+/// unchanged.
+///
+/// # Examples
+///
+/// ```
+/// use http_rewriter::{Rewriter, UrlRewriter};
+/// use http::Request;
+///
+/// // Send everything to a maintenance page, dropping the query
+/// let rewriter = UrlRewriter::builder()
+///     .path("/maintenance.html")
+///     .query(None)
+///     .build();
+///
+/// let request = Request::builder()
+///     .uri("https://example.com/api/users?page=2")
+///     .body(())
+///     .unwrap();
+///
+/// let result = rewriter.rewrite(request).unwrap();
+/// assert_eq!(result.uri().to_string(), "https://example.com/maintenance.html");
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct UrlRewriterBuilder {
+    scheme: Option<String>,
+    host: Option<String>,
+    port: Option<Option<u16>>,
+    path: Option<String>,
+    query: Option<Option<String>>,
+}
+
+impl UrlRewriterBuilder {
+    /// Set the URL scheme, such as `https`
+    pub fn scheme(mut self, scheme: impl Into<String>) -> Self {
+        self.scheme = Some(scheme.into());
+        self
+    }
+
+    /// Set the URL host
+    pub fn host(mut self, host: impl Into<String>) -> Self {
+        self.host = Some(host.into());
+        self
+    }
+
+    /// Set the URL port, or remove an explicit port with `None`
+    pub fn port(mut self, port: Option<u16>) -> Self {
+        self.port = Some(port);
+        self
+    }
+
+    /// Set the URL path
+    pub fn path(mut self, path: impl Into<String>) -> Self {
+        self.path = Some(path.into());
+        self
+    }
+
+    /// Set the URL query, or remove it with `None`
+    pub fn query(mut self, query: Option<&str>) -> Self {
+        self.query = Some(query.map(str::to_string));
+        self
+    }
+
+    /// Build the URL rewriter
+    pub fn build(self) -> UrlRewriter {
+        UrlRewriter::new(move |url| {
+            if let Some(scheme) = &self.scheme {
+                url.set_scheme(scheme)
+                    .map_err(|_| RewriteError::new(format!("Invalid URL scheme: {scheme}")))?;
+            }
+            if let Some(host) = &self.host {
+                url.set_host(Some(host))
+                    .map_err(|e| RewriteError::new(format!("Invalid URL host: {e}")))?;
+            }
+            if let Some(port) = self.port {
+                url.set_port(port)
+                    .map_err(|_| RewriteError::new("URL can not have a port"))?;
+            }
+            if let Some(path) = &self.path {
+                url.set_path(path);
+            }
+            if let Some(query) = &self.query {
+                url.set_query(query.as_deref());
+            }
+            Ok(())
+        })
+    }
+}
@@ -1,7 +1,7 @@
-import { Request, PathCondition, HeaderCondition, MethodCondition, ExistenceCondition, NonExistenceCondition, GroupCondition } from '../index.js'
+import { Request, PathCondition, HeaderCondition, MethodCondition, ExistenceCondition, NonExistenceCondition, HeaderAbsentCondition, AuthSchemeCondition, LanguageCondition, ForwardedForCountCondition, PathGlobCondition, ForwardedCondition, HeaderNameExistsCondition, RequiredHeadersCondition, HeaderCompareCondition, TrailingSlashCondition, TargetLengthCondition, QueryFlagCondition, CorsPreflightCondition, HostAllowlistCondition, DocumentRootCondition, DuplicateHeaderCondition, TraversalCondition, AcceptEncodingCondition, GroupCondition } from '../index.js'
 
 import { fileURLToPath } from 'node:url'
-import { ok } from 'node:assert/strict'
+import { ok, throws } from 'node:assert/strict'
 import { test } from 'node:test'
 
 const docroot = fileURLToPath(new URL('.', import.meta.url))
@@ -93,13 +93,390 @@ test('NonExistenceCondition', async () => {
   ok(!nonExistenceCondition.matches(notMatchingRequest), 'should not match Request with existing path')
 })
 
+test('HeaderAbsentCondition', async () => {
+  const matchingRequest = new Request({
+    url: '/test/foo'
+  })
+  const notMatchingRequest = new Request({
+    url: '/test/foo',
+    headers: {
+      'X-Request-Id': 'abc123'
+    }
+  })
+
+  const headerAbsentCondition = new HeaderAbsentCondition('x-request-id')
+
+  ok(headerAbsentCondition instanceof HeaderAbsentCondition, 'should create HeaderAbsentCondition instance')
+  ok(headerAbsentCondition.matches(matchingRequest), 'should match Request without the header')
+  ok(!headerAbsentCondition.matches(notMatchingRequest), 'should not match Request with the header')
+})
+
+test('AuthSchemeCondition', async () => {
+  const bearerRequest = new Request({
+    url: '/test/foo',
+    headers: {
+      'Authorization': 'Bearer xyz'
+    }
+  })
+  const basicRequest = new Request({
+    url: '/test/foo',
+    headers: {
+      'Authorization': 'Basic abc'
+    }
+  })
+  const missingRequest = new Request({
+    url: '/test/foo'
+  })
+
+  const authSchemeCondition = new AuthSchemeCondition('bearer')
+
+  ok(authSchemeCondition instanceof AuthSchemeCondition, 'should create AuthSchemeCondition instance')
+  ok(authSchemeCondition.matches(bearerRequest), 'should match Request with the auth scheme')
+  ok(!authSchemeCondition.matches(basicRequest), 'should not match Request with another auth scheme')
+  ok(!authSchemeCondition.matches(missingRequest), 'should not match Request without Authorization header')
+})
+
+test('LanguageCondition', async () => {
+  const request = new Request({
+    url: '/test/foo',
+    headers: {
+      'Accept-Language': 'en-US,en;q=0.9,fr;q=0.8'
+    }
+  })
+  const missingRequest = new Request({
+    url: '/test/foo'
+  })
+
+  const englishCondition = new LanguageCondition('^en')
+  const frenchCondition = new LanguageCondition('^fr')
+  const anyFrenchCondition = new LanguageCondition('^fr', true)
+
+  ok(englishCondition instanceof LanguageCondition, 'should create LanguageCondition instance')
+  ok(englishCondition.matches(request), 'should match the most preferred language')
+  ok(!frenchCondition.matches(request), 'should not match a less preferred language')
+  ok(anyFrenchCondition.matches(request), 'should match any acceptable language when requested')
+  ok(!englishCondition.matches(missingRequest), 'should not match Request without Accept-Language')
+})
+
+test('ForwardedForCountCondition', async () => {
+  const directRequest = new Request({
+    url: '/test/foo'
+  })
+  const oneHopRequest = new Request({
+    url: '/test/foo',
+    headers: {
+      'X-Forwarded-For': '203.0.113.7'
+    }
+  })
+  const threeHopRequest = new Request({
+    url: '/test/foo',
+    headers: {
+      'X-Forwarded-For': '203.0.113.7, 10.0.0.2, 10.0.0.3'
+    }
+  })
+
+  const multiHopCondition = new ForwardedForCountCondition('gt', 1)
+
+  ok(multiHopCondition instanceof ForwardedForCountCondition, 'should create ForwardedForCountCondition instance')
+  ok(!multiHopCondition.matches(directRequest), 'should count a missing header as zero hops')
+  ok(!multiHopCondition.matches(oneHopRequest), 'should not match a single hop')
+  ok(multiHopCondition.matches(threeHopRequest), 'should match three hops')
+  throws(() => new ForwardedForCountCondition('more', 1), 'should reject an unknown comparison')
+})
+
+test('PathGlobCondition', async () => {
+  const topLevelRequest = new Request({
+    url: '/assets/site.css'
+  })
+  const nestedRequest = new Request({
+    url: '/assets/themes/dark/site.css'
+  })
+
+  const singleSegmentCondition = new PathGlobCondition('/assets/*.css')
+  const multiSegmentCondition = new PathGlobCondition('/assets/**/*.css')
+
+  ok(singleSegmentCondition instanceof PathGlobCondition, 'should create PathGlobCondition instance')
+  ok(singleSegmentCondition.matches(topLevelRequest), 'should match within a single segment')
+  ok(!singleSegmentCondition.matches(nestedRequest), 'should not let * cross segments')
+  ok(multiSegmentCondition.matches(topLevelRequest), 'should let **/ match no segments')
+  ok(multiSegmentCondition.matches(nestedRequest), 'should let ** cross segments')
+})
+
+test('ForwardedCondition', async () => {
+  const httpsRequest = new Request({
+    url: '/test/foo',
+    headers: {
+      Forwarded: 'for=1.2.3.4;proto=https;host=example.com'
+    }
+  })
+  const httpRequest = new Request({
+    url: '/test/foo',
+    headers: {
+      Forwarded: 'for=1.2.3.4;proto=http'
+    }
+  })
+  const plainRequest = new Request({
+    url: '/test/foo'
+  })
+
+  const httpsCondition = new ForwardedCondition('proto', '^https$')
+  const hostCondition = new ForwardedCondition('host', '^example\\.com$')
+
+  ok(httpsCondition instanceof ForwardedCondition, 'should create ForwardedCondition instance')
+  ok(httpsCondition.matches(httpsRequest), 'should match the proto parameter')
+  ok(!httpsCondition.matches(httpRequest), 'should not match a different proto')
+  ok(!httpsCondition.matches(plainRequest), 'should not match Request without Forwarded')
+  ok(hostCondition.matches(httpsRequest), 'should match the host parameter')
+  throws(() => new ForwardedCondition('by', '.*'), 'should reject an unknown parameter')
+})
+
+test('HeaderNameExistsCondition', async () => {
+  const debugRequest = new Request({
+    url: '/test/foo',
+    headers: {
+      'X-Debug-Level': 'verbose'
+    }
+  })
+  const plainRequest = new Request({
+    url: '/test/foo',
+    headers: {
+      'X-Request-Id': 'abc'
+    }
+  })
+
+  const debugCondition = new HeaderNameExistsCondition('^x-debug-')
+
+  ok(debugCondition instanceof HeaderNameExistsCondition, 'should create HeaderNameExistsCondition instance')
+  ok(debugCondition.matches(debugRequest), 'should match Request with a matching header name')
+  ok(!debugCondition.matches(plainRequest), 'should not match Request without a matching header name')
+  throws(() => new HeaderNameExistsCondition('('), 'should reject an invalid pattern')
+})
+
+test('RequiredHeadersCondition', async () => {
+  const completeRequest = new Request({
+    url: '/test/foo',
+    headers: {
+      Authorization: 'Bearer abc123',
+      'X-Tenant-Id': 'acme'
+    }
+  })
+  const partialRequest = new Request({
+    url: '/test/foo',
+    headers: {
+      Authorization: 'Bearer abc123'
+    }
+  })
+
+  const missingCondition = new RequiredHeadersCondition(['Authorization', 'X-Tenant-Id'])
+
+  ok(missingCondition instanceof RequiredHeadersCondition, 'should create RequiredHeadersCondition instance')
+  ok(!missingCondition.matches(completeRequest), 'should not match Request with every header')
+  ok(missingCondition.matches(partialRequest), 'should match Request missing one header')
+})
+
+test('HeaderCompareCondition', async () => {
+  const equalRequest = new Request({
+    url: '/test/foo',
+    headers: {
+      Host: 'example.com',
+      'X-Forwarded-Host': 'Example.com'
+    }
+  })
+  const missingRequest = new Request({
+    url: '/test/foo',
+    headers: {
+      Host: 'example.com'
+    }
+  })
+
+  const exactCondition = new HeaderCompareCondition('Host', 'X-Forwarded-Host')
+  const caseCondition = new HeaderCompareCondition('Host', 'X-Forwarded-Host', true)
+
+  ok(exactCondition instanceof HeaderCompareCondition, 'should create HeaderCompareCondition instance')
+  ok(!exactCondition.matches(equalRequest), 'should compare values exactly by default')
+  ok(caseCondition.matches(equalRequest), 'should match values differing only in case')
+  ok(!caseCondition.matches(missingRequest), 'should not match when a header is missing')
+})
+
+test('TrailingSlashCondition', async () => {
+  const slashRequest = new Request({ url: '/docs/' })
+  const noSlashRequest = new Request({ url: '/docs' })
+  const rootRequest = new Request({ url: '/' })
+
+  const present = new TrailingSlashCondition(true)
+  const absent = new TrailingSlashCondition(false)
+
+  ok(present instanceof TrailingSlashCondition, 'should create TrailingSlashCondition instance')
+  ok(present.matches(slashRequest), 'should match path with trailing slash')
+  ok(!present.matches(noSlashRequest), 'should not match path without trailing slash')
+  ok(absent.matches(noSlashRequest), 'should match path without trailing slash when absent')
+  ok(present.matches(rootRequest), 'should count the root as having a trailing slash')
+  ok(!absent.matches(rootRequest), 'should not match the root when absent')
+})
+
+test('TargetLengthCondition', async () => {
+  const shortRequest = new Request({ url: '/search?q=rust' })
+  const longRequest = new Request({ url: '/search?q=rusty' })
+
+  const condition = new TargetLengthCondition('gt', 14)
+
+  ok(condition instanceof TargetLengthCondition, 'should create TargetLengthCondition instance')
+  ok(!condition.matches(shortRequest), 'should not match target at the limit')
+  ok(condition.matches(longRequest), 'should match target over the limit')
+  throws(() => new TargetLengthCondition('bigger', 14), 'should reject unknown comparisons')
+})
+
+test('QueryFlagCondition', async () => {
+  const condition = new QueryFlagCondition('beta')
+
+  ok(condition instanceof QueryFlagCondition, 'should create QueryFlagCondition instance')
+  for (const url of ['/?beta', '/?beta=', '/?beta=1', '/?beta=True', '/?beta=yes', '/?beta=ON']) {
+    ok(condition.matches(new Request({ url })), `should match ${url}`)
+  }
+  for (const url of ['/', '/?beta=0', '/?beta=false', '/?beta=maybe', '/?alpha=1']) {
+    ok(!condition.matches(new Request({ url })), `should not match ${url}`)
+  }
+})
+
+test('CorsPreflightCondition', async () => {
+  const preflight = new Request({
+    method: 'OPTIONS',
+    url: '/api/users',
+    headers: {
+      Origin: 'https://app.example.com',
+      'Access-Control-Request-Method': 'PUT'
+    }
+  })
+  const plainOptions = new Request({ method: 'OPTIONS', url: '/api/users' })
+  const get = new Request({
+    method: 'GET',
+    url: '/api/users',
+    headers: { 'Access-Control-Request-Method': 'PUT' }
+  })
+
+  const condition = new CorsPreflightCondition()
+
+  ok(condition instanceof CorsPreflightCondition, 'should create CorsPreflightCondition instance')
+  ok(condition.matches(preflight), 'should match a preflight request')
+  ok(!condition.matches(plainOptions), 'should not match a plain OPTIONS request')
+  ok(!condition.matches(get), 'should not match other methods')
+})
+
+test('HostAllowlistCondition', async () => {
+  const apex = new Request({ method: 'GET', url: '/', headers: { Host: 'example.com' } })
+  const subdomain = new Request({ method: 'GET', url: '/', headers: { Host: 'api.example.com' } })
+  const other = new Request({ method: 'GET', url: '/', headers: { Host: 'evil.test' } })
+
+  const allow = new HostAllowlistCondition(['example.com', '*.example.com'], 'allow')
+
+  ok(allow instanceof HostAllowlistCondition, 'should create HostAllowlistCondition instance')
+  ok(!allow.matches(apex), 'should not match an allowed host')
+  ok(!allow.matches(subdomain), 'should not match an allowed wildcard host')
+  ok(allow.matches(other), 'should match a host outside the allow-list')
+
+  const deny = new HostAllowlistCondition(['evil.test'], 'deny')
+
+  ok(deny.matches(other), 'should match a denied host')
+  ok(!deny.matches(apex), 'should not match other hosts')
+
+  throws(() => new HostAllowlistCondition(['example.com'], 'maybe'), 'should reject an invalid mode')
+})
+
+test('DocumentRootCondition', async () => {
+  const tenantRequest = new Request({
+    url: '/index.php',
+    docroot: '/srv/tenants/acme/public'
+  })
+  const otherRequest = new Request({
+    url: '/index.php',
+    docroot: '/srv/tenants/globex/public'
+  })
+  const noRootRequest = new Request({ url: '/index.php' })
+
+  const condition = new DocumentRootCondition('^/srv/tenants/acme/')
+
+  ok(condition instanceof DocumentRootCondition, 'should create DocumentRootCondition instance')
+  ok(condition.matches(tenantRequest), 'should match a matching document root')
+  ok(!condition.matches(otherRequest), 'should not match another document root')
+  ok(!condition.matches(noRootRequest), 'should not match without a document root')
+  throws(() => new DocumentRootCondition('[unclosed'), 'should reject an invalid pattern')
+})
+
+test('DuplicateHeaderCondition', async () => {
+  const single = new Request({
+    method: 'POST',
+    url: '/upload',
+    headers: { 'Content-Length': '5' }
+  })
+  const missing = new Request({ method: 'POST', url: '/upload' })
+
+  const condition = new DuplicateHeaderCondition('Content-Length')
+
+  ok(condition instanceof DuplicateHeaderCondition, 'should create DuplicateHeaderCondition instance')
+  ok(!condition.matches(single), 'should not match a single header')
+  ok(!condition.matches(missing), 'should not match a missing header')
+})
+
+test('TraversalCondition', async () => {
+  const condition = new TraversalCondition()
+
+  ok(condition instanceof TraversalCondition, 'should create TraversalCondition instance')
+  for (const url of ['/static/%2e%2e%2fsecret', '/static/..%2Fsecret', '/static/../secret', '/download?file=../etc/passwd', '/static/..;/secret', '/static/..%3b/secret']) {
+    ok(condition.matches(new Request({ url })), `should match ${url}`)
+  }
+  for (const url of ['/static/app.js', '/static/app.v2..min.js', '/static/.../file', '/search?q=%2e']) {
+    ok(!condition.matches(new Request({ url })), `should not match ${url}`)
+  }
+})
+
+test('AcceptEncodingCondition', async () => {
+  const request = new Request({
+    url: '/app.js',
+    headers: { 'Accept-Encoding': 'gzip;q=0, br' }
+  })
+
+  const gzip = new AcceptEncodingCondition('gzip')
+  const br = new AcceptEncodingCondition('br')
+
+  ok(gzip instanceof AcceptEncodingCondition, 'should create AcceptEncodingCondition instance')
+  ok(!gzip.matches(request), 'should not match a codec excluded with q=0')
+  ok(br.matches(request), 'should match a listed codec')
+  ok(new AcceptEncodingCondition('zstd').matches(new Request({
+    url: '/app.js',
+    headers: { 'Accept-Encoding': 'gzip, *;q=0.1' }
+  })), 'should match a codec allowed by the wildcard')
+  ok(!gzip.matches(new Request({
+    url: '/app.js',
+    headers: { 'Accept-Encoding': 'gzip;Q=0, br' }
+  })), 'should read the q parameter ignoring case')
+  ok(br.matches(new Request({ url: '/app.js' })), 'should match without the header')
+})
+
 test('combinators', async () => {
   const conditionFactories = [
     () => new HeaderCondition('X-Test-Header', '^test'),
     () => new PathCondition('^/test'),
     () => new MethodCondition('GET'),
     () => new ExistenceCondition(),
-    () => new NonExistenceCondition()
+    () => new NonExistenceCondition(),
+    () => new HeaderAbsentCondition('X-Missing-Header'),
+    () => new AuthSchemeCondition('Bearer'),
+    () => new LanguageCondition('^en'),
+    () => new ForwardedForCountCondition('gt', 1),
+    () => new PathGlobCondition('/test/*'),
+    () => new ForwardedCondition('proto', '^https$'),
+    () => new HeaderNameExistsCondition('^x-debug-'),
+    () => new RequiredHeadersCondition(['X-Missing-Header']),
+    () => new HeaderCompareCondition('X-Test-Header', 'X-Other-Header'),
+    () => new TrailingSlashCondition(true),
+    () => new TargetLengthCondition('gt', 8192),
+    () => new QueryFlagCondition('beta'),
+    () => new CorsPreflightCondition(),
+    () => new HostAllowlistCondition,
+    () => new DocumentRootCondition,
+    () => new DuplicateHeaderCondition,
+    () => new TraversalCondition(),
+    () => new AcceptEncodingCondition('br')
   ]
 
   // For each condition type, try combining it with itself and every other condition type
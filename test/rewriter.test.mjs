@@ -1,6 +1,6 @@
-import { Request, PathRewriter, HeaderRewriter, MethodRewriter, HrefRewriter, HeaderCondition, MethodCondition, SequenceRewriter, ConditionalRewriter, Rewriter } from '../index.js'
+import { Request, PathRewriter, HeaderRewriter, MethodRewriter, PortRewriter, HrefRewriter, PathAndQueryRewriter, HeaderCondition, MethodCondition, SequenceRewriter, ConditionalRewriter, Rewriter } from '../index.js'
 
-import { ok, strictEqual } from 'node:assert/strict'
+import { ok, strictEqual, throws } from 'node:assert/strict'
 import { test } from 'node:test'
 
 test('PathRewriter', async () => {
@@ -52,6 +52,16 @@ test('MethodRewriter', async () => {
   strictEqual(rewritten.method, 'GET', 'should rewrite the method correctly')
 })
 
+test('PortRewriter', async () => {
+  const portRewriter = new PortRewriter(8080)
+
+  const added = portRewriter.rewrite(new Request({ url: 'http://example.com/foo?bar=1' }))
+  strictEqual(added.url, 'http://example.com:8080/foo?bar=1', 'should add a missing port')
+
+  const replaced = portRewriter.rewrite(new Request({ url: 'http://example.com:3000/foo' }))
+  strictEqual(replaced.url, 'http://example.com:8080/foo', 'should replace an existing port')
+})
+
 test('HrefRewriter', async () => {
   const request = new Request({
     url: 'http://example.com/foo/bar'
@@ -66,6 +76,21 @@ test('HrefRewriter', async () => {
   strictEqual(rewritten.url, 'http://example.com/index.php?route=/foo/bar', 'should rewrite the path correctly')
 })
 
+test('PathAndQueryRewriter', async () => {
+  const rewriter = new PathAndQueryRewriter('^/(\\w+)\\?x=(\\d+)$', '/$2?x=$1')
+  ok(rewriter instanceof PathAndQueryRewriter, 'should create PathAndQueryRewriter instance')
+
+  const rewritten = rewriter.rewrite(new Request({ url: '/old?x=1' }))
+  strictEqual(rewritten.url, '/1?x=old', 'should rewrite path and query together')
+
+  const fromConfig = new Rewriter([{
+    rewriters: [
+      { type: 'path_and_query', args: ['^/old\\?x=1$', '/new?x=2'] }
+    ]
+  }])
+  strictEqual(fromConfig.rewrite(new Request({ url: '/old?x=1' })).url, '/new?x=2', 'should rewrite from config')
+})
+
 test('HrefRewriter preserves query string', async () => {
   const request = new Request({
     url: 'http://example.com/api/users?page=2'
@@ -310,3 +335,156 @@ test('ConditionalRewriter.fromConfig with no conditions applies rewriters uncond
   strictEqual(rewritten.method, 'POST', 'should rewrite method unconditionally')
   strictEqual(rewritten.headers.get('X-API-Version'), '2.0', 'should rewrite header unconditionally')
 })
+
+test('ConditionalRewriter.fromConfig with header_absent condition', async () => {
+  const rewriter = new Rewriter([{
+    conditions: [
+      { type: 'header_absent', args: ['X-Request-Id'] }
+    ],
+    rewriters: [
+      { type: 'header', args: ['X-Source', '.*', 'generated'] }
+    ]
+  }])
+
+  const missing = rewriter.rewrite(new Request({
+    url: '/api',
+    headers: { 'X-Source': 'client' }
+  }))
+  strictEqual(missing.headers.get('X-Source'), 'generated', 'should rewrite when header is absent')
+
+  const present = rewriter.rewrite(new Request({
+    url: '/api',
+    headers: { 'X-Source': 'client', 'X-Request-Id': 'abc' }
+  }))
+  strictEqual(present.headers.get('X-Source'), 'client', 'should not rewrite when header is present')
+})
+
+test('ConditionalRewriter.fromConfig with replaceAll', async () => {
+  const first = new Rewriter([{
+    rewriters: [
+      { type: 'path', args: ['/x/', '/y/'] }
+    ]
+  }])
+  strictEqual(first.rewrite(new Request({ url: '/x/a/x/b' })).url, '/y/a/x/b', 'should replace only the first match by default')
+
+  const all = new Rewriter([{
+    rewriters: [
+      { type: 'path', args: ['/x/', '/y/'], replaceAll: true }
+    ]
+  }])
+  strictEqual(all.rewrite(new Request({ url: '/x/a/x/b' })).url, '/y/a/y/b', 'should replace every match with replaceAll')
+})
+
+test('ConditionalRewriter.fromConfig with query and host conditions', async () => {
+  const rewriter = new Rewriter([{
+    conditions: [
+      { type: 'host', args: ['^api\\.'] },
+      { type: 'query_param', args: ['format', '^json$'] }
+    ],
+    rewriters: [
+      { type: 'path', args: ['^/', '/json/'] }
+    ]
+  }, {
+    operation: 'or',
+    conditions: [
+      { type: 'query', args: ['(^|&)debug(=|&|$)'] },
+      { type: 'query_param', args: ['trace'] }
+    ],
+    rewriters: [
+      { type: 'header', args: ['X-Debug', '.*', 'on'] }
+    ]
+  }])
+
+  const json = rewriter.rewrite(new Request({
+    url: '/users?format=json',
+    headers: { Host: 'api.example.com' }
+  }))
+  strictEqual(json.url, '/json/users?format=json', 'should rewrite when host and query param match')
+
+  const www = rewriter.rewrite(new Request({
+    url: '/users?format=json',
+    headers: { Host: 'www.example.com' }
+  }))
+  strictEqual(www.url, '/users?format=json', 'should not rewrite for another host')
+
+  const debug = rewriter.rewrite(new Request({
+    url: '/users?trace',
+    headers: { Host: 'www.example.com', 'X-Debug': 'off' }
+  }))
+  strictEqual(debug.headers.get('X-Debug'), 'on', 'should rewrite when any or condition matches')
+})
+
+test('ConditionalRewriter.fromConfig with query stripping rewriters', async () => {
+  const strip = new Rewriter([{
+    rewriters: [
+      { type: 'strip_query' }
+    ]
+  }])
+  strictEqual(strip.rewrite(new Request({ url: '/search?q=rust&utm_source=mail' })).url, '/search', 'should drop the whole query')
+  strictEqual(strip.rewrite(new Request({ url: '/search' })).url, '/search', 'should leave requests without a query alone')
+
+  const allow = new Rewriter([{
+    rewriters: [
+      { type: 'allow_query_params', args: ['q', 'page'] }
+    ]
+  }])
+  strictEqual(allow.rewrite(new Request({ url: '/search?page=2&utm_source=mail&q=rust' })).url, '/search?page=2&q=rust', 'should keep only allowed params in order')
+})
+
+test('ConditionalRewriter.fromConfig with named capture groups', async () => {
+  const rewriter = new Rewriter([{
+    rewriters: [
+      { type: 'path', args: ['^/item/(?P<id>\\d+)$', '/items/${id}'] }
+    ]
+  }])
+  strictEqual(rewriter.rewrite(new Request({ url: '/item/42' })).url, '/items/42', 'should substitute the named group')
+
+  throws(() => new Rewriter([{
+    rewriters: [
+      { type: 'path', args: ['^/item/(?P<id>\\d+)$', '/items/${ident}'] }
+    ]
+  }]), /unknown capture group/, 'should reject references to missing groups')
+})
+
+test('ConditionalRewriter.fromConfig with regexLimits', async () => {
+  const words = Array.from({ length: 2000 }, (_, i) => `word${i}`).join('|')
+
+  throws(() => new Rewriter([{
+    rewriters: [
+      { type: 'path', args: [`^/(${words})$`, '/'], regexLimits: { sizeLimit: 16 * 1024 } }
+    ]
+  }]), /size limit/, 'should reject patterns over the size limit')
+
+  const rewriter = new Rewriter([{
+    rewriters: [
+      { type: 'path', args: [`^/(${words})$`, '/'] }
+    ]
+  }])
+  strictEqual(rewriter.rewrite(new Request({ url: '/word42' })).url, '/', 'should accept the pattern within the default limits')
+})
+
+test('Rewriter.concat merges pipelines in order', async () => {
+  const base = new Rewriter([{
+    conditions: [
+      { type: 'path', args: ['^/api/'] }
+    ],
+    rewriters: [
+      { type: 'header', args: ['X-Base', '.*', 'applied'] }
+    ]
+  }])
+  const tenant = new Rewriter([{
+    conditions: [
+      { type: 'header', args: ['X-Base', '^applied$'] }
+    ],
+    rewriters: [
+      { type: 'path', args: ['^/api/', '/tenant-a/api/'] }
+    ]
+  }])
+
+  const rewritten = base.concat(tenant).rewrite(new Request({
+    url: '/api/users',
+    headers: { 'X-Base': '' }
+  }))
+  strictEqual(rewritten.headers.get('X-Base'), 'applied', 'should apply the base rule')
+  strictEqual(rewritten.url, '/tenant-a/api/users', 'should apply the tenant rule after the base rule')
+})